@@ -3,6 +3,7 @@
 # Define paths
 DATA_DIR="/path/to/data_dir"
 JOBS_FILE="$DATA_DIR/jobs.json"
+HISTORY_FILE="$DATA_DIR/history.json"
 PROGRAM="/path/to/my_program" 
 
 # Check if jobs.json exists
@@ -19,27 +20,46 @@ timestamp_to_date() {
     date -r "$1" +"%Y-%m-%d"
 }
 
-# Function to extract the week number from a timestamp
-timestamp_to_week() {
-    date -r "$1" +"%V"
+# Function to extract the weekday (1=Monday, 7=Sunday)
+timestamp_to_weekday() {
+    date -r "$1" +"%u"
 }
 
-# Function to extract the month from a timestamp
-timestamp_to_month() {
-    date -r "$1" +"%m"
+# Function to extract the day of the month, without the leading zero
+timestamp_to_day_of_month() {
+    echo $((10#$(date -r "$1" +"%d")))
 }
 
-# Function to extract the weekday (1=Monday, 7=Sunday)
-timestamp_to_weekday() {
-    date -r "$1" +"%u"
+# Whether the job is scheduled on the day of a timestamp, as
+# Schedule::runs_on: the days of the week go from Monday, 1, to Sunday, 7,
+# and the days after the end of a month run on its last day
+runs_on() {
+    local timestamp="$1" schedule_type="$2" day="$3"
+    case "$schedule_type" in
+        daily)
+            return 0
+            ;;
+        weekly)
+            (( day < 1 )) && day=1
+            (( day > 7 )) && day=7
+            (( $(timestamp_to_weekday "$timestamp") == day ))
+            ;;
+        monthly)
+            local day_of_month next_day_of_month
+            day_of_month=$(timestamp_to_day_of_month "$timestamp")
+            next_day_of_month=$(timestamp_to_day_of_month $((timestamp + 86400)))
+            (( day_of_month == day || (next_day_of_month == 1 && day_of_month < day) ))
+            ;;
+        *)
+            return 1
+            ;;
+    esac
 }
 
 # Get current date info
 NOW_DATE=$(timestamp_to_date "$NOW")
-NOW_WEEK=$(timestamp_to_week "$NOW")
-NOW_MONTH=$(timestamp_to_month "$NOW")
-NOW_DAY_OF_WEEK=$(date +"%u")  # 1=Monday, 7=Sunday
-NOW_DAY_OF_MONTH=$(date +"%d")
+# Midday of today, the days are walked back from it
+NOON=$(date -j -f "%Y-%m-%d %H:%M:%S" "$NOW_DATE 12:00:00" +%s)
 
 # Check if at least one job should run
 SHOULD_RUN=false
@@ -57,6 +77,22 @@ while IFS= read -r line; do
         LAST_RUN=0  # If last_run is missing, assume it's never run
     fi
 
+    # Split orders stop once complete, intraday ones run every interval
+    SPLIT=$(echo "$line" | jq -c '.command.split // empty')
+    if [[ -n "$SPLIT" ]]; then
+        if [[ $(echo "$SPLIT" | jq '(.executed_slices // 0) >= .slices or (.executed_amount // 0) >= .total_amount') == "true" ]]; then
+            continue
+        fi
+        INTERVAL=$(echo "$SPLIT" | jq -r '.interval_minutes // empty')
+        if [[ -n "$INTERVAL" ]]; then
+            if (( NOW - LAST_RUN >= INTERVAL * 60 )); then
+                SHOULD_RUN=true
+                break
+            fi
+            continue
+        fi
+    fi
+
     LAST_RUN_DATE=$(timestamp_to_date "$LAST_RUN")
 
     if [[ "$SCHEDULE_TYPE" == "daily" ]]; then
@@ -64,21 +100,52 @@ while IFS= read -r line; do
             SHOULD_RUN=true
             break
         fi
-    elif [[ "$SCHEDULE_TYPE" == "weekly" ]]; then
-        LAST_RUN_WEEK=$(timestamp_to_week "$LAST_RUN")
-        if [[ "$LAST_RUN_WEEK" != "$NOW_WEEK" ]]; then
+    elif [[ "$LAST_RUN_DATE" < "$NOW_DATE" ]]; then
+        # Same rules as Job::should_run: the job runs on its scheduled day,
+        # and one whose last run did not complete is retried until the next
+        # scheduled day
+        SCHEDULE_DAY=$(echo "$line" | jq -r '.schedule[]?.day // 0')
+        if runs_on "$NOON" "$SCHEDULE_TYPE" "$SCHEDULE_DAY"; then
             SHOULD_RUN=true
             break
         fi
-    elif [[ "$SCHEDULE_TYPE" == "monthly" ]]; then
-        LAST_RUN_MONTH=$(timestamp_to_month "$LAST_RUN")
-        if [[ "$LAST_RUN_MONTH" != "$NOW_MONTH" ]]; then
-            SHOULD_RUN=true
-            break
+
+        WAITING=$(echo "$line" | jq '
+            .last_outcome as $outcome
+            | $outcome != null and (
+                $outcome.status == "not_triggered"
+                or $outcome.status == "failed"
+                or ($outcome.status == "price_moved" and $outcome.postponed)
+                or ($outcome.status == "limit_exceeded"
+                    and ($outcome.reason.kind == "confirmation_required"
+                        or $outcome.reason.kind == "wrong_pin"))
+            )
+        ')
+        if [[ "$WAITING" == "true" ]]; then
+            # Scheduled day missed since the last run
+            for ((DAYS = 1; DAYS <= 31; DAYS++)); do
+                DAY=$((NOON - DAYS * 86400))
+                if [[ ! "$(timestamp_to_date "$DAY")" > "$LAST_RUN_DATE" ]]; then
+                    break
+                fi
+                if runs_on "$DAY" "$SCHEDULE_TYPE" "$SCHEDULE_DAY"; then
+                    SHOULD_RUN=true
+                    break 2
+                fi
+            done
         fi
     fi
 done < <(jq -c '.[]' "$JOBS_FILE")  # Read each job as a JSON object
 
+# The program also takes the net worth snapshot of the day, run it when no
+# snapshot was taken today even if no job is due
+if [[ "$SHOULD_RUN" == "false" ]]; then
+    LAST_SNAPSHOT=$(jq -r '[.snapshots[]?.date] | max // empty' "$HISTORY_FILE" 2>/dev/null)
+    if [[ "$LAST_SNAPSHOT" != "$NOW_DATE" ]]; then
+        SHOULD_RUN=true
+    fi
+fi
+
 # If a job should run, execute the Rust program
 if [[ "$SHOULD_RUN" == "true" ]]; then
     # Check if program is already running with the exact command
@@ -0,0 +1,600 @@
+use anyhow::Result;
+use bourso_api::account::AccountKind;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use tauri::{command, AppHandle, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::events::{load_events, AccountEvent, EventKind};
+use crate::order::{load_orders, OrderPassed, OrderSide};
+use crate::scheduler::HISTORY_FILE_PATH;
+use crate::snapshot::{load_snapshots, AccountSnapshot, Snapshot};
+use crate::transfer::{load_transfers, TransferPassed};
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 86_400.0;
+
+/// Money put into a scope, negative when taken out of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flow {
+    pub timestamp: i64,
+    pub amount: f64,
+}
+
+/// Value of a scope when a snapshot was taken
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Valuation {
+    pub date: NaiveDate,
+    pub timestamp: i64,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, Default, PartialEq)]
+pub struct Metrics {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub start_value: f64,
+    pub end_value: f64,
+    /// Money put in minus money taken out over the range
+    pub net_contributions: f64,
+    pub gain: f64,
+    /// Time-weighted return over the range, not annualized
+    pub twr: Option<f64>,
+    /// Money-weighted return, annualized
+    pub xirr: Option<f64>,
+    /// Annualized standard deviation of the returns between snapshots
+    pub volatility: Option<f64>,
+    /// Largest fall from a peak of the time-weighted return, as a positive
+    /// fraction
+    pub max_drawdown: f64,
+}
+
+/// Metrics of a position, an account or the whole portfolio
+#[derive(Debug, Serialize)]
+pub struct ScopePerformance {
+    pub account: Option<String>,
+    pub symbol: Option<String>,
+    pub label: String,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Performance {
+    pub portfolio: ScopePerformance,
+    pub accounts: Vec<ScopePerformance>,
+    pub positions: Vec<ScopePerformance>,
+}
+
+/// Performance of the portfolio, of its accounts and of its positions
+/// between `from` and `to`, computed from the daily snapshots and the
+/// orders, events and transfers of the history
+#[command]
+pub fn get_performance(
+    app: AppHandle,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> core::result::Result<Performance, String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let history = History::load(&store).map_err(|e| e.to_string())?;
+
+    Ok(performance(&history, from, to))
+}
+
+/// Records of the history the performance is computed from
+pub struct History {
+    pub snapshots: Vec<Snapshot>,
+    pub orders: Vec<OrderPassed>,
+    pub events: Vec<AccountEvent>,
+    pub transfers: Vec<TransferPassed>,
+}
+
+impl History {
+    fn load(store: &Store<Wry>) -> Result<Self> {
+        Ok(History {
+            snapshots: load_snapshots(store)?,
+            orders: load_orders(store)?,
+            events: load_events(store)?,
+            transfers: load_transfers(store)?,
+        })
+    }
+}
+
+/// Only the trading and savings accounts are invested, the flows of the
+/// current accounts are unknown. The transfers made on the website are
+/// unknown too and show up as gains or losses.
+fn is_invested(kind: &AccountKind) -> bool {
+    matches!(kind, AccountKind::Trading | AccountKind::Savings)
+}
+
+pub fn performance(
+    history: &History,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Performance {
+    let transfers = &history.transfers;
+    let mut snapshots: Vec<&Snapshot> = history
+        .snapshots
+        .iter()
+        .filter(|s| from.is_none_or(|from| s.date >= from) && to.is_none_or(|to| s.date <= to))
+        .collect();
+    snapshots.sort_by_key(|s| s.timestamp);
+
+    let mut invested: BTreeMap<&str, &AccountSnapshot> = BTreeMap::new();
+    for account in snapshots.iter().flat_map(|s| &s.accounts) {
+        if is_invested(&account.kind) {
+            invested.insert(&account.id, account);
+        }
+    }
+
+    let accounts = invested
+        .values()
+        .map(|account| {
+            let valuations = valuations(&snapshots, |s| {
+                s.accounts
+                    .iter()
+                    .find(|a| a.id == account.id)
+                    .map(|a| a.value)
+            });
+            let flows: Vec<Flow> = transfers
+                .iter()
+                .filter_map(|t| {
+                    let amount = if t.to == account.id {
+                        t.amount
+                    } else if t.from == account.id {
+                        -t.amount
+                    } else {
+                        return None;
+                    };
+                    Some(Flow {
+                        timestamp: t.timestamp,
+                        amount,
+                    })
+                })
+                .collect();
+
+            ScopePerformance {
+                account: Some(account.id.clone()),
+                symbol: None,
+                label: account.name.clone(),
+                metrics: metrics(&valuations, &flows),
+            }
+        })
+        .collect();
+
+    // Transfers between two invested accounts stay in the portfolio
+    let portfolio_valuations = valuations(&snapshots, |s| {
+        let values: Vec<f64> = s
+            .accounts
+            .iter()
+            .filter(|a| invested.contains_key(a.id.as_str()))
+            .map(|a| a.value)
+            .collect();
+        (!values.is_empty()).then(|| values.iter().sum())
+    });
+    let portfolio_flows: Vec<Flow> = transfers
+        .iter()
+        .filter_map(|t| {
+            let amount = match (
+                invested.contains_key(t.from.as_str()),
+                invested.contains_key(t.to.as_str()),
+            ) {
+                (false, true) => t.amount,
+                (true, false) => -t.amount,
+                _ => return None,
+            };
+            Some(Flow {
+                timestamp: t.timestamp,
+                amount,
+            })
+        })
+        .collect();
+    let portfolio = ScopePerformance {
+        account: None,
+        symbol: None,
+        label: "Portfolio".to_string(),
+        metrics: metrics(&portfolio_valuations, &portfolio_flows),
+    };
+
+    Performance {
+        portfolio,
+        accounts,
+        positions: positions(&snapshots, &history.orders, &history.events),
+    }
+}
+
+/// The positions are valued with the positions of the trading summary kept
+/// in the snapshots, and fed by the orders of the history. The dividends
+/// are taken out of the position, the fees recorded apart are put in.
+fn positions(
+    snapshots: &[&Snapshot],
+    orders: &[OrderPassed],
+    events: &[AccountEvent],
+) -> Vec<ScopePerformance> {
+    let mut keys: BTreeSet<(String, String)> = orders
+        .iter()
+        .map(|o| (o.args.account.clone(), o.args.symbol.clone()))
+        .collect();
+    for account in snapshots.iter().flat_map(|s| &s.accounts) {
+        for position in &account.positions {
+            keys.insert((account.id.clone(), position.symbol.clone()));
+        }
+    }
+
+    keys.into_iter()
+        .filter_map(|(account, symbol)| {
+            let valuations = valuations(snapshots, |s| {
+                let snapshot = s.accounts.iter().find(|a| a.id == account)?;
+                // The summary could not be fetched when the account has
+                // neither cash nor positions
+                if snapshot.cash.is_none() && snapshot.positions.is_empty() {
+                    return None;
+                }
+                Some(
+                    snapshot
+                        .positions
+                        .iter()
+                        .filter(|p| p.symbol == symbol)
+                        .map(|p| p.value)
+                        .sum(),
+                )
+            });
+            if valuations.is_empty() {
+                return None;
+            }
+
+            let flows: Vec<Flow> = orders
+                .iter()
+                .filter(|o| o.args.account == account && o.args.symbol == symbol)
+                .filter_map(|o| {
+                    let gross = o.executed_quantity() as f64 * o.price;
+                    let amount = match o.args.side {
+                        OrderSide::Buy => gross + o.fees,
+                        OrderSide::Sell => -(gross - o.fees),
+                    };
+                    Some(Flow {
+                        timestamp: o.timestamp?,
+                        amount,
+                    })
+                })
+                .chain(
+                    events
+                        .iter()
+                        .filter(|e| {
+                            e.account == account && e.symbol.as_deref() == Some(symbol.as_str())
+                        })
+                        .filter_map(|e| {
+                            let amount = match e.kind {
+                                EventKind::Dividend { .. } => -e.net_dividend()?,
+                                EventKind::Fee { amount } => amount,
+                                EventKind::Split { .. } => return None,
+                            };
+                            Some(Flow {
+                                timestamp: e.timestamp,
+                                amount,
+                            })
+                        }),
+                )
+                .collect();
+
+            Some(ScopePerformance {
+                label: symbol.clone(),
+                account: Some(account),
+                symbol: Some(symbol),
+                metrics: metrics(&valuations, &flows),
+            })
+        })
+        .collect()
+}
+
+fn valuations(snapshots: &[&Snapshot], value: impl Fn(&Snapshot) -> Option<f64>) -> Vec<Valuation> {
+    snapshots
+        .iter()
+        .filter_map(|s| {
+            Some(Valuation {
+                date: s.date,
+                timestamp: s.timestamp,
+                value: value(s)?,
+            })
+        })
+        .collect()
+}
+
+/// Metrics of a scope valued at `valuations`, sorted chronologically. Only
+/// the flows after the first valuation and until the last one are counted.
+pub fn metrics(valuations: &[Valuation], flows: &[Flow]) -> Metrics {
+    let (Some(first), Some(last)) = (valuations.first(), valuations.last()) else {
+        return Metrics::default();
+    };
+    let flows: Vec<Flow> = flows
+        .iter()
+        .filter(|f| f.timestamp > first.timestamp && f.timestamp <= last.timestamp)
+        .copied()
+        .collect();
+    let net_contributions: f64 = flows.iter().map(|f| f.amount).sum();
+
+    let returns = period_returns(valuations, &flows);
+
+    let twr =
+        (!returns.is_empty()).then(|| returns.iter().map(|(_, r)| 1.0 + r).product::<f64>() - 1.0);
+
+    Metrics {
+        start_date: Some(first.date),
+        end_date: Some(last.date),
+        start_value: first.value,
+        end_value: last.value,
+        net_contributions,
+        gain: last.value - first.value - net_contributions,
+        twr,
+        xirr: xirr(first, last, &flows),
+        volatility: volatility(&returns),
+        max_drawdown: max_drawdown(&returns),
+    }
+}
+
+/// Return of each period between two valuations, with its length in
+/// seconds. The flows are weighted by the time they were invested during the
+/// period (Modified Dietz), so that a position opened or sold out during a
+/// period gets a return too.
+fn period_returns(valuations: &[Valuation], flows: &[Flow]) -> Vec<(i64, f64)> {
+    valuations
+        .windows(2)
+        .filter_map(|pair| {
+            let (start, end) = (pair[0], pair[1]);
+            let length = end.timestamp - start.timestamp;
+            if length <= 0 {
+                return None;
+            }
+
+            let flows: Vec<&Flow> = flows
+                .iter()
+                .filter(|f| f.timestamp > start.timestamp && f.timestamp <= end.timestamp)
+                .collect();
+            let flow: f64 = flows.iter().map(|f| f.amount).sum();
+            let invested: f64 = start.value
+                + flows
+                    .iter()
+                    .map(|f| f.amount * (end.timestamp - f.timestamp) as f64 / length as f64)
+                    .sum::<f64>();
+            if invested <= 0.0 {
+                return None;
+            }
+
+            Some((length, (end.value - start.value - flow) / invested))
+        })
+        .collect()
+}
+
+fn volatility(returns: &[(i64, f64)]) -> Option<f64> {
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let count = returns.len() as f64;
+    let mean = returns.iter().map(|(_, r)| r).sum::<f64>() / count;
+    let variance = returns.iter().map(|(_, r)| (r - mean).powi(2)).sum::<f64>() / (count - 1.0);
+    let period = returns
+        .iter()
+        .map(|(length, _)| *length as f64)
+        .sum::<f64>()
+        / count;
+    if period <= 0.0 {
+        return None;
+    }
+
+    Some(variance.sqrt() * (SECONDS_PER_YEAR / period).sqrt())
+}
+
+fn max_drawdown(returns: &[(i64, f64)]) -> f64 {
+    let mut index = 1.0;
+    let mut peak = 1.0;
+    let mut drawdown: f64 = 0.0;
+
+    for (_, r) in returns {
+        index *= 1.0 + r;
+        peak = f64::max(peak, index);
+        drawdown = drawdown.max(1.0 - index / peak);
+    }
+
+    drawdown
+}
+
+/// Annual rate at which the starting value and the flows grow to the last
+/// value, solved with Newton's method and bisection as a fallback
+fn xirr(first: &Valuation, last: &Valuation, flows: &[Flow]) -> Option<f64> {
+    if last.timestamp <= first.timestamp {
+        return None;
+    }
+
+    // Cash flows of the investor: what is put in is negative
+    let cash_flows: Vec<(f64, f64)> = [(first.timestamp, -first.value)]
+        .into_iter()
+        .chain(flows.iter().map(|f| (f.timestamp, -f.amount)))
+        .chain([(last.timestamp, last.value)])
+        .filter(|(_, amount)| *amount != 0.0)
+        .map(|(timestamp, amount)| {
+            (
+                (timestamp - first.timestamp) as f64 / SECONDS_PER_YEAR,
+                amount,
+            )
+        })
+        .collect();
+    if !cash_flows.iter().any(|(_, a)| *a > 0.0) || !cash_flows.iter().any(|(_, a)| *a < 0.0) {
+        return None;
+    }
+
+    let npv = |rate: f64| -> f64 {
+        cash_flows
+            .iter()
+            .map(|(years, amount)| amount / (1.0 + rate).powf(*years))
+            .sum()
+    };
+    let derivative = |rate: f64| -> f64 {
+        cash_flows
+            .iter()
+            .map(|(years, amount)| -years * amount / (1.0 + rate).powf(years + 1.0))
+            .sum()
+    };
+
+    let mut rate = 0.1;
+    for _ in 0..100 {
+        let value = npv(rate);
+        if value.abs() < 1e-9 {
+            return Some(rate);
+        }
+        let slope = derivative(rate);
+        if slope == 0.0 {
+            break;
+        }
+        let next = rate - value / slope;
+        if !next.is_finite() || next <= -1.0 {
+            break;
+        }
+        if (next - rate).abs() < 1e-12 {
+            return Some(next);
+        }
+        rate = next;
+    }
+
+    // The NPV decreases with the rate when the money is put in first
+    let (mut low, mut high) = (-0.9999, 100.0);
+    if npv(low).signum() == npv(high).signum() {
+        return None;
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if npv(mid).signum() == npv(low).signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Some((low + high) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{OrderArgs, OrderSource};
+    use crate::snapshot::PositionValue;
+
+    const DAY: i64 = 86_400;
+
+    fn valuation(day: i64, value: f64) -> Valuation {
+        Valuation {
+            date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Duration::days(day),
+            timestamp: day * DAY,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        // +10%, then 1000 put in, then -20%
+        let valuations = vec![
+            valuation(0, 1_000.0),
+            valuation(1, 1_100.0),
+            valuation(2, 1_680.0),
+        ];
+        let flows = vec![Flow {
+            timestamp: DAY + 1,
+            amount: 1_000.0,
+        }];
+
+        let metrics = metrics(&valuations, &flows);
+
+        assert_eq!(metrics.net_contributions, 1_000.0);
+        assert!((metrics.gain - -320.0).abs() < 1e-9);
+        // 1.1 * 0.8 - 1, the contribution does not weigh on the TWR
+        assert!((metrics.twr.unwrap() - -0.12).abs() < 1e-4);
+        assert!((metrics.max_drawdown - 0.2).abs() < 1e-4);
+        assert!(metrics.volatility.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_xirr() {
+        // 1000 growing to 1100 in a year
+        let first = valuation(0, 1_000.0);
+        let last = Valuation {
+            timestamp: SECONDS_PER_YEAR as i64,
+            ..valuation(365, 1_100.0)
+        };
+
+        assert!((xirr(&first, &last, &[]).unwrap() - 0.1).abs() < 1e-6);
+
+        // Nothing was put in
+        assert_eq!(xirr(&valuation(0, 0.0), &valuation(1, 0.0), &[]), None);
+    }
+
+    #[test]
+    fn test_performance() {
+        let account = |value: f64, positions: Vec<PositionValue>| AccountSnapshot {
+            id: "pea".to_string(),
+            name: "PEA".to_string(),
+            kind: AccountKind::Trading,
+            value,
+            cash: Some(value - positions.iter().map(|p| p.value).sum::<f64>()),
+            positions,
+        };
+        let position = |value: f64| PositionValue {
+            symbol: "1rTCW8".to_string(),
+            quantity: 10.0,
+            price: value / 10.0,
+            value,
+        };
+        let snapshots = vec![
+            Snapshot {
+                date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                timestamp: 0,
+                accounts: vec![account(500.0, vec![])],
+            },
+            Snapshot {
+                date: NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+                timestamp: DAY,
+                accounts: vec![account(1_500.0, vec![position(1_000.0)])],
+            },
+            Snapshot {
+                date: NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+                timestamp: 2 * DAY,
+                accounts: vec![account(1_600.0, vec![position(1_100.0)])],
+            },
+        ];
+        let orders = vec![OrderPassed::new(
+            "a".to_string(),
+            100.0,
+            Some(DAY - 10),
+            OrderArgs {
+                account: "pea".to_string(),
+                symbol: "1rTCW8".to_string(),
+                quantity: Some(10),
+                amount: None,
+                side: OrderSide::Buy,
+            },
+            OrderSource::App,
+        )];
+        let mut transfer = TransferPassed::new("checking", "pea", 1_000.0, None);
+        transfer.timestamp = DAY - 20;
+
+        let history = History {
+            snapshots,
+            orders,
+            events: vec![],
+            transfers: vec![transfer],
+        };
+
+        let performance = performance(&history, None, None);
+
+        assert_eq!(performance.portfolio.metrics.net_contributions, 1_000.0);
+        assert!((performance.portfolio.metrics.gain - 100.0).abs() < 1e-9);
+        assert_eq!(performance.accounts.len(), 1);
+
+        let position = &performance.positions[0];
+        assert_eq!(position.symbol.as_deref(), Some("1rTCW8"));
+        // Bought at 1000 the first day, worth 1100 the next one
+        assert!((position.metrics.twr.unwrap() - 0.1).abs() < 1e-9);
+
+        let last_day = super::performance(&history, NaiveDate::from_ymd_opt(2024, 1, 3), None);
+        assert_eq!(last_day.portfolio.metrics.twr, None);
+    }
+}
@@ -0,0 +1,311 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::Serialize;
+use tauri::{async_runtime::Mutex, command, State};
+
+use crate::order::{qty_for_budget, OrderArgs, OrderSide};
+use crate::scheduler::{Command, Job, JobOutcome, Schedule};
+use crate::ticks::{get_daily_closes, DailyClose};
+use crate::BoursoState;
+
+/// Value of the DCA and of the lump-sum investment at the close of a day
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BacktestPoint {
+    pub date: NaiveDate,
+    pub price: f64,
+    pub shares: u64,
+    /// Money spent on shares so far
+    pub invested: f64,
+    pub value: f64,
+    pub lump_sum_value: f64,
+}
+
+/// Order placed by a simulated run of the job
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BacktestRun {
+    pub date: NaiveDate,
+    pub price: f64,
+    pub quantity: u64,
+    /// Cash carried over to the next run
+    pub leftover: f64,
+}
+
+/// The total spent by the DCA invested at the close of the first day
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LumpSum {
+    pub price: f64,
+    pub shares: u64,
+    /// Cash that could not buy a whole share
+    pub cash: f64,
+    pub value: f64,
+    pub gain: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Backtest {
+    pub points: Vec<BacktestPoint>,
+    pub runs: Vec<BacktestRun>,
+    pub shares: u64,
+    pub invested: f64,
+    pub average_cost: f64,
+    pub value: f64,
+    pub gain: f64,
+    pub lump_sum: LumpSum,
+}
+
+/// Replay a job over the end of day closes of its symbol between `from` and
+/// `to`, as if it had been created the day before `from`
+#[command]
+pub async fn backtest_dca(
+    state: State<'_, Mutex<BoursoState>>,
+    schedule: Schedule,
+    order: OrderArgs,
+    from: NaiveDate,
+    to: Option<NaiveDate>,
+    carry_over: Option<bool>,
+) -> core::result::Result<Backtest, String> {
+    let today = Local::now().date_naive();
+    // The ticks are counted back from today
+    let length = (today - from).num_days().clamp(1, u16::MAX as i64) as u16;
+
+    let closes = {
+        let state = state.lock().await;
+        get_daily_closes(&state.client, &order.symbol, length)
+            .await
+            .map_err(|e| e.to_string())?
+    };
+
+    backtest(
+        &closes,
+        schedule,
+        order,
+        from,
+        to.unwrap_or(today),
+        carry_over.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn noon(date: NaiveDate) -> Option<DateTime<chrono::Utc>> {
+    let local = Local
+        .from_local_datetime(&date.and_hms_opt(12, 0, 0)?)
+        .earliest()?;
+    DateTime::from_timestamp(local.timestamp(), 0)
+}
+
+/// Replays `Job::should_run` day by day: the job runs on its scheduled day,
+/// or on the next trading day when the market is closed, and buys at the
+/// close of that day. The quantity of amount-based orders follows
+/// `qty_at_price`. Fees are ignored.
+pub fn backtest(
+    closes: &[DailyClose],
+    schedule: Schedule,
+    order: OrderArgs,
+    from: NaiveDate,
+    to: NaiveDate,
+    carry_over: bool,
+) -> Result<Backtest> {
+    if order.side != OrderSide::Buy {
+        return Err(anyhow::anyhow!("Only buy orders can be backtested"));
+    }
+    let closes: Vec<&DailyClose> = closes
+        .iter()
+        .filter(|c| c.date >= from && c.date <= to)
+        .collect();
+    let (Some(first), Some(last)) = (closes.first(), closes.last()) else {
+        return Err(anyhow::anyhow!(
+            "No price for {} between {} and {}",
+            order.symbol,
+            from,
+            to
+        ));
+    };
+
+    let mut job = Job::new(schedule, Command::Order(order.clone()));
+    let mut last_run = noon(from.pred_opt().unwrap_or(from))
+        .ok_or_else(|| anyhow::anyhow!("Invalid date {}", from))?;
+    let mut date = from;
+    let mut closes_iter = closes.iter().peekable();
+
+    let mut runs = vec![];
+    let mut points = vec![];
+    let mut shares = 0;
+    let mut invested = 0.0;
+    let mut leftover = 0.0;
+
+    while date <= to {
+        let now = noon(date).ok_or_else(|| anyhow::anyhow!("Invalid date {}", date))?;
+        let close = closes_iter.next_if(|c| c.date == date);
+
+        if job.should_run(now, last_run) {
+            match close {
+                Some(close) => {
+                    let carried = if carry_over { leftover } else { 0.0 };
+                    let quantity = order.qty_at_price(close.close, carried)?;
+                    let cost = quantity as f64 * close.close;
+                    if let Some(amount) = order.amount {
+                        leftover = (amount + carried - cost).max(0.0);
+                    }
+
+                    shares += quantity;
+                    invested += cost;
+                    runs.push(BacktestRun {
+                        date,
+                        price: close.close,
+                        quantity,
+                        leftover: if carry_over { leftover } else { 0.0 },
+                    });
+                    last_run = now;
+                    job.last_outcome = Some(JobOutcome::Executed {
+                        quantity,
+                        price: close.close,
+                    });
+                }
+                // The scheduler retries on the next days
+                None => {
+                    job.last_outcome = Some(JobOutcome::Failed {
+                        error: "Market is closed".to_string(),
+                    })
+                }
+            }
+        }
+
+        if let Some(close) = close {
+            points.push(BacktestPoint {
+                date,
+                price: close.close,
+                shares,
+                invested,
+                value: shares as f64 * close.close,
+                lump_sum_value: 0.0,
+            });
+        }
+
+        date = match date.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    let lump_shares = qty_for_budget(invested, first.close);
+    let lump_cash = invested - lump_shares as f64 * first.close;
+    for point in &mut points {
+        point.lump_sum_value = lump_shares as f64 * point.price + lump_cash;
+    }
+    let lump_value = lump_shares as f64 * last.close + lump_cash;
+
+    let value = shares as f64 * last.close;
+    Ok(Backtest {
+        points,
+        runs,
+        shares,
+        invested,
+        average_cost: if shares > 0 {
+            invested / shares as f64
+        } else {
+            0.0
+        },
+        value,
+        gain: value - invested,
+        lump_sum: LumpSum {
+            price: first.close,
+            shares: lump_shares,
+            cash: lump_cash,
+            value: lump_value,
+            gain: lump_value - invested,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn closes() -> Vec<DailyClose> {
+        // Trading days of January 2024, the price goes up by 1 each day
+        (1..=31)
+            .filter_map(|day| NaiveDate::from_ymd_opt(2024, 1, day))
+            .filter(|date| !matches!(date.weekday().number_from_monday(), 6 | 7))
+            .enumerate()
+            .map(|(index, date)| DailyClose {
+                date,
+                close: 10.0 + index as f64,
+            })
+            .collect()
+    }
+
+    fn order(amount: Option<f64>, quantity: Option<u64>) -> OrderArgs {
+        OrderArgs {
+            account: "1234567890".to_string(),
+            symbol: "1rTCW8".to_string(),
+            quantity,
+            amount,
+            side: OrderSide::Buy,
+        }
+    }
+
+    #[test]
+    fn test_weekly_backtest() {
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+
+        // Every Monday, the 1st of January 2024 is a Monday
+        let result = backtest(
+            &closes(),
+            Schedule::Weekly { day: 1 },
+            order(None, Some(2)),
+            from,
+            to,
+            false,
+        )
+        .unwrap();
+
+        let dates: Vec<u32> = result.runs.iter().map(|r| r.date.day()).collect();
+        assert_eq!(dates, vec![1, 8, 15, 22, 29]);
+        assert_eq!(result.shares, 10);
+        // 2 * (10 + 15 + 20 + 25 + 30)
+        assert_eq!(result.invested, 200.0);
+        assert_eq!(result.average_cost, 20.0);
+        assert_eq!(result.value, 10.0 * 32.0);
+        // Everything bought at 10 on the first day
+        assert_eq!(result.lump_sum.shares, 20);
+        assert_eq!(result.lump_sum.value, 640.0);
+        assert_eq!(result.points.len(), 23);
+    }
+
+    #[test]
+    fn test_monthly_backtest_carry_over() {
+        let from = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+
+        // Scheduled on a Saturday, runs on the Monday after at 15
+        let result = backtest(
+            &closes(),
+            Schedule::Monthly { day: 6 },
+            order(Some(30.0), None),
+            from,
+            to,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(result.runs.len(), 1);
+        assert_eq!(result.runs[0].price, 15.0);
+        assert_eq!(result.runs[0].quantity, 2);
+        assert_eq!(result.runs[0].leftover, 0.0);
+
+        assert!(backtest(
+            &closes(),
+            Schedule::Daily,
+            OrderArgs {
+                side: OrderSide::Sell,
+                ..order(None, Some(1))
+            },
+            from,
+            to,
+            false,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! Amount invested across several securities in one run, e.g. 300€ split
+//! 60/30/10 across three ETFs
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::order::{OrderArgs, OrderSide};
+
+/// Share of an amount invested in a security
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Weight {
+    pub symbol: String,
+    /// Relative weight, the weights do not need to add up to 1 or 100
+    pub weight: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Basket {
+    pub account: String,
+    /// Total amount invested on each run, in euros
+    pub amount: f64,
+    pub legs: Vec<Weight>,
+}
+
+impl Basket {
+    pub fn validate(&self) -> Result<()> {
+        validate_weights(&self.legs)?;
+        if self.amount <= 0.0 {
+            return Err(anyhow::anyhow!("The amount of the basket must be positive"));
+        }
+
+        Ok(())
+    }
+
+    pub fn orders(&self) -> Vec<OrderArgs> {
+        split_amount(&self.account, &self.legs, self.amount)
+    }
+}
+
+pub fn validate_weights(weights: &[Weight]) -> Result<()> {
+    if weights.is_empty() || weights.iter().any(|w| w.weight <= 0.0) {
+        return Err(anyhow::anyhow!(
+            "At least one symbol is needed and every weight must be positive"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Amount-based buy orders splitting `amount` across the `weights`
+pub fn split_amount(account: &str, weights: &[Weight], amount: f64) -> Vec<OrderArgs> {
+    let total: f64 = weights.iter().map(|w| w.weight).sum();
+    if total <= 0.0 {
+        return vec![];
+    }
+
+    weights
+        .iter()
+        .map(|w| OrderArgs {
+            account: account.to_string(),
+            symbol: w.symbol.clone(),
+            quantity: None,
+            amount: Some(amount * w.weight / total),
+            side: OrderSide::Buy,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basket_orders() {
+        let weight = |symbol: &str, weight: f64| Weight {
+            symbol: symbol.to_string(),
+            weight,
+        };
+        let basket = Basket {
+            account: "1234567890".to_string(),
+            amount: 300.0,
+            legs: vec![
+                weight("1rTCW8", 60.0),
+                weight("1rPAEEM", 30.0),
+                weight("1rPCSPX", 10.0),
+            ],
+        };
+        assert!(basket.validate().is_ok());
+
+        let amounts: Vec<f64> = basket.orders().iter().map(|o| o.amount.unwrap()).collect();
+        assert_eq!(amounts, vec![180.0, 90.0, 30.0]);
+
+        let empty = Basket {
+            legs: vec![weight("1rTCW8", 0.0)],
+            ..basket
+        };
+        assert!(empty.validate().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use tauri::{async_runtime::Mutex, command, AppHandle, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::analytics::{metrics, Flow, Metrics, Valuation};
+use crate::events::{load_events, AccountEvent, EventKind};
+use crate::history::order_date;
+use crate::order::{load_orders, OrderPassed, OrderSide};
+use crate::scheduler::HISTORY_FILE_PATH;
+use crate::ticks::{get_daily_closes, DailyClose};
+use crate::BoursoState;
+
+/// Values at the close of a trading day of the benchmark
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BenchmarkPoint {
+    pub date: NaiveDate,
+    /// Money put in minus money taken out so far
+    pub net_contributions: f64,
+    pub portfolio: f64,
+    pub benchmark: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchmarkComparison {
+    pub symbol: String,
+    pub points: Vec<BenchmarkPoint>,
+    pub portfolio: Metrics,
+    pub benchmark: Metrics,
+    /// Value of the portfolio minus value of the benchmark
+    pub outperformance: f64,
+    /// Difference of the time-weighted returns
+    pub outperformance_twr: Option<f64>,
+    /// Securities of the history without closes, valued at the price of
+    /// their last order
+    pub unpriced: Vec<String>,
+}
+
+/// Compare the orders of the history with the same cash flows invested in
+/// the benchmark `symbol`
+#[command]
+pub async fn compare_benchmark(
+    app: AppHandle,
+    state: State<'_, Mutex<BoursoState>>,
+    symbol: String,
+    account: Option<String>,
+) -> core::result::Result<BenchmarkComparison, String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let in_account = |id: &String| account.as_ref().is_none_or(|account| id == account);
+    let orders: Vec<OrderPassed> = load_orders(&store)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|o| in_account(&o.args.account))
+        .collect();
+    let events: Vec<AccountEvent> = load_events(&store)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|e| in_account(&e.account))
+        .collect();
+
+    let state = state.lock().await;
+    let (closes, benchmark) = fetch_closes(&state.client, &orders, &symbol)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(compare(&orders, &events, &closes, &symbol, &benchmark))
+}
+
+async fn fetch_closes(
+    web_client: &bourso_api::client::BoursoWebClient,
+    orders: &[OrderPassed],
+    symbol: &str,
+) -> Result<(BTreeMap<String, Vec<DailyClose>>, Vec<DailyClose>)> {
+    let first = orders
+        .iter()
+        .filter_map(|o| o.timestamp.and_then(order_date))
+        .min()
+        .ok_or_else(|| anyhow::anyhow!("No order in the history"))?;
+    // The ticks are counted back from today
+    let length = (Local::now().date_naive() - first).num_days() + 1;
+    let length = length.clamp(1, u16::MAX as i64) as u16;
+
+    let symbols: BTreeSet<&str> = orders.iter().map(|o| o.args.symbol.as_str()).collect();
+    let mut closes = BTreeMap::new();
+    for held in symbols {
+        // A delisted or renamed security should not prevent the comparison,
+        // `compare` values it at the price of its last order
+        match get_daily_closes(web_client, held, length).await {
+            Ok(held_closes) => {
+                closes.insert(held.to_string(), held_closes);
+            }
+            Err(e) => log::warn!("Error while getting closes of {}: {:?}", held, e),
+        }
+    }
+
+    Ok((closes, get_daily_closes(web_client, symbol, length).await?))
+}
+
+/// Cash an order puts into the portfolio, negative for a sale
+fn order_flow(order: &OrderPassed) -> f64 {
+    let gross = order.executed_quantity() as f64 * order.price;
+    match order.args.side {
+        OrderSide::Buy => gross + order.fees,
+        OrderSide::Sell => -(gross - order.fees),
+    }
+}
+
+/// Last close on or before `date`
+fn close_on(closes: &[DailyClose], date: NaiveDate) -> Option<f64> {
+    closes
+        .iter()
+        .rev()
+        .find(|c| c.date <= date)
+        .map(|c| c.close)
+}
+
+fn end_of_day(date: NaiveDate) -> i64 {
+    date.and_hms_opt(23, 59, 59)
+        .and_then(|datetime| Local.from_local_datetime(&datetime).latest())
+        .map(|datetime| datetime.timestamp())
+        .unwrap_or_default()
+}
+
+/// Replay the orders over the trading days of the benchmark. The cash of
+/// each order buys, or sells, fractional shares of the benchmark at the
+/// close of its day. A security is valued at its last close, or at the price
+/// of its last order before its first close. The splits change the shares
+/// held and the dividends are kept as cash in the portfolio.
+pub fn compare(
+    orders: &[OrderPassed],
+    events: &[AccountEvent],
+    closes: &BTreeMap<String, Vec<DailyClose>>,
+    symbol: &str,
+    benchmark: &[DailyClose],
+) -> BenchmarkComparison {
+    let mut orders: Vec<(NaiveDate, &OrderPassed)> = orders
+        .iter()
+        .filter(|o| o.executed_quantity() > 0)
+        .filter_map(|o| Some((o.timestamp.and_then(order_date)?, o)))
+        .collect();
+    orders.sort_by_key(|(_, o)| o.timestamp);
+
+    let start = orders.first().map(|(date, _)| *date);
+    let days: Vec<&DailyClose> = benchmark
+        .iter()
+        .filter(|c| start.is_some_and(|start| c.date >= start))
+        .collect();
+
+    let mut pending = orders.iter().peekable();
+    let mut quantities: BTreeMap<&str, i64> = BTreeMap::new();
+    let mut order_prices: BTreeMap<&str, f64> = BTreeMap::new();
+    let mut events: Vec<(NaiveDate, &AccountEvent)> = events
+        .iter()
+        .filter_map(|e| Some((order_date(e.timestamp)?, e)))
+        .collect();
+    events.sort_by_key(|(_, e)| e.timestamp);
+    let mut pending_events = events.iter().peekable();
+    let mut dividends = 0.0;
+
+    let mut benchmark_shares = 0.0;
+    let mut net_contributions = 0.0;
+    let mut flows = vec![];
+    let mut points = vec![];
+
+    for day in days {
+        while let Some((_, order)) = pending.next_if(|(date, _)| *date <= day.date) {
+            let quantity = order.executed_quantity() as i64;
+            let held = quantities.entry(&order.args.symbol).or_default();
+            match order.args.side {
+                OrderSide::Buy => *held += quantity,
+                OrderSide::Sell => *held = (*held - quantity).max(0),
+            }
+            order_prices.insert(&order.args.symbol, order.price);
+
+            let flow = order_flow(order);
+            net_contributions += flow;
+            benchmark_shares = f64::max(benchmark_shares + flow / day.close, 0.0);
+            // At the close, when the benchmark is bought
+            flows.push(Flow {
+                timestamp: end_of_day(day.date),
+                amount: flow,
+            });
+        }
+
+        while let Some((_, event)) = pending_events.next_if(|(date, _)| *date <= day.date) {
+            let Some(symbol) = event.symbol.as_deref() else {
+                continue;
+            };
+            match event.kind {
+                EventKind::Dividend { .. } => dividends += event.net_dividend().unwrap_or_default(),
+                EventKind::Split { from, to } => {
+                    if let Some(held) = quantities.get_mut(symbol) {
+                        *held = *held * to as i64 / from as i64;
+                    }
+                }
+                EventKind::Fee { .. } => (),
+            }
+        }
+
+        let portfolio = dividends
+            + quantities
+                .iter()
+                .map(|(held, quantity)| {
+                    let price = closes
+                        .get(*held)
+                        .and_then(|closes| close_on(closes, day.date))
+                        .or(order_prices.get(held).copied())
+                        .unwrap_or_default();
+                    *quantity as f64 * price
+                })
+                .sum::<f64>();
+
+        points.push(BenchmarkPoint {
+            date: day.date,
+            net_contributions,
+            portfolio,
+            benchmark: benchmark_shares * day.close,
+        });
+    }
+
+    let valuations = |value: fn(&BenchmarkPoint) -> f64| -> Vec<Valuation> {
+        points
+            .iter()
+            .map(|p| Valuation {
+                date: p.date,
+                timestamp: end_of_day(p.date),
+                value: value(p),
+            })
+            .collect()
+    };
+    // The first valuation already includes the orders of its day
+    let portfolio = metrics(&valuations(|p| p.portfolio), &flows);
+    let benchmark = metrics(&valuations(|p| p.benchmark), &flows);
+
+    let unpriced = orders
+        .iter()
+        .map(|(_, o)| o.args.symbol.as_str())
+        .filter(|held| !closes.contains_key(*held))
+        .collect::<BTreeSet<&str>>()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+    BenchmarkComparison {
+        symbol: symbol.to_string(),
+        unpriced,
+        outperformance: portfolio.end_value - benchmark.end_value,
+        outperformance_twr: portfolio.twr.zip(benchmark.twr).map(|(p, b)| p - b),
+        points,
+        portfolio,
+        benchmark,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{OrderArgs, OrderSource};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    fn order(day: u32, symbol: &str, side: OrderSide, quantity: u64, price: f64) -> OrderPassed {
+        let timestamp = Local
+            .from_local_datetime(&date(day).and_hms_opt(10, 0, 0).unwrap())
+            .unwrap()
+            .timestamp();
+        OrderPassed::new(
+            format!("{}-{}", symbol, day),
+            price,
+            Some(timestamp),
+            OrderArgs {
+                account: "1234567890".to_string(),
+                symbol: symbol.to_string(),
+                quantity: Some(quantity),
+                amount: None,
+                side,
+            },
+            OrderSource::App,
+        )
+    }
+
+    fn closes(prices: &[(u32, f64)]) -> Vec<DailyClose> {
+        prices
+            .iter()
+            .map(|(day, close)| DailyClose {
+                date: date(*day),
+                close: *close,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let orders = vec![
+            order(4, "1rTCW8", OrderSide::Buy, 10, 10.0),
+            order(6, "1rTCW8", OrderSide::Buy, 10, 12.0),
+        ];
+        let held = BTreeMap::from([(
+            "1rTCW8".to_string(),
+            closes(&[(4, 10.0), (5, 11.0), (6, 12.0), (7, 15.0)]),
+        )]);
+        // The benchmark goes up by 10% a day
+        let benchmark = closes(&[(1, 50.0), (4, 100.0), (5, 110.0), (6, 121.0), (7, 133.1)]);
+
+        let comparison = compare(&orders, &[], &held, "1rCW8", &benchmark);
+
+        // The days before the first order are ignored
+        assert_eq!(comparison.points.len(), 4);
+        let last = comparison.points.last().unwrap();
+        assert_eq!(last.net_contributions, 220.0);
+        assert_eq!(last.portfolio, 300.0);
+        // 1 share bought at 100, 120 / 121 bought at 121
+        assert!((last.benchmark - (133.1 + 120.0 * 1.1)).abs() < 1e-9);
+        assert!((comparison.outperformance - (300.0 - 265.1)).abs() < 1e-9);
+
+        // 10 shares at 10 up to 15 with 10 more bought at 12 on the way
+        let twr = comparison.portfolio.twr.unwrap();
+        assert!((twr - (1.2 * 15.0 / 12.0 - 1.0)).abs() < 1e-6);
+        assert!((comparison.benchmark.twr.unwrap() - (1.1f64.powi(3) - 1.0)).abs() < 1e-6);
+        assert!(comparison.outperformance_twr.unwrap() > 0.0);
+        assert!(comparison.unpriced.is_empty());
+    }
+
+    #[test]
+    fn test_unpriced_security() {
+        let orders = vec![
+            order(4, "1rTCW8", OrderSide::Buy, 10, 10.0),
+            order(5, "1rDELISTED", OrderSide::Buy, 2, 50.0),
+        ];
+        let held = BTreeMap::from([(
+            "1rTCW8".to_string(),
+            closes(&[(4, 10.0), (5, 11.0), (6, 12.0)]),
+        )]);
+        let benchmark = closes(&[(4, 100.0), (5, 100.0), (6, 100.0)]);
+
+        let comparison = compare(&orders, &[], &held, "1rCW8", &benchmark);
+
+        assert_eq!(comparison.unpriced, vec!["1rDELISTED".to_string()]);
+        // Valued at the price of its order
+        assert_eq!(comparison.points.last().unwrap().portfolio, 120.0 + 100.0);
+    }
+}
@@ -1,8 +1,14 @@
 use anyhow::Result;
+use chrono::NaiveDate;
+use std::path::Path;
 use tauri::App;
 use tauri_plugin_cli::Matches;
+use tauri_plugin_store::StoreExt;
 
-use crate::scheduler;
+use crate::export::{export_orders, ExportFormat};
+use crate::history::OrderFilter;
+use crate::order::load_orders;
+use crate::scheduler::{self, HISTORY_FILE_PATH};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Order {
@@ -28,9 +34,48 @@ pub async fn cli_handler(app: &mut App, matches: Matches) -> Result<()> {
                     }
                 }
             }
+            "history" => {
+                if let Some(history_subcommand) = subcommand.matches.subcommand {
+                    if history_subcommand.name == "export" {
+                        return export_history(app, &history_subcommand.matches);
+                    }
+                }
+            }
             "transfer" => todo!(),
             _ => {}
         }
     }
     Ok(())
 }
+
+fn arg<'a>(matches: &'a Matches, name: &str) -> Option<&'a str> {
+    matches.args.get(name).and_then(|arg| arg.value.as_str())
+}
+
+fn date_arg(matches: &Matches, name: &str) -> Result<Option<NaiveDate>> {
+    arg(matches, name)
+        .map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --{} date, expected YYYY-MM-DD: {}", name, e))
+}
+
+/// `history export --output <path> [--format csv|json|ofx] [--from] [--to]
+/// [--account] [--symbol]`
+fn export_history(app: &mut App, matches: &Matches) -> Result<()> {
+    let output = arg(matches, "output").ok_or_else(|| anyhow::anyhow!("--output is required"))?;
+    let format: ExportFormat = arg(matches, "format").unwrap_or("csv").parse()?;
+    let filter = OrderFilter {
+        from: date_arg(matches, "from")?,
+        to: date_arg(matches, "to")?,
+        account: arg(matches, "account").map(str::to_string),
+        symbol: arg(matches, "symbol").map(str::to_string),
+        ..Default::default()
+    };
+
+    let store = app.store(HISTORY_FILE_PATH)?;
+    let orders = load_orders(&store)?;
+    let exported = export_orders(&orders, &filter, format, Path::new(output))?;
+    println!("{} orders exported to {}", exported, output);
+
+    Ok(())
+}
@@ -0,0 +1,354 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use tauri::{command, AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::limits::LimitError;
+use crate::scheduler::{load_jobs, Command, Job, Schedule, HISTORY_FILE_PATH};
+use crate::transfer::{load_transfers, TransferPassed};
+
+const ENVELOPES_FILE_PATH: &str = "envelopes.json";
+
+/// Projections stop after this many years
+const PROJECTION_YEARS: i64 = 50;
+
+/// Tax-advantaged accounts with a regulatory contribution ceiling
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvelopeKind {
+    Pea,
+    PeaPme,
+    LivretA,
+    Ldds,
+}
+
+impl EnvelopeKind {
+    /// Ceiling in euros, as of 2024
+    pub fn default_ceiling(&self) -> f64 {
+        match self {
+            EnvelopeKind::Pea => 150_000.0,
+            EnvelopeKind::PeaPme => 225_000.0,
+            EnvelopeKind::LivretA => 22_950.0,
+            EnvelopeKind::Ldds => 12_000.0,
+        }
+    }
+
+    pub fn is_pea(&self) -> bool {
+        matches!(self, EnvelopeKind::Pea | EnvelopeKind::PeaPme)
+    }
+
+    /// The ceiling of the savings accounts applies to the deposits left on
+    /// the account, the PEA ceiling applies to all the payments made
+    pub fn withdrawals_free_room(&self) -> bool {
+        matches!(self, EnvelopeKind::LivretA | EnvelopeKind::Ldds)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CeilingAction {
+    /// Notify and transfer anyway
+    Warn,
+    /// Refuse the transfer
+    #[default]
+    Block,
+}
+
+/// Contribution ceiling tracked for an account
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Envelope {
+    pub account: String,
+    pub kind: EnvelopeKind,
+    /// Contributions made before the transfers were recorded by the app,
+    /// in euros
+    #[serde(default)]
+    pub starting_amount: f64,
+    /// Overrides the regulatory ceiling of the kind, in euros
+    #[serde(default)]
+    pub custom_ceiling: Option<f64>,
+    #[serde(default)]
+    pub action: CeilingAction,
+}
+
+impl Envelope {
+    pub fn ceiling(&self) -> f64 {
+        self.custom_ceiling.unwrap_or(self.kind.default_ceiling())
+    }
+
+    /// Contributions counted against the ceiling
+    pub fn contributed(&self, transfers: &[TransferPassed]) -> f64 {
+        let deposits: f64 = transfers
+            .iter()
+            .filter(|t| t.to == self.account)
+            .map(|t| t.amount)
+            .sum();
+        let withdrawals: f64 = if self.kind.withdrawals_free_room() {
+            transfers
+                .iter()
+                .filter(|t| t.from == self.account)
+                .map(|t| t.amount)
+                .sum()
+        } else {
+            0.0
+        };
+
+        (self.starting_amount + deposits - withdrawals).max(0.0)
+    }
+
+    pub fn remaining(&self, transfers: &[TransferPassed]) -> f64 {
+        (self.ceiling() - self.contributed(transfers)).max(0.0)
+    }
+}
+
+/// Check a transfer of `amount` to `account` against the ceiling of its
+/// envelope. Returns the error as a warning when the envelope only warns.
+pub fn check_ceiling(
+    envelopes: &[Envelope],
+    transfers: &[TransferPassed],
+    account: &str,
+    amount: f64,
+) -> Result<Option<LimitError>, LimitError> {
+    let Some(envelope) = envelopes.iter().find(|e| e.account == account) else {
+        return Ok(None);
+    };
+
+    let remaining = envelope.remaining(transfers);
+    if amount <= remaining {
+        return Ok(None);
+    }
+
+    let error = LimitError::CeilingExceeded {
+        account: account.to_string(),
+        amount,
+        remaining,
+    };
+    match envelope.action {
+        CeilingAction::Warn => Ok(Some(error)),
+        CeilingAction::Block => Err(error),
+    }
+}
+
+/// When the recurring transfers of the scheduled jobs reach the ceiling
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Projection {
+    /// Average contribution of the jobs, in euros per month
+    pub monthly_contribution: f64,
+    /// Unset when the jobs do not reach the ceiling
+    pub ceiling_date: Option<NaiveDate>,
+}
+
+/// Amount a job contributes to `account` on each run. Amount-based orders
+/// topped up from another account count for their whole amount, an upper
+/// bound of the top-up.
+fn job_contribution(job: &Job, account: &str) -> f64 {
+    match &job.command {
+        Command::Transfer(transfer) if transfer.to == account => {
+            transfer.amount.parse().unwrap_or_default()
+        }
+        Command::Order(order) if order.account == account && job.top_up_from.is_some() => {
+            order.amount.unwrap_or_default()
+        }
+        Command::Basket(basket) if basket.account == account && job.top_up_from.is_some() => {
+            basket.amount
+        }
+        _ => 0.0,
+    }
+}
+
+pub fn project(remaining: f64, jobs: &[Job], account: &str, today: NaiveDate) -> Projection {
+    let jobs: Vec<(&Job, f64)> = jobs
+        .iter()
+        .map(|job| (job, job_contribution(job, account)))
+        .filter(|(_, amount)| *amount > 0.0)
+        .collect();
+
+    let monthly_contribution = jobs
+        .iter()
+        .map(|(job, amount)| match job.schedule {
+            Schedule::Daily => amount * 365.25 / 12.0,
+            Schedule::Weekly { .. } => amount * 52.18 / 12.0,
+            Schedule::Monthly { .. } => *amount,
+        })
+        .sum();
+
+    let mut ceiling_date = None;
+    if !jobs.is_empty() {
+        let mut remaining = remaining;
+        let mut date = today;
+        while date < today + Duration::days(PROJECTION_YEARS * 365) {
+            date += Duration::days(1);
+            // One run per scheduled day, as `Job::should_run`
+            remaining -= jobs
+                .iter()
+                .filter(|(job, _)| job.schedule.runs_on(date))
+                .map(|(_, amount)| amount)
+                .sum::<f64>();
+            if remaining <= 0.0 {
+                ceiling_date = Some(date);
+                break;
+            }
+        }
+    }
+
+    Projection {
+        monthly_contribution,
+        ceiling_date,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvelopeStatus {
+    #[serde(flatten)]
+    pub envelope: Envelope,
+    pub ceiling: f64,
+    pub contributed: f64,
+    pub remaining: f64,
+    pub projection: Projection,
+}
+
+pub fn load_envelopes(app_local_data_dir: &Path) -> Result<Vec<Envelope>> {
+    let envelopes_path = app_local_data_dir.join(ENVELOPES_FILE_PATH);
+
+    if !fs::exists(&envelopes_path)? {
+        return Ok(vec![]);
+    }
+
+    let content = fs::read_to_string(envelopes_path)?;
+
+    if content.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_envelopes(app_local_data_dir: &Path, envelopes: &[Envelope]) -> Result<()> {
+    let envelopes_path = app_local_data_dir.join(ENVELOPES_FILE_PATH);
+
+    fs::write(envelopes_path, serde_json::to_string(envelopes)?)?;
+
+    Ok(())
+}
+
+/// Envelopes with their contributions and the projection of the scheduled
+/// jobs
+#[command]
+pub fn get_envelopes(app: AppHandle) -> core::result::Result<Vec<EnvelopeStatus>, String> {
+    let app_local_data_dir = app.path().app_local_data_dir().unwrap();
+    let envelopes = load_envelopes(&app_local_data_dir).map_err(|e| e.to_string())?;
+    let jobs = load_jobs(&app_local_data_dir).map_err(|e| e.to_string())?;
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let transfers = load_transfers(&store).map_err(|e| e.to_string())?;
+    let today = Local::now().date_naive();
+
+    Ok(envelopes
+        .into_iter()
+        .map(|envelope| {
+            let remaining = envelope.remaining(&transfers);
+            EnvelopeStatus {
+                ceiling: envelope.ceiling(),
+                contributed: envelope.contributed(&transfers),
+                remaining,
+                projection: project(remaining, &jobs, &envelope.account, today),
+                envelope,
+            }
+        })
+        .collect())
+}
+
+#[command]
+pub fn set_envelopes(app: AppHandle, envelopes: Vec<Envelope>) -> core::result::Result<(), String> {
+    let app_local_data_dir = app.path().app_local_data_dir().unwrap();
+
+    save_envelopes(&app_local_data_dir, &envelopes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::Transfer;
+
+    fn envelope(kind: EnvelopeKind, action: CeilingAction) -> Envelope {
+        Envelope {
+            account: "livret".to_string(),
+            kind,
+            starting_amount: 20_000.0,
+            custom_ceiling: None,
+            action,
+        }
+    }
+
+    fn transfer(from: &str, to: &str, amount: f64) -> TransferPassed {
+        TransferPassed::new(from, to, amount, None)
+    }
+
+    #[test]
+    fn test_contributed() {
+        let transfers = vec![
+            transfer("checking", "livret", 2_000.0),
+            transfer("livret", "checking", 500.0),
+        ];
+
+        let livret = envelope(EnvelopeKind::LivretA, CeilingAction::Block);
+        assert_eq!(livret.contributed(&transfers), 21_500.0);
+        assert_eq!(livret.remaining(&transfers), 1_450.0);
+
+        // The withdrawals do not free room on a PEA
+        let pea = envelope(EnvelopeKind::Pea, CeilingAction::Block);
+        assert_eq!(pea.contributed(&transfers), 22_000.0);
+    }
+
+    #[test]
+    fn test_check_ceiling() {
+        let transfers = vec![transfer("checking", "livret", 2_000.0)];
+        let envelopes = vec![envelope(EnvelopeKind::LivretA, CeilingAction::Block)];
+
+        assert_eq!(
+            check_ceiling(&envelopes, &transfers, "livret", 950.0),
+            Ok(None)
+        );
+        assert_eq!(
+            check_ceiling(&envelopes, &transfers, "livret", 1_000.0),
+            Err(LimitError::CeilingExceeded {
+                account: "livret".to_string(),
+                amount: 1_000.0,
+                remaining: 950.0,
+            })
+        );
+        assert_eq!(
+            check_ceiling(&envelopes, &transfers, "other", 1e9),
+            Ok(None)
+        );
+
+        let envelopes = vec![envelope(EnvelopeKind::LivretA, CeilingAction::Warn)];
+        assert!(check_ceiling(&envelopes, &transfers, "livret", 1_000.0)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_project() {
+        let job = Job::new(
+            Schedule::Monthly { day: 31 },
+            Command::Transfer(Transfer {
+                from: "checking".to_string(),
+                to: "livret".to_string(),
+                amount: "500".to_string(),
+            }),
+        );
+        let today = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        let projection = project(1_200.0, &[job], "livret", today);
+
+        assert_eq!(projection.monthly_contribution, 500.0);
+        // Runs on the last day of February
+        assert_eq!(
+            projection.ceiling_date,
+            NaiveDate::from_ymd_opt(2024, 3, 31)
+        );
+
+        assert_eq!(project(1_200.0, &[], "livret", today).ceiling_date, None);
+    }
+}
@@ -0,0 +1,422 @@
+//! Dividends, splits and fees of the trading accounts, recorded next to the
+//! orders. They are entered by hand or imported from a CSV, `bourso_api`
+//! does not expose the account statements.
+
+use anyhow::Result;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::{command, AppHandle, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::history::{
+    find_column, normalize, parse_date, parse_number, split_csv_line, ImportSummary,
+};
+use crate::order::OrderSource;
+use crate::scheduler::HISTORY_FILE_PATH;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventKind {
+    /// Dividend or coupon, in euros
+    Dividend {
+        gross: f64,
+        /// Tax withheld at source, deducted from the gross amount
+        #[serde(default)]
+        withholding_tax: f64,
+    },
+    /// `from` shares become `to` shares, a reverse split when `to` is lower
+    Split { from: u64, to: u64 },
+    /// Fee not attached to an order, e.g. custody fees or the financial
+    /// transaction tax, in euros
+    Fee { amount: f64 },
+}
+
+/// Dividend, split or fee of an account
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountEvent {
+    pub id: String,
+    pub account: String,
+    /// Security of the event, unset for the fees of the account
+    #[serde(default)]
+    pub symbol: Option<String>,
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub kind: EventKind,
+    #[serde(default)]
+    pub source: OrderSource,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl AccountEvent {
+    /// Dividend received after the withholding tax
+    pub fn net_dividend(&self) -> Option<f64> {
+        match self.kind {
+            EventKind::Dividend {
+                gross,
+                withholding_tax,
+            } => Some(gross - withholding_tax),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self.kind {
+            EventKind::Dividend {
+                gross,
+                withholding_tax,
+            } => {
+                if gross <= 0.0 || withholding_tax < 0.0 || withholding_tax > gross {
+                    return Err(anyhow::anyhow!(
+                        "The withholding tax must be between 0 and the gross amount"
+                    ));
+                }
+            }
+            EventKind::Split { from, to } => {
+                if from == 0 || to == 0 || from == to {
+                    return Err(anyhow::anyhow!("Invalid split ratio {}:{}", from, to));
+                }
+            }
+            EventKind::Fee { amount } => {
+                if amount <= 0.0 {
+                    return Err(anyhow::anyhow!("The fee must be positive"));
+                }
+            }
+        }
+        if self.symbol.is_none() && !matches!(self.kind, EventKind::Fee { .. }) {
+            return Err(anyhow::anyhow!("Dividends and splits need a symbol"));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn load_events(store: &Store<Wry>) -> Result<Vec<AccountEvent>> {
+    let events = store.get("events").unwrap_or(serde_json::json!([]));
+    Ok(serde_json::from_value(events)?)
+}
+
+fn save_events(store: &Store<Wry>, events: &[AccountEvent]) -> Result<()> {
+    store.set("events", serde_json::json!(events));
+    store.save()?;
+
+    Ok(())
+}
+
+/// Add the events not yet in the history, de-duplicated by id, and keep the
+/// history sorted by date
+fn merge_events(history: &mut Vec<AccountEvent>, events: Vec<AccountEvent>) -> ImportSummary {
+    let mut ids: HashSet<String> = history.iter().map(|e| e.id.clone()).collect();
+    let mut summary = ImportSummary::default();
+
+    for event in events {
+        if ids.insert(event.id.clone()) {
+            history.push(event);
+            summary.imported += 1;
+        } else {
+            summary.duplicates += 1;
+        }
+    }
+
+    history.sort_by_key(|e| e.timestamp);
+
+    summary
+}
+
+#[command]
+pub fn get_events(
+    app: AppHandle,
+    account: Option<String>,
+) -> core::result::Result<Vec<AccountEvent>, String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let events = load_events(&store).map_err(|e| e.to_string())?;
+
+    Ok(events
+        .into_iter()
+        .filter(|e| account.as_ref().is_none_or(|account| e.account == *account))
+        .collect())
+}
+
+/// Record an event entered by hand, its id is generated
+#[command]
+pub fn add_event_cmd(
+    app: AppHandle,
+    mut event: AccountEvent,
+) -> core::result::Result<AccountEvent, String> {
+    event.symbol = event.symbol.filter(|s| !s.trim().is_empty());
+    event.validate().map_err(|e| e.to_string())?;
+    event.id = format!("manual-{}", Local::now().timestamp_millis());
+    event.source = OrderSource::Manual;
+    event.note = event.note.filter(|n| !n.trim().is_empty());
+
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let mut history = load_events(&store).map_err(|e| e.to_string())?;
+    merge_events(&mut history, vec![event.clone()]);
+    save_events(&store, &history).map_err(|e| e.to_string())?;
+
+    Ok(event)
+}
+
+#[command]
+pub fn delete_event_cmd(app: AppHandle, id: String) -> core::result::Result<(), String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let mut history = load_events(&store).map_err(|e| e.to_string())?;
+
+    let index = history
+        .iter()
+        .position(|e| e.id == id)
+        .ok_or_else(|| format!("Event {} not found", id))?;
+    history.remove(index);
+
+    save_events(&store, &history).map_err(|e| e.to_string())
+}
+
+/// Import the events of a CSV, `account` is used for the rows without an
+/// account column
+#[command]
+pub fn import_events_csv(
+    app: AppHandle,
+    content: String,
+    account: String,
+) -> core::result::Result<ImportSummary, String> {
+    let (events, skipped) = parse_events_csv(&content, &account).map_err(|e| e.to_string())?;
+
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let mut history = load_events(&store).map_err(|e| e.to_string())?;
+
+    let mut summary = merge_events(&mut history, events);
+    summary.skipped = skipped;
+    save_events(&store, &history).map_err(|e| e.to_string())?;
+
+    Ok(summary)
+}
+
+/// Column names, compared without accents and case
+const DATE_COLUMNS: &[&str] = &["date d'operation", "date operation", "date"];
+const TYPE_COLUMNS: &[&str] = &["type", "operation", "nature", "kind"];
+const SYMBOL_COLUMNS: &[&str] = &["code", "symbole", "symbol", "isin", "code isin"];
+const GROSS_COLUMNS: &[&str] = &["montant brut", "brut", "gross"];
+const WITHHOLDING_COLUMNS: &[&str] = &[
+    "retenue a la source",
+    "retenue",
+    "prelevement",
+    "withholding",
+];
+const AMOUNT_COLUMNS: &[&str] = &["montant net", "montant", "net", "amount"];
+const RATIO_COLUMNS: &[&str] = &["parite", "ratio"];
+const ACCOUNT_COLUMNS: &[&str] = &["compte", "account"];
+const NOTE_COLUMNS: &[&str] = &["libelle", "label", "note"];
+
+/// Parse a `from:to` or `from/to` ratio
+fn parse_ratio(s: &str) -> Result<(u64, u64)> {
+    let (from, to) = s
+        .split_once([':', '/'])
+        .ok_or_else(|| anyhow::anyhow!("invalid ratio {:?}, expected old:new", s))?;
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<u64>()
+            .map_err(|_| anyhow::anyhow!("invalid ratio {:?}", s))
+    };
+
+    Ok((parse(from)?, parse(to)?))
+}
+
+/// Parse the events of a CSV with a type column: "dividende" or "coupon"
+/// with a gross or net amount and an optional withholding tax, "division"
+/// or "regroupement" with an `old:new` ratio, "frais" with an amount.
+/// Returns the events and the skipped rows.
+pub fn parse_events_csv(content: &str, account: &str) -> Result<(Vec<AccountEvent>, Vec<String>)> {
+    let mut lines = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .filter(|l| !l.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("The file is empty"))?;
+    let separator = if header.matches(';').count() >= header.matches(',').count() {
+        ';'
+    } else {
+        ','
+    };
+    let columns: Vec<String> = split_csv_line(header, separator)
+        .iter()
+        .map(|c| normalize(c))
+        .collect();
+
+    let column = |aliases: &[&str]| find_column(&columns, aliases);
+    let date_col = column(DATE_COLUMNS).ok_or_else(|| anyhow::anyhow!("Missing date column"))?;
+    let type_col = column(TYPE_COLUMNS).ok_or_else(|| anyhow::anyhow!("Missing type column"))?;
+    let symbol_col = column(SYMBOL_COLUMNS);
+    let gross_col = column(GROSS_COLUMNS);
+    let withholding_col = column(WITHHOLDING_COLUMNS);
+    let amount_col = column(AMOUNT_COLUMNS);
+    let ratio_col = column(RATIO_COLUMNS);
+    let account_col = column(ACCOUNT_COLUMNS);
+    let note_col = column(NOTE_COLUMNS);
+
+    let mut events = vec![];
+    let mut skipped = vec![];
+    let mut generated: HashMap<String, u32> = HashMap::new();
+
+    // Line numbers start at 2, after the header
+    for (i, line) in lines.enumerate() {
+        let row = split_csv_line(line, separator);
+        let field = |col: Option<usize>| {
+            col.and_then(|col| row.get(col))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+        };
+        let number = |col: Option<usize>| -> Result<Option<f64>> {
+            field(col)
+                .map(parse_number)
+                .transpose()
+                .map(|n| n.map(f64::abs))
+        };
+
+        let parsed = (|| -> Result<AccountEvent> {
+            let timestamp = parse_date(field(Some(date_col)).unwrap_or_default())?;
+            let kind_name = normalize(field(Some(type_col)).unwrap_or_default());
+
+            let kind = if kind_name.contains("dividend") || kind_name.contains("coupon") {
+                let withholding_tax = number(withholding_col)?.unwrap_or_default();
+                let gross = match number(gross_col)? {
+                    Some(gross) => gross,
+                    None => {
+                        number(amount_col)?.ok_or_else(|| anyhow::anyhow!("missing amount"))?
+                            + withholding_tax
+                    }
+                };
+                EventKind::Dividend {
+                    gross,
+                    withholding_tax,
+                }
+            } else if ["division", "regroupement", "split"]
+                .iter()
+                .any(|k| kind_name.contains(k))
+            {
+                let (from, to) =
+                    parse_ratio(field(ratio_col).ok_or_else(|| anyhow::anyhow!("missing ratio"))?)?;
+                EventKind::Split { from, to }
+            } else if kind_name.contains("frais") || kind_name.contains("fee") {
+                let amount = number(amount_col)?
+                    .or(number(gross_col)?)
+                    .ok_or_else(|| anyhow::anyhow!("missing amount"))?;
+                EventKind::Fee { amount }
+            } else {
+                return Err(anyhow::anyhow!("unknown type {:?}", kind_name));
+            };
+
+            let symbol = field(symbol_col).map(str::to_string);
+            let account = field(account_col).unwrap_or(account).to_string();
+            // Built from the content so that importing the same file twice
+            // does not duplicate the events, identical rows are numbered in
+            // the order of the file
+            let amount = match kind {
+                EventKind::Dividend { gross, .. } => format!("{:.2}", gross),
+                EventKind::Split { from, to } => format!("{}:{}", from, to),
+                EventKind::Fee { amount } => format!("{:.2}", amount),
+            };
+            let id = format!(
+                "import-{}-{}-{}-{}-{}",
+                account,
+                timestamp,
+                kind_name,
+                symbol.as_deref().unwrap_or_default(),
+                amount
+            );
+            let occurrence = generated.entry(id.clone()).or_default();
+            *occurrence += 1;
+            let event = AccountEvent {
+                id: if *occurrence == 1 {
+                    id
+                } else {
+                    format!("{}-{}", id, occurrence)
+                },
+                account,
+                symbol,
+                timestamp,
+                kind,
+                source: OrderSource::Imported,
+                note: field(note_col).map(str::to_string),
+            };
+            event.validate()?;
+
+            Ok(event)
+        })();
+
+        match parsed {
+            Ok(event) => events.push(event),
+            Err(e) => skipped.push(format!("Line {}: {}", i + 2, e)),
+        }
+    }
+
+    Ok((events, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events_csv() {
+        let content = "Date;Type;Code ISIN;Montant brut;Retenue à la source;Montant net;Parité\n\
+            15/03/2024;Dividende;US0378331005;\"12,50\";\"1,88\";;\n\
+            16/03/2024;Coupon;FR0000120271;;;8;\n\
+            17/03/2024;Division;FR0010315770;;;;1:10\n\
+            18/03/2024;Regroupement;FR0000131104;;;;20/1\n\
+            19/03/2024;Frais;;;;\"-3,00\";\n\
+            20/03/2024;Virement;;;;100;\n";
+
+        let (events, skipped) = parse_events_csv(content, "1234567890").unwrap();
+
+        assert_eq!(events.len(), 5);
+        assert_eq!(
+            events[0].kind,
+            EventKind::Dividend {
+                gross: 12.5,
+                withholding_tax: 1.88
+            }
+        );
+        assert!((events[0].net_dividend().unwrap() - 10.62).abs() < 1e-9);
+        assert_eq!(events[1].net_dividend(), Some(8.0));
+        assert_eq!(events[2].kind, EventKind::Split { from: 1, to: 10 });
+        assert_eq!(events[3].kind, EventKind::Split { from: 20, to: 1 });
+        assert_eq!(events[4].kind, EventKind::Fee { amount: 3.0 });
+        assert_eq!(events[4].symbol, None);
+        assert_eq!(events[4].account, "1234567890");
+        assert_eq!(skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_import_ids() {
+        let content = "Date;Type;Compte;Code ISIN;Montant net\n\
+            31/03/2024;Frais;1234567890;;2\n\
+            31/03/2024;Frais;1234567890;;3\n\
+            31/03/2024;Frais;1234567890;;3\n\
+            31/03/2024;Frais;0987654321;;2\n";
+
+        let (events, skipped) = parse_events_csv(content, "1234567890").unwrap();
+        assert!(skipped.is_empty());
+
+        // Fees of the same day on the same or another account
+        let ids: HashSet<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids.len(), 4);
+        let timestamp = events[0].timestamp;
+        assert_eq!(
+            events[0].id,
+            format!("import-1234567890-{}-frais--2.00", timestamp)
+        );
+        assert_eq!(
+            events[2].id,
+            format!("import-1234567890-{}-frais--3.00-2", timestamp)
+        );
+
+        // Importing the file again gives the same ids
+        let (again, _) = parse_events_csv(content, "1234567890").unwrap();
+        let mut history = events.clone();
+        let summary = merge_events(&mut history, again);
+        assert_eq!(summary.imported, 0);
+        assert_eq!(history.len(), 4);
+    }
+}
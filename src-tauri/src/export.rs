@@ -0,0 +1,287 @@
+use anyhow::Result;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
+use tauri::{command, AppHandle};
+use tauri_plugin_store::StoreExt;
+
+use crate::history::OrderFilter;
+use crate::order::{load_orders, OrderPassed, OrderSide};
+use crate::scheduler::HISTORY_FILE_PATH;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    /// OFX 2 investment statement, read by most portfolio tools
+    Ofx,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "ofx" => Ok(ExportFormat::Ofx),
+            _ => Err(anyhow::anyhow!("Unknown export format: {}", format)),
+        }
+    }
+}
+
+/// Export the orders of the history matching `filter` to `path`, returns
+/// the number of orders exported
+#[command]
+pub fn export_orders_cmd(
+    app: AppHandle,
+    path: String,
+    format: ExportFormat,
+    filter: Option<OrderFilter>,
+) -> core::result::Result<usize, String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let orders = load_orders(&store).map_err(|e| e.to_string())?;
+
+    export_orders(
+        &orders,
+        &filter.unwrap_or_default(),
+        format,
+        Path::new(&path),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub fn export_orders(
+    orders: &[OrderPassed],
+    filter: &OrderFilter,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<usize> {
+    let orders: Vec<&OrderPassed> = orders.iter().filter(|o| filter.matches(o)).collect();
+
+    let content = match format {
+        ExportFormat::Csv => to_csv(&orders),
+        ExportFormat::Json => serde_json::to_string_pretty(&orders)?,
+        ExportFormat::Ofx => to_ofx(&orders),
+    };
+    fs::write(path, content)?;
+
+    Ok(orders.len())
+}
+
+fn quantity(order: &OrderPassed) -> u64 {
+    order.executed_quantity()
+}
+
+fn to_csv(orders: &[&OrderPassed]) -> String {
+    let mut csv = String::from("id,date,account,symbol,side,quantity,price,amount\n");
+
+    for order in orders {
+        let date = order
+            .timestamp
+            .and_then(|ts| Local.timestamp_opt(ts, 0).single())
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let row = [
+            csv_field(&order.id),
+            date,
+            csv_field(&order.args.account),
+            csv_field(&order.args.symbol),
+            order.args.side.to_string(),
+            quantity(order).to_string(),
+            order.price.to_string(),
+            format!("{:.2}", quantity(order) as f64 * order.price),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Quote a CSV field when it contains a separator, a quote or a new line
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escape the XML special characters
+pub fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn ofx_date(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(|ts| Local.timestamp_opt(ts, 0).single())
+        .unwrap_or_else(Local::now)
+        .format("%Y%m%d%H%M%S")
+        .to_string()
+}
+
+/// ISIN codes are identified as such, the other symbols are Bourso codes
+fn security_id(symbol: &str) -> String {
+    let id_type = if symbol.len() == 12
+        && symbol.chars().all(|c| c.is_ascii_alphanumeric())
+        && symbol[..2].chars().all(|c| c.is_ascii_uppercase())
+    {
+        "ISIN"
+    } else {
+        "TICKER"
+    };
+
+    format!(
+        "<SECID><UNIQUEID>{}</UNIQUEID><UNIQUEIDTYPE>{}</UNIQUEIDTYPE></SECID>",
+        xml_escape(symbol),
+        id_type
+    )
+}
+
+/// One investment statement per account with a buy or sell transaction per
+/// order, and the list of the securities traded
+fn to_ofx(orders: &[&OrderPassed]) -> String {
+    let now = ofx_date(Some(Local::now().timestamp()));
+    let mut by_account: BTreeMap<&str, Vec<&OrderPassed>> = BTreeMap::new();
+    for order in orders {
+        by_account
+            .entry(&order.args.account)
+            .or_default()
+            .push(order);
+    }
+
+    let mut ofx = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+         <?OFX OFXHEADER=\"200\" VERSION=\"220\" SECURITY=\"NONE\" OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>\n\
+         <OFX>\n",
+    );
+    ofx.push_str(&format!(
+        "<SIGNONMSGSRSV1><SONRS><STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>\
+         <DTSERVER>{}</DTSERVER><LANGUAGE>FRA</LANGUAGE></SONRS></SIGNONMSGSRSV1>\n",
+        now
+    ));
+    ofx.push_str("<INVSTMTMSGSRSV1>\n");
+
+    for (account, orders) in &by_account {
+        let start = ofx_date(orders.iter().filter_map(|o| o.timestamp).min());
+        let end = ofx_date(orders.iter().filter_map(|o| o.timestamp).max());
+
+        ofx.push_str(&format!(
+            "<INVSTMTTRNRS><TRNUID>{account}</TRNUID>\
+             <STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>\n\
+             <INVSTMTRS><DTASOF>{now}</DTASOF><CURDEF>EUR</CURDEF>\
+             <INVACCTFROM><BROKERID>boursobank.com</BROKERID><ACCTID>{account}</ACCTID></INVACCTFROM>\n\
+             <INVTRANLIST><DTSTART>{start}</DTSTART><DTEND>{end}</DTEND>\n",
+            account = xml_escape(account),
+        ));
+
+        for order in orders {
+            let units = quantity(order) as f64;
+            let total = units * order.price;
+            // Units and cash are signed from the account point of view
+            let (tag, inner, kind, units, total) = match order.args.side {
+                OrderSide::Buy => (
+                    "BUYSTOCK",
+                    "INVBUY",
+                    "<BUYTYPE>BUY</BUYTYPE>",
+                    units,
+                    -total,
+                ),
+                OrderSide::Sell => (
+                    "SELLSTOCK",
+                    "INVSELL",
+                    "<SELLTYPE>SELL</SELLTYPE>",
+                    -units,
+                    total,
+                ),
+            };
+            ofx.push_str(&format!(
+                "<{tag}><{inner}><INVTRAN><FITID>{id}</FITID><DTTRADE>{date}</DTTRADE></INVTRAN>\
+                 {secid}<UNITS>{units}</UNITS><UNITPRICE>{price}</UNITPRICE>\
+                 <TOTAL>{total:.2}</TOTAL><SUBACCTSEC>CASH</SUBACCTSEC><SUBACCTFUND>CASH</SUBACCTFUND>\
+                 </{inner}>{kind}</{tag}>\n",
+                id = xml_escape(&order.id),
+                date = ofx_date(order.timestamp),
+                secid = security_id(&order.args.symbol),
+                price = order.price,
+            ));
+        }
+
+        ofx.push_str("</INVTRANLIST></INVSTMTRS></INVSTMTTRNRS>\n");
+    }
+    ofx.push_str("</INVSTMTMSGSRSV1>\n");
+
+    let mut symbols: Vec<&str> = orders.iter().map(|o| o.args.symbol.as_str()).collect();
+    symbols.sort();
+    symbols.dedup();
+    if !symbols.is_empty() {
+        ofx.push_str("<SECLISTMSGSRSV1><SECLIST>\n");
+        for symbol in symbols {
+            ofx.push_str(&format!(
+                "<STOCKINFO><SECINFO>{}<SECNAME>{}</SECNAME></SECINFO></STOCKINFO>\n",
+                security_id(symbol),
+                xml_escape(symbol)
+            ));
+        }
+        ofx.push_str("</SECLIST></SECLISTMSGSRSV1>\n");
+    }
+    ofx.push_str("</OFX>\n");
+
+    ofx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{OrderArgs, OrderSource};
+
+    fn order(id: &str, symbol: &str, side: OrderSide) -> OrderPassed {
+        OrderPassed::new(
+            id.to_string(),
+            12.5,
+            Some(1_709_294_400),
+            OrderArgs {
+                account: "1234567890".to_string(),
+                symbol: symbol.to_string(),
+                quantity: Some(4),
+                amount: None,
+                side,
+            },
+            OrderSource::App,
+        )
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let a = order("a", "1rTCW8", OrderSide::Buy);
+        let b = order("b,1", "FR0010315770", OrderSide::Sell);
+
+        let csv = to_csv(&[&a, &b]);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("a,"));
+        assert!(lines[1].ends_with(",1234567890,1rTCW8,buy,4,12.5,50.00"));
+        assert!(lines[2].starts_with("\"b,1\","));
+    }
+
+    #[test]
+    fn test_to_ofx() {
+        let a = order("a", "1rTCW8", OrderSide::Buy);
+        let b = order("b", "FR0010315770", OrderSide::Sell);
+
+        let ofx = to_ofx(&[&a, &b]);
+
+        assert_eq!(ofx.matches("<INVSTMTTRNRS>").count(), 1);
+        assert!(ofx.contains("<BUYSTOCK><INVBUY><INVTRAN><FITID>a</FITID>"));
+        assert!(ofx.contains("<UNITS>4</UNITS><UNITPRICE>12.5</UNITPRICE><TOTAL>-50.00</TOTAL>"));
+        assert!(ofx.contains("<UNITS>-4</UNITS><UNITPRICE>12.5</UNITPRICE><TOTAL>50.00</TOTAL>"));
+        assert!(ofx.contains("<UNIQUEID>FR0010315770</UNIQUEID><UNIQUEIDTYPE>ISIN</UNIQUEIDTYPE>"));
+        assert!(ofx.contains("<UNIQUEID>1rTCW8</UNIQUEID><UNIQUEIDTYPE>TICKER</UNIQUEIDTYPE>"));
+    }
+}
@@ -0,0 +1,815 @@
+//! Local history of the orders: filtering, manual edits, and import of the
+//! orders passed outside of the app.
+//!
+//! `bourso_api` does not expose the order history of the website, so the
+//! history cannot be fetched from Bourso: the orders are imported from the
+//! CSV export of the orders page instead (or from a CSV built from the PDF
+//! statements). The exports identify the securities by ISIN, which are
+//! mapped to the Bourso symbols used by the rest of the app.
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::{command, AppHandle};
+use tauri_plugin_store::StoreExt;
+
+use crate::order::{
+    load_orders, save_orders, OrderArgs, OrderEdit, OrderPassed, OrderSide, OrderSource,
+};
+use crate::scheduler::HISTORY_FILE_PATH;
+
+/// Filter of the orders of the history, unset fields match every order.
+/// Dates are inclusive and in local time.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OrderFilter {
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+    #[serde(default)]
+    pub to: Option<NaiveDate>,
+    #[serde(default)]
+    pub account: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub side: Option<OrderSide>,
+    #[serde(default)]
+    pub source: Option<OrderSource>,
+    #[serde(default)]
+    pub job_id: Option<String>,
+}
+
+impl OrderFilter {
+    pub fn matches(&self, order: &OrderPassed) -> bool {
+        if self
+            .account
+            .as_ref()
+            .is_some_and(|a| *a != order.args.account)
+            || self
+                .symbol
+                .as_ref()
+                .is_some_and(|s| *s != order.args.symbol)
+            || self.side.is_some_and(|s| s != order.args.side)
+            || self.source.is_some_and(|s| s != order.source)
+            || self.job_id.is_some() && self.job_id != order.job_id
+        {
+            return false;
+        }
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+
+        let Some(date) = order.timestamp.and_then(order_date) else {
+            return false;
+        };
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+}
+
+/// Local date of an order timestamp
+pub fn order_date(timestamp: i64) -> Option<NaiveDate> {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Orders already in the history
+    pub duplicates: usize,
+    /// Rows that could not be imported, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// Column names of the Bourso exports and of generic exports, compared
+/// without accents and case
+const ID_COLUMNS: &[&str] = &["reference", "ref", "id", "order id", "numero", "n° d'ordre"];
+const DATE_COLUMNS: &[&str] = &[
+    "date d'execution",
+    "date execution",
+    "date",
+    "timestamp",
+    "date de saisie",
+];
+const SIDE_COLUMNS: &[&str] = &["sens", "side", "operation", "type"];
+const SYMBOL_COLUMNS: &[&str] = &["code", "symbole", "symbol", "isin", "code isin"];
+const QUANTITY_COLUMNS: &[&str] = &["quantite executee", "quantite", "quantity", "qte"];
+const PRICE_COLUMNS: &[&str] = &[
+    "cours d'execution",
+    "cours execute",
+    "cours",
+    "prix",
+    "price",
+];
+const FEES_COLUMNS: &[&str] = &[
+    "frais",
+    "frais de courtage",
+    "courtage",
+    "commission",
+    "fees",
+];
+const ACCOUNT_COLUMNS: &[&str] = &["compte", "account"];
+const STATUS_COLUMNS: &[&str] = &["statut", "etat", "status"];
+
+/// Import the orders of a CSV export into the local history, `account` is
+/// used for the rows without an account column. `symbols` maps the ISIN of
+/// the exports to Bourso symbols, the rows of an unmapped ISIN are skipped.
+#[command]
+pub fn import_orders_csv(
+    app: AppHandle,
+    content: String,
+    account: String,
+    symbols: Option<HashMap<String, String>>,
+) -> core::result::Result<ImportSummary, String> {
+    let (orders, skipped) = parse_orders_csv(&content, &account, &symbols.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let mut history = load_orders(&store).map_err(|e| e.to_string())?;
+
+    let mut summary = merge_orders(&mut history, orders);
+    summary.skipped = skipped;
+    save_orders(&store, &history).map_err(|e| e.to_string())?;
+
+    Ok(summary)
+}
+
+/// Order added by hand, e.g. an order placed on the website
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManualOrder {
+    /// Reference of the order on Bourso, generated when unset
+    #[serde(default)]
+    pub id: Option<String>,
+    pub account: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: u64,
+    pub price: f64,
+    pub timestamp: i64,
+    #[serde(default)]
+    pub fees: f64,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Changes to an order record, unset fields are kept. An empty note
+/// removes the note.
+#[derive(Debug, Deserialize, Default)]
+pub struct OrderUpdate {
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub quantity: Option<u64>,
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+    #[serde(default)]
+    pub fees: Option<f64>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+impl OrderUpdate {
+    /// Apply the changes and record them in the audit trail of the order
+    pub fn apply(&self, order: &mut OrderPassed, now: i64) -> Result<()> {
+        if self.price.is_some_and(|price| price <= 0.0) {
+            return Err(anyhow::anyhow!("The price must be positive"));
+        }
+        if self.quantity == Some(0) {
+            return Err(anyhow::anyhow!("The quantity must be positive"));
+        }
+        if self.fees.is_some_and(|fees| fees < 0.0) {
+            return Err(anyhow::anyhow!("The fees cannot be negative"));
+        }
+
+        let mut edits = vec![];
+        if let Some(price) = self.price {
+            edit(&mut edits, now, "price", &mut order.price, price);
+        }
+        if let Some(quantity) = self.quantity {
+            edit(
+                &mut edits,
+                now,
+                "quantity",
+                &mut order.args.quantity,
+                Some(quantity),
+            );
+        }
+        if let Some(timestamp) = self.timestamp {
+            edit(
+                &mut edits,
+                now,
+                "timestamp",
+                &mut order.timestamp,
+                Some(timestamp),
+            );
+        }
+        if let Some(fees) = self.fees {
+            edit(&mut edits, now, "fees", &mut order.fees, fees);
+        }
+        if let Some(note) = &self.note {
+            let note = Some(note.trim().to_string()).filter(|n| !n.is_empty());
+            edit(&mut edits, now, "note", &mut order.note, note);
+        }
+        if let Some(tags) = &self.tags {
+            edit(&mut edits, now, "tags", &mut order.tags, tags.clone());
+        }
+        order.edits.extend(edits);
+
+        Ok(())
+    }
+}
+
+fn edit<T: Serialize + PartialEq>(
+    edits: &mut Vec<OrderEdit>,
+    now: i64,
+    field: &str,
+    current: &mut T,
+    new: T,
+) {
+    if *current != new {
+        edits.push(OrderEdit {
+            timestamp: now,
+            field: field.to_string(),
+            from: serde_json::json!(current),
+            to: serde_json::json!(new),
+        });
+        *current = new;
+    }
+}
+
+#[command]
+pub fn add_order_cmd(
+    app: AppHandle,
+    order: ManualOrder,
+) -> core::result::Result<OrderPassed, String> {
+    if order.quantity == 0 || order.price <= 0.0 {
+        return Err("The quantity and the price must be positive".to_string());
+    }
+    if order.fees < 0.0 {
+        return Err("The fees cannot be negative".to_string());
+    }
+
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let mut history = load_orders(&store).map_err(|e| e.to_string())?;
+
+    let id = order
+        .id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| {
+            format!(
+                "manual-{}-{}",
+                Local::now().timestamp_millis(),
+                order.symbol
+            )
+        });
+    if history.iter().any(|o| o.id == id) {
+        return Err(format!("Order {} is already in the history", id));
+    }
+
+    let mut passed = OrderPassed::new(
+        id,
+        order.price,
+        Some(order.timestamp),
+        OrderArgs {
+            account: order.account,
+            symbol: order.symbol,
+            quantity: Some(order.quantity),
+            amount: None,
+            side: order.side,
+        },
+        OrderSource::Manual,
+    );
+    passed.fees = order.fees;
+    passed.note = order.note.filter(|n| !n.trim().is_empty());
+    passed.tags = order.tags;
+
+    merge_orders(&mut history, vec![passed.clone()]);
+    save_orders(&store, &history).map_err(|e| e.to_string())?;
+
+    Ok(passed)
+}
+
+#[command]
+pub fn update_order_cmd(
+    app: AppHandle,
+    id: String,
+    update: OrderUpdate,
+) -> core::result::Result<OrderPassed, String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let mut history = load_orders(&store).map_err(|e| e.to_string())?;
+
+    let order = history
+        .iter_mut()
+        .find(|o| o.id == id)
+        .ok_or_else(|| format!("Order {} not found", id))?;
+    update
+        .apply(order, Local::now().timestamp())
+        .map_err(|e| e.to_string())?;
+    let order = order.clone();
+
+    history.sort_by_key(|o| o.timestamp.unwrap_or_default());
+    save_orders(&store, &history).map_err(|e| e.to_string())?;
+
+    Ok(order)
+}
+
+/// Remove an order from the history. It is kept aside with the other
+/// deleted orders so that the deletion can be audited.
+#[command]
+pub fn delete_order_cmd(app: AppHandle, id: String) -> core::result::Result<(), String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let mut history = load_orders(&store).map_err(|e| e.to_string())?;
+
+    let index = history
+        .iter()
+        .position(|o| o.id == id)
+        .ok_or_else(|| format!("Order {} not found", id))?;
+    let mut order = history.remove(index);
+    order.edits.push(OrderEdit {
+        timestamp: Local::now().timestamp(),
+        field: "deleted".to_string(),
+        from: serde_json::json!(false),
+        to: serde_json::json!(true),
+    });
+
+    let deleted = store.get("deleted_orders").unwrap_or(serde_json::json!([]));
+    let mut deleted: Vec<OrderPassed> =
+        serde_json::from_value(deleted).map_err(|e| e.to_string())?;
+    deleted.push(order);
+    store.set("deleted_orders", serde_json::json!(deleted));
+
+    save_orders(&store, &history).map_err(|e| e.to_string())
+}
+
+/// Add the orders not yet in the history, de-duplicated by id, and keep the
+/// history sorted by date
+pub fn merge_orders(history: &mut Vec<OrderPassed>, orders: Vec<OrderPassed>) -> ImportSummary {
+    let mut ids: HashSet<String> = history.iter().map(|o| o.id.clone()).collect();
+    let mut summary = ImportSummary::default();
+
+    for order in orders {
+        if ids.insert(order.id.clone()) {
+            history.push(order);
+            summary.imported += 1;
+        } else {
+            summary.duplicates += 1;
+        }
+    }
+
+    history.sort_by_key(|o| o.timestamp.unwrap_or_default());
+
+    summary
+}
+
+/// Parse the executed orders of a CSV export, the separator (`;` or `,`) is
+/// detected from the header. The ISIN are replaced with their symbol in
+/// `symbols`. Returns the orders and the skipped rows.
+pub fn parse_orders_csv(
+    content: &str,
+    account: &str,
+    symbols: &HashMap<String, String>,
+) -> Result<(Vec<OrderPassed>, Vec<String>)> {
+    let mut lines = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .filter(|l| !l.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("The file is empty"))?;
+    let separator = if header.matches(';').count() >= header.matches(',').count() {
+        ';'
+    } else {
+        ','
+    };
+    let columns: Vec<String> = split_csv_line(header, separator)
+        .iter()
+        .map(|c| normalize(c))
+        .collect();
+
+    let column = |aliases: &[&str]| find_column(&columns, aliases);
+    let required = |aliases: &[&str], name: &str| {
+        column(aliases).ok_or_else(|| anyhow::anyhow!("Missing {} column", name))
+    };
+    let date_col = required(DATE_COLUMNS, "date")?;
+    let side_col = required(SIDE_COLUMNS, "side")?;
+    let symbol_col = required(SYMBOL_COLUMNS, "symbol")?;
+    let quantity_col = required(QUANTITY_COLUMNS, "quantity")?;
+    let price_col = required(PRICE_COLUMNS, "price")?;
+    let id_col = column(ID_COLUMNS);
+    let account_col = column(ACCOUNT_COLUMNS);
+    let status_col = column(STATUS_COLUMNS);
+    let fees_col = column(FEES_COLUMNS);
+
+    let mut orders = vec![];
+    let mut skipped = vec![];
+    // Occurrences of each generated id, see below
+    let mut generated: HashMap<String, usize> = HashMap::new();
+
+    // Line numbers start at 2, after the header
+    for (i, line) in lines.enumerate() {
+        let row = split_csv_line(line, separator);
+        let field = |col: usize| row.get(col).map(|f| f.trim()).unwrap_or_default();
+
+        if let Some(status) = status_col.map(field).map(normalize) {
+            if ["annul", "cancel", "rejet", "reject", "expir"]
+                .iter()
+                .any(|s| status.contains(s))
+            {
+                skipped.push(format!("Line {}: order not executed ({})", i + 2, status));
+                continue;
+            }
+        }
+
+        let parsed = (|| -> Result<OrderPassed> {
+            let timestamp = parse_date(field(date_col))?;
+            let side = parse_side(field(side_col))?;
+            let symbol = field(symbol_col).to_string();
+            if symbol.is_empty() {
+                return Err(anyhow::anyhow!("missing symbol"));
+            }
+            // The orders of the app are keyed by Bourso symbol, an ISIN
+            // would be a separate position
+            let symbol = if is_isin(&symbol) {
+                symbols
+                    .get(&symbol)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("no Bourso symbol for the ISIN {}", symbol))?
+            } else {
+                symbol
+            };
+            let quantity = parse_number(field(quantity_col))?;
+            if quantity <= 0.0 || quantity.fract() != 0.0 {
+                return Err(anyhow::anyhow!("invalid quantity {}", quantity));
+            }
+            let price = parse_number(field(price_col))?;
+            let fees = match fees_col.map(field).filter(|f| !f.is_empty()) {
+                Some(fees) => parse_number(fees)?.abs(),
+                None => 0.0,
+            };
+            let account = account_col
+                .map(field)
+                .filter(|a| !a.is_empty())
+                .unwrap_or(account)
+                .to_string();
+            // Rows without a reference get an id built from their content so
+            // that importing the same file twice does not duplicate them. The
+            // exports with dates only can have identical fills on the same
+            // day, they are numbered in the order of the file.
+            let id = match id_col.map(field).filter(|id| !id.is_empty()) {
+                Some(id) => id.to_string(),
+                None => {
+                    let id = format!(
+                        "import-{}-{}-{}-{}-{}-{}",
+                        account, timestamp, side, quantity, symbol, price
+                    );
+                    let occurrence = generated.entry(id.clone()).or_default();
+                    *occurrence += 1;
+                    if *occurrence == 1 {
+                        id
+                    } else {
+                        format!("{}-{}", id, occurrence)
+                    }
+                }
+            };
+
+            let mut order = OrderPassed::new(
+                id,
+                price,
+                Some(timestamp),
+                OrderArgs {
+                    account,
+                    symbol,
+                    quantity: Some(quantity as u64),
+                    amount: None,
+                    side,
+                },
+                OrderSource::Imported,
+            );
+            order.fees = fees;
+
+            Ok(order)
+        })();
+
+        match parsed {
+            Ok(order) => orders.push(order),
+            Err(e) => skipped.push(format!("Line {}: {}", i + 2, e)),
+        }
+    }
+
+    Ok((orders, skipped))
+}
+
+pub fn find_column(columns: &[String], aliases: &[&str]) -> Option<usize> {
+    aliases
+        .iter()
+        .find_map(|alias| columns.iter().position(|c| c == alias))
+        .or_else(|| {
+            aliases
+                .iter()
+                .find_map(|alias| columns.iter().position(|c| c.starts_with(alias)))
+        })
+}
+
+/// Split a CSV line, fields may be quoted with `"` and contain the separator
+pub fn split_csv_line(line: &str, separator: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+/// Lowercase and strip the accents of the French exports
+pub fn normalize(s: &str) -> String {
+    s.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'à' | 'â' => 'a',
+            'î' | 'ï' => 'i',
+            'ô' => 'o',
+            'ù' | 'û' => 'u',
+            'ç' => 'c',
+            '’' => '\'',
+            c => c,
+        })
+        .collect()
+}
+
+/// Parse `1 234,56`, `1234.56` or `1,234.56 €`
+pub fn parse_number(s: &str) -> Result<f64> {
+    let s: String = s
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, ',' | '.' | '-'))
+        .collect();
+    let s = if s.contains(',') && s.contains('.') {
+        s.replace(',', "")
+    } else {
+        s.replace(',', ".")
+    };
+
+    s.parse()
+        .map_err(|_| anyhow::anyhow!("invalid number {:?}", s))
+}
+
+/// Parse the French `dd/mm/yyyy` dates and ISO dates, with an optional
+/// time, as a local timestamp
+pub fn parse_date(s: &str) -> Result<i64> {
+    const DATE_TIME_FORMATS: &[&str] = &[
+        "%d/%m/%Y %H:%M:%S",
+        "%d/%m/%Y %H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ];
+    const DATE_FORMATS: &[&str] = &["%d/%m/%Y", "%Y-%m-%d", "%d/%m/%y"];
+
+    let date_time = DATE_TIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|f| NaiveDate::parse_from_str(s, f).ok())
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| anyhow::anyhow!("invalid date {:?}", s))?;
+
+    Ok(Local
+        .from_local_datetime(&date_time)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| date_time.and_utc().timestamp()))
+}
+
+/// Whether `s` looks like an ISIN, e.g. `FR0010315770`
+fn is_isin(s: &str) -> bool {
+    s.len() == 12
+        && s.is_ascii()
+        && s[..2].chars().all(|c| c.is_ascii_uppercase())
+        && s[2..]
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        && s[11..].chars().all(|c| c.is_ascii_digit())
+}
+
+fn parse_side(s: &str) -> Result<OrderSide> {
+    let side = normalize(s);
+    if side.contains("achat") || side.starts_with("buy") || side == "a" {
+        Ok(OrderSide::Buy)
+    } else if side.contains("vente") || side.starts_with("sell") || side == "v" {
+        Ok(OrderSide::Sell)
+    } else {
+        Err(anyhow::anyhow!("unknown side {:?}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bourso_export() {
+        let content = "\u{feff}Référence;Date d'exécution;Sens;Libellé;Code ISIN;Quantité;Cours d'exécution;Statut\n\
+            123ABC;15/01/2024 09:05:12;Achat;AMUNDI MSCI WORLD;FR0010315770;3;\"1 234,56\";Exécuté\n\
+            124ABD;16/01/2024;Vente;AMUNDI MSCI WORLD;FR0010315770;1;458,2;Exécuté\n\
+            125ABE;17/01/2024;Achat;AMUNDI MSCI WORLD;FR0010315770;2;450;Annulé\n\
+            126ABF;pas de date;Achat;AMUNDI MSCI WORLD;FR0010315770;2;450;Exécuté\n";
+
+        let symbols = HashMap::from([("FR0010315770".to_string(), "1rTCW8".to_string())]);
+        let (orders, skipped) = parse_orders_csv(content, "1234567890", &symbols).unwrap();
+
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].id, "123ABC");
+        assert_eq!(orders[0].price, 1234.56);
+        assert_eq!(orders[0].args.quantity, Some(3));
+        assert_eq!(orders[0].args.side, OrderSide::Buy);
+        assert_eq!(orders[0].args.symbol, "1rTCW8");
+        assert_eq!(orders[0].args.account, "1234567890");
+        assert_eq!(orders[1].args.side, OrderSide::Sell);
+        assert_eq!(orders[1].price, 458.2);
+        assert_eq!(skipped.len(), 2);
+
+        assert!(is_isin("FR0010315770"));
+        // 12 bytes, but not 12 characters
+        assert!(!is_isin("€€€€"));
+        assert!(!is_isin("éFR00103157"));
+
+        // Without a symbol for the ISIN, the rows are not imported
+        let (orders, skipped) = parse_orders_csv(content, "1234567890", &HashMap::new()).unwrap();
+        assert!(orders.is_empty());
+        assert!(skipped[0].contains("FR0010315770"));
+    }
+
+    #[test]
+    fn test_parse_generic_export() {
+        let content = "date,side,symbol,quantity,price,account,fees\n\
+            2024-03-01,buy,1rTCW8,10,\"1,012.50\",0987654321,1.99\n";
+
+        let (orders, skipped) = parse_orders_csv(content, "1234567890", &HashMap::new()).unwrap();
+
+        assert!(skipped.is_empty());
+        assert_eq!(orders[0].price, 1012.5);
+        assert_eq!(orders[0].args.account, "0987654321");
+        assert_eq!(orders[0].fees, 1.99);
+        assert!(orders[0].id.starts_with("import-"));
+
+        // The generated id is stable between imports
+        let (again, _) = parse_orders_csv(content, "1234567890", &HashMap::new()).unwrap();
+        assert_eq!(orders[0].id, again[0].id);
+    }
+
+    #[test]
+    fn test_identical_fills() {
+        let content = "date;side;symbol;quantity;price
+            2024-03-01;buy;1rTCW8;2;10
+            2024-03-01;buy;1rTCW8;2;10
+";
+
+        let (orders, _) = parse_orders_csv(content, "A", &HashMap::new()).unwrap();
+        assert_ne!(orders[0].id, orders[1].id);
+
+        let mut history = vec![];
+        assert_eq!(merge_orders(&mut history, orders).imported, 2);
+        let (again, _) = parse_orders_csv(content, "A", &HashMap::new()).unwrap();
+        assert_eq!(merge_orders(&mut history, again).duplicates, 2);
+
+        // The same fills on another account, from another file
+        let (other, _) = parse_orders_csv(content, "B", &HashMap::new()).unwrap();
+        assert_eq!(merge_orders(&mut history, other).imported, 2);
+    }
+
+    #[test]
+    fn test_order_filter() {
+        let (orders, _) = parse_orders_csv(
+            "date;side;symbol;quantity;price;account\n\
+            2024-03-01 12:00:00;buy;1rTCW8;1;10;A\n\
+            2024-04-01 12:00:00;buy;1rTCW8;1;10;B\n\
+            2024-05-01 12:00:00;buy;1rAAPL;1;10;A\n",
+            "A",
+            &HashMap::new(),
+        )
+        .unwrap();
+        let count = |filter: OrderFilter| orders.iter().filter(|o| filter.matches(o)).count();
+
+        assert_eq!(count(OrderFilter::default()), 3);
+        assert_eq!(
+            count(OrderFilter {
+                account: Some("A".to_string()),
+                ..Default::default()
+            }),
+            2
+        );
+        assert_eq!(
+            count(OrderFilter {
+                from: NaiveDate::from_ymd_opt(2024, 4, 1),
+                symbol: Some("1rTCW8".to_string()),
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(OrderFilter {
+                to: NaiveDate::from_ymd_opt(2024, 4, 1),
+                ..Default::default()
+            }),
+            2
+        );
+    }
+
+    #[test]
+    fn test_order_update() {
+        let (mut orders, _) = parse_orders_csv(
+            "date;side;symbol;quantity;price\n2024-03-01;buy;1rTCW8;2;10\n",
+            "A",
+            &HashMap::new(),
+        )
+        .unwrap();
+        let order = &mut orders[0];
+
+        let update = OrderUpdate {
+            price: Some(10.5),
+            quantity: Some(2),
+            note: Some("Bought on the website".to_string()),
+            ..Default::default()
+        };
+        update.apply(order, 100).unwrap();
+
+        assert_eq!(order.price, 10.5);
+        assert_eq!(order.note.as_deref(), Some("Bought on the website"));
+        // The quantity did not change
+        assert_eq!(order.edits.len(), 2);
+        assert_eq!(
+            order.edits[0],
+            OrderEdit {
+                timestamp: 100,
+                field: "price".to_string(),
+                from: serde_json::json!(10.0),
+                to: serde_json::json!(10.5),
+            }
+        );
+
+        let clear_note = OrderUpdate {
+            note: Some(" ".to_string()),
+            ..Default::default()
+        };
+        clear_note.apply(order, 200).unwrap();
+        assert_eq!(order.note, None);
+        assert_eq!(order.edits.len(), 3);
+
+        let invalid = OrderUpdate {
+            price: Some(-1.0),
+            ..Default::default()
+        };
+        assert!(invalid.apply(order, 300).is_err());
+        assert_eq!(order.edits.len(), 3);
+    }
+
+    #[test]
+    fn test_merge_orders() {
+        let order = |id: &str, timestamp: i64| {
+            OrderPassed::new(
+                id.to_string(),
+                10.0,
+                Some(timestamp),
+                OrderArgs {
+                    account: "1234567890".to_string(),
+                    symbol: "1rTCW8".to_string(),
+                    quantity: Some(1),
+                    amount: None,
+                    side: OrderSide::Buy,
+                },
+                OrderSource::App,
+            )
+        };
+        let mut history = vec![order("a", 20), order("b", 30)];
+
+        let summary = merge_orders(&mut history, vec![order("b", 30), order("c", 10)]);
+
+        assert_eq!(summary.imported, 1);
+        assert_eq!(summary.duplicates, 1);
+        let ids: Vec<&str> = history.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "a", "b"]);
+    }
+}
@@ -0,0 +1,554 @@
+//! Plain-text accounting export of the history, for Beancount and hledger.
+//!
+//! Orders are posted to a commodity with their cost basis, transfers as two
+//! legs between the ledger accounts, and the balances of the accounts at
+//! the time of the export as balance assertions. Each entry carries the id
+//! of its order or transfer so that the incremental export only appends the
+//! entries not yet in the file.
+
+use anyhow::Result;
+use bourso_api::account::AccountKind;
+use chrono::{Local, NaiveDate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs,
+    io::Write,
+    path::Path,
+};
+use tauri::{async_runtime::Mutex, command, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::history::order_date;
+use crate::order::{load_orders, OrderPassed, OrderSide};
+use crate::scheduler::HISTORY_FILE_PATH;
+use crate::trading::get_cash_balance;
+use crate::transfer::{load_transfers, TransferPassed};
+use crate::BoursoState;
+
+const LEDGER_FILE_PATH: &str = "ledger.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerFormat {
+    #[default]
+    Beancount,
+    Hledger,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LedgerSettings {
+    pub format: LedgerFormat,
+    /// Ledger account names by Bourso account id, `Assets:Bourso:<id>` when
+    /// unset
+    pub accounts: HashMap<String, String>,
+    /// Commodity names by symbol, the symbol made a valid commodity name
+    /// when unset
+    pub commodities: HashMap<String, String>,
+    /// Account of the gains and losses of the sales, Beancount only
+    pub gains_account: String,
+}
+
+impl Default for LedgerSettings {
+    fn default() -> Self {
+        LedgerSettings {
+            format: LedgerFormat::Beancount,
+            accounts: HashMap::new(),
+            commodities: HashMap::new(),
+            gains_account: "Income:Bourso:Gains".to_string(),
+        }
+    }
+}
+
+/// Balance of an account at the time of the export, in euros
+#[derive(Debug, Clone)]
+pub struct BalanceSnapshot {
+    pub account: String,
+    pub amount: f64,
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Default, PartialEq)]
+pub struct LedgerExport {
+    /// Orders and transfers written
+    pub entries: usize,
+    /// Orders and transfers already in the file
+    pub existing: usize,
+    pub balances: usize,
+}
+
+impl LedgerSettings {
+    fn account(&self, id: &str) -> String {
+        self.accounts
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| format!("Assets:Bourso:{}", id))
+    }
+
+    /// Commodity names start with a capital letter and only contain capital
+    /// letters, digits and `'._-`
+    fn commodity(&self, symbol: &str) -> String {
+        if let Some(commodity) = self.commodities.get(symbol) {
+            return commodity.clone();
+        }
+
+        let commodity: String = symbol
+            .to_uppercase()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || "'._-".contains(*c))
+            .take(23)
+            .collect();
+        if commodity.starts_with(|c: char| c.is_ascii_uppercase()) {
+            commodity
+        } else {
+            format!("X{}", commodity)
+        }
+    }
+
+    /// hledger needs quotes around commodities with digits
+    fn quoted_commodity(&self, symbol: &str) -> String {
+        let commodity = self.commodity(symbol);
+        match self.format {
+            LedgerFormat::Beancount => commodity,
+            LedgerFormat::Hledger => format!("\"{}\"", commodity),
+        }
+    }
+}
+
+#[command]
+pub fn get_ledger_settings(app: AppHandle) -> core::result::Result<LedgerSettings, String> {
+    load_ledger_settings(&app.path().app_local_data_dir().unwrap()).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn set_ledger_settings(
+    app: AppHandle,
+    settings: LedgerSettings,
+) -> core::result::Result<(), String> {
+    save_ledger_settings(&app.path().app_local_data_dir().unwrap(), &settings)
+        .map_err(|e| e.to_string())
+}
+
+/// Export the history to a Beancount or hledger file. With `incremental`,
+/// the entries are appended to the existing file, skipping the orders and
+/// transfers it already contains.
+#[command]
+pub async fn export_ledger(
+    app: AppHandle,
+    state: State<'_, Mutex<BoursoState>>,
+    path: String,
+    incremental: bool,
+    include_balances: bool,
+) -> core::result::Result<LedgerExport, String> {
+    let settings = load_ledger_settings(&app.path().app_local_data_dir().unwrap())
+        .map_err(|e| e.to_string())?;
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let orders = load_orders(&store).map_err(|e| e.to_string())?;
+    let transfers = load_transfers(&store).map_err(|e| e.to_string())?;
+
+    let balances = if include_balances {
+        let state = state.lock().await;
+        balance_snapshots(&state.client)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        vec![]
+    };
+
+    let path = Path::new(&path);
+    let existing = if incremental && path.exists() {
+        Some(fs::read_to_string(path).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+
+    let (content, export) = render_ledger(
+        existing.as_deref(),
+        &orders,
+        &transfers,
+        &balances,
+        &settings,
+    );
+
+    let written = if existing.is_some() {
+        fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(content.as_bytes()))
+    } else {
+        fs::write(path, content)
+    };
+    written.map_err(|e| e.to_string())?;
+
+    Ok(export)
+}
+
+/// Balances of the accounts, the cash only for trading accounts since the
+/// securities are tracked as commodities
+async fn balance_snapshots(
+    web_client: &bourso_api::client::BoursoWebClient,
+) -> Result<Vec<BalanceSnapshot>> {
+    let date = Local::now().date_naive();
+    let mut balances = vec![];
+
+    for account in web_client.get_accounts(None).await? {
+        let amount = match account.kind {
+            AccountKind::Loans => continue,
+            AccountKind::Trading => get_cash_balance(web_client, &account.id).await?,
+            _ => account.balance as f64 / 100.0,
+        };
+        balances.push(BalanceSnapshot {
+            account: account.id,
+            amount,
+            date,
+        });
+    }
+
+    Ok(balances)
+}
+
+/// Render the entries not yet in `existing`, sorted by date. Returns the
+/// whole file, or what to append to `existing`.
+pub fn render_ledger(
+    existing: Option<&str>,
+    orders: &[OrderPassed],
+    transfers: &[TransferPassed],
+    balances: &[BalanceSnapshot],
+    settings: &LedgerSettings,
+) -> (String, LedgerExport) {
+    let id_regex = Regex::new(r#"(?:order_id|transfer_id):\s*"?([^"\s]+)"?"#).unwrap();
+    let open_regex = Regex::new(r"(?m)^\d{4}-\d{2}-\d{2} open (\S+)").unwrap();
+    let known_ids: HashSet<&str> = existing
+        .map(|e| {
+            id_regex
+                .captures_iter(e)
+                .filter_map(|c| c.get(1))
+                .map(|m| m.as_str())
+                .collect()
+        })
+        .unwrap_or_default();
+    let opened: HashSet<&str> = existing
+        .map(|e| {
+            open_regex
+                .captures_iter(e)
+                .filter_map(|c| c.get(1))
+                .map(|m| m.as_str())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut export = LedgerExport::default();
+    let mut entries: Vec<(NaiveDate, String)> = vec![];
+    let mut accounts = BTreeSet::new();
+
+    for order in orders {
+        let Some(date) = order.timestamp.and_then(order_date) else {
+            continue;
+        };
+        if known_ids.contains(order.id.as_str()) {
+            export.existing += 1;
+            continue;
+        }
+        accounts.insert(settings.account(&order.args.account));
+        if order.args.side == OrderSide::Sell && settings.format == LedgerFormat::Beancount {
+            accounts.insert(settings.gains_account.clone());
+        }
+        entries.push((date, order_entry(order, date, settings)));
+        export.entries += 1;
+    }
+
+    for transfer in transfers {
+        let Some(date) = order_date(transfer.timestamp) else {
+            continue;
+        };
+        if known_ids.contains(transfer.id.as_str()) {
+            export.existing += 1;
+            continue;
+        }
+        accounts.insert(settings.account(&transfer.from));
+        accounts.insert(settings.account(&transfer.to));
+        entries.push((date, transfer_entry(transfer, date, settings)));
+        export.entries += 1;
+    }
+
+    if let Some(date) = balances.first().map(|b| b.date) {
+        for balance in balances {
+            accounts.insert(settings.account(&balance.account));
+        }
+        entries.push((date, balance_entry(balances, date, settings)));
+        export.balances = balances.len();
+    }
+
+    // Stable sort, entries of the same day keep the history order
+    entries.sort_by_key(|(date, _)| *date);
+
+    let mut ledger = String::new();
+    if settings.format == LedgerFormat::Beancount {
+        if existing.is_none() {
+            ledger.push_str("option \"operating_currency\" \"EUR\"\n\n");
+        }
+        let opens: Vec<&String> = accounts
+            .iter()
+            .filter(|a| !opened.contains(a.as_str()))
+            .collect();
+        for account in &opens {
+            ledger.push_str(&format!("1970-01-01 open {}\n", account));
+        }
+        if !opens.is_empty() {
+            ledger.push('\n');
+        }
+    }
+    for (_, entry) in entries {
+        ledger.push_str(&entry);
+        ledger.push('\n');
+    }
+
+    (ledger, export)
+}
+
+fn narration(s: &str) -> String {
+    s.replace('"', "'")
+}
+
+fn order_entry(order: &OrderPassed, date: NaiveDate, settings: &LedgerSettings) -> String {
+    let account = settings.account(&order.args.account);
+    let commodity = settings.quoted_commodity(&order.args.symbol);
+    let quantity = order.executed_quantity();
+    let cash = quantity as f64 * order.price;
+    let title = format!("{} {} {}", order.args.side, quantity, order.args.symbol);
+
+    match (settings.format, order.args.side) {
+        (LedgerFormat::Beancount, OrderSide::Buy) => format!(
+            "{date} * \"{title}\"\n  order_id: \"{id}\"\n  {account}  {quantity} {commodity} {{{price} EUR}}\n  {account}  {cash:.2} EUR\n",
+            title = narration(&title),
+            id = narration(&order.id),
+            price = order.price,
+            cash = -cash,
+        ),
+        // The lot is picked by the booking method of the account, the
+        // difference with its cost goes to the gains account
+        (LedgerFormat::Beancount, OrderSide::Sell) => format!(
+            "{date} * \"{title}\"\n  order_id: \"{id}\"\n  {account}  -{quantity} {commodity} {{}} @ {price} EUR\n  {account}  {cash:.2} EUR\n  {gains}\n",
+            title = narration(&title),
+            id = narration(&order.id),
+            price = order.price,
+            gains = settings.gains_account,
+        ),
+        (LedgerFormat::Hledger, side) => {
+            let (quantity, cash) = match side {
+                OrderSide::Buy => (quantity as i64, -cash),
+                OrderSide::Sell => (-(quantity as i64), cash),
+            };
+            format!(
+                "{date} * {title}  ; order_id: {id}\n  {account}  {quantity} {commodity} @ {price} EUR\n  {account}  {cash:.2} EUR\n",
+                id = order.id,
+                price = order.price,
+            )
+        }
+    }
+}
+
+fn transfer_entry(transfer: &TransferPassed, date: NaiveDate, settings: &LedgerSettings) -> String {
+    let from = settings.account(&transfer.from);
+    let to = settings.account(&transfer.to);
+    let reason = transfer.reason.as_deref().unwrap_or("Transfer");
+
+    match settings.format {
+        LedgerFormat::Beancount => format!(
+            "{date} * \"{reason}\"\n  transfer_id: \"{id}\"\n  {from}  {amount:.2} EUR\n  {to}  {credit:.2} EUR\n",
+            reason = narration(reason),
+            id = transfer.id,
+            amount = -transfer.amount,
+            credit = transfer.amount,
+        ),
+        LedgerFormat::Hledger => format!(
+            "{date} * {reason}  ; transfer_id: {id}\n  {from}  {amount:.2} EUR\n  {to}  {credit:.2} EUR\n",
+            id = transfer.id,
+            amount = -transfer.amount,
+            credit = transfer.amount,
+        ),
+    }
+}
+
+fn balance_entry(
+    balances: &[BalanceSnapshot],
+    date: NaiveDate,
+    settings: &LedgerSettings,
+) -> String {
+    match settings.format {
+        // Beancount checks the balance at the beginning of the day
+        LedgerFormat::Beancount => balances
+            .iter()
+            .map(|b| {
+                format!(
+                    "{} balance {}  {:.2} EUR\n",
+                    date.succ_opt().unwrap_or(date),
+                    settings.account(&b.account),
+                    b.amount
+                )
+            })
+            .collect(),
+        LedgerFormat::Hledger => {
+            let mut entry = format!("{} Balances\n", date);
+            for b in balances {
+                entry.push_str(&format!(
+                    "  {}  0 EUR = {:.2} EUR\n",
+                    settings.account(&b.account),
+                    b.amount
+                ));
+            }
+            entry
+        }
+    }
+}
+
+pub fn load_ledger_settings(app_local_data_dir: &Path) -> Result<LedgerSettings> {
+    let settings_path = app_local_data_dir.join(LEDGER_FILE_PATH);
+
+    if !fs::exists(&settings_path)? {
+        return Ok(LedgerSettings::default());
+    }
+
+    let content = fs::read_to_string(settings_path)?;
+
+    if content.trim().is_empty() {
+        return Ok(LedgerSettings::default());
+    }
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_ledger_settings(app_local_data_dir: &Path, settings: &LedgerSettings) -> Result<()> {
+    let settings_path = app_local_data_dir.join(LEDGER_FILE_PATH);
+
+    fs::write(settings_path, serde_json::to_string(settings)?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{OrderArgs, OrderSource};
+
+    fn order(id: &str, side: OrderSide) -> OrderPassed {
+        OrderPassed::new(
+            id.to_string(),
+            12.5,
+            // 2024-03-01 12:00:00 UTC
+            Some(1_709_294_400),
+            OrderArgs {
+                account: "pea".to_string(),
+                symbol: "1rTCW8".to_string(),
+                quantity: Some(4),
+                amount: None,
+                side,
+            },
+            OrderSource::App,
+        )
+    }
+
+    fn transfer() -> TransferPassed {
+        TransferPassed {
+            id: "t1".to_string(),
+            from: "checking".to_string(),
+            to: "pea".to_string(),
+            amount: 100.0,
+            timestamp: 1_709_208_000,
+            reason: Some("DCA".to_string()),
+        }
+    }
+
+    fn settings(format: LedgerFormat) -> LedgerSettings {
+        LedgerSettings {
+            format,
+            accounts: HashMap::from([("pea".to_string(), "Assets:Bourso:PEA".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_beancount() {
+        let (ledger, export) = render_ledger(
+            None,
+            &[order("o1", OrderSide::Buy), order("o2", OrderSide::Sell)],
+            &[transfer()],
+            &[],
+            &settings(LedgerFormat::Beancount),
+        );
+
+        assert_eq!(export.entries, 3);
+        assert!(ledger.contains("1970-01-01 open Assets:Bourso:PEA\n"));
+        assert!(ledger.contains("1970-01-01 open Assets:Bourso:checking\n"));
+        assert!(ledger.contains("1970-01-01 open Income:Bourso:Gains\n"));
+        assert!(ledger.contains(
+            "  order_id: \"o1\"\n  Assets:Bourso:PEA  4 X1RTCW8 {12.5 EUR}\n  Assets:Bourso:PEA  -50.00 EUR\n"
+        ));
+        assert!(ledger.contains("  Assets:Bourso:PEA  -4 X1RTCW8 {} @ 12.5 EUR\n  Assets:Bourso:PEA  50.00 EUR\n  Income:Bourso:Gains\n"));
+        assert!(ledger.contains(
+            "  transfer_id: \"t1\"\n  Assets:Bourso:checking  -100.00 EUR\n  Assets:Bourso:PEA  100.00 EUR\n"
+        ));
+        // The transfer happened the day before the orders
+        assert!(ledger.find("transfer_id").unwrap() < ledger.find("order_id").unwrap());
+    }
+
+    #[test]
+    fn test_hledger() {
+        let balances = [BalanceSnapshot {
+            account: "checking".to_string(),
+            amount: 1234.5,
+            date: NaiveDate::from_ymd_opt(2024, 3, 2).unwrap(),
+        }];
+        let (ledger, export) = render_ledger(
+            None,
+            &[order("o1", OrderSide::Sell)],
+            &[],
+            &balances,
+            &settings(LedgerFormat::Hledger),
+        );
+
+        assert_eq!(export.balances, 1);
+        assert!(!ledger.contains(" open "));
+        assert!(ledger.contains(
+            "; order_id: o1\n  Assets:Bourso:PEA  -4 \"X1RTCW8\" @ 12.5 EUR\n  Assets:Bourso:PEA  50.00 EUR\n"
+        ));
+        assert!(
+            ledger.contains("2024-03-02 Balances\n  Assets:Bourso:checking  0 EUR = 1234.50 EUR\n")
+        );
+    }
+
+    #[test]
+    fn test_incremental() {
+        let settings = settings(LedgerFormat::Beancount);
+        let (existing, _) = render_ledger(
+            None,
+            &[order("o1", OrderSide::Buy)],
+            &[transfer()],
+            &[],
+            &settings,
+        );
+
+        let (appended, export) = render_ledger(
+            Some(&existing),
+            &[order("o1", OrderSide::Buy), order("o2", OrderSide::Buy)],
+            &[transfer()],
+            &[],
+            &settings,
+        );
+
+        assert_eq!(
+            export,
+            LedgerExport {
+                entries: 1,
+                existing: 2,
+                balances: 0
+            }
+        );
+        assert!(appended.contains("order_id: \"o2\""));
+        assert!(!appended.contains("order_id: \"o1\""));
+        assert!(!appended.contains("option"));
+        assert!(!appended.contains(" open "));
+    }
+}
@@ -1,32 +1,71 @@
 use std::process::exit;
 
+use analytics::get_performance;
 use assets::{get_saved_assets, save_assets};
+use backtest::backtest_dca;
+use benchmark::compare_benchmark;
 use bourso_api::{
     client::{trade::tick::GetTicksEOD, BoursoWebClient},
     get_client,
 };
 use cron::{deactivate_dca_scheduler, init_dca_scheduler, is_dca_scheduler_setup};
+use envelope::{get_envelopes, set_envelopes};
+use events::{add_event_cmd, delete_event_cmd, get_events, import_events_csv};
+use export::export_orders_cmd;
+use history::{add_order_cmd, delete_order_cmd, import_orders_csv, update_order_cmd};
+use ledger::{export_ledger, get_ledger_settings, set_ledger_settings};
+use limits::{get_limits, set_limits};
 use futures_util::{pin_mut, StreamExt};
 use log::{debug, info};
 use order::{get_orders_cmd, new_order_cmd};
+use portfolio::get_positions;
+use query::query_orders;
+use rebalance::get_rebalance_report;
+use reconcile::reconcile_positions;
 use scheduler::{run_job_manually, skip_dca_job};
 use sentry;
+use snapshot::get_net_worth;
 use tauri::{
     async_runtime::{block_on, Mutex},
     AppHandle, Emitter, Manager, State,
 };
 use tauri_plugin_cli::CliExt;
 use tauri_plugin_sentry;
+use tauri_plugin_store::StoreExt;
 use tauri_plugin_updater::UpdaterExt;
+use tax::{export_tax_report, get_tax_report, set_pea_accounts};
 use trading::get_trading_summary;
 
+mod analytics;
 mod assets;
+mod backtest;
+mod basket;
+mod benchmark;
 mod cli;
 mod cron;
+mod envelope;
+mod events;
+mod export;
 mod files;
+mod history;
+mod ledger;
+mod limits;
 mod order;
+mod portfolio;
+mod price_guard;
+mod query;
+mod rebalance;
+mod reconcile;
+mod reinvest;
 mod scheduler;
+mod snapshot;
+mod split;
+mod tax;
+mod ticks;
 mod trading;
+mod transfer;
+mod trigger;
+mod value_averaging;
 
 struct BoursoState {
     pub client: BoursoWebClient,
@@ -58,10 +97,21 @@ async fn init(
 
     update_embed_files(&app).expect("error while updating embedded files");
 
+    let mut jobs_to_run = state.jobs_to_run.clone();
+    // Jobs above a soft limit are left due by the scheduler until the user
+    // confirms them from the UI
+    let jobs = scheduler::load_jobs(&app.path().app_local_data_dir().unwrap())
+        .map_err(|e| e.to_string())?;
+    for job in jobs {
+        if job.awaiting_confirmation() && !jobs_to_run.iter().any(|j| j.id == job.id) {
+            jobs_to_run.push(job);
+        }
+    }
+
     Ok(InitResponse {
         // defined by CLI helper if the app was started by a CRON job
         dca_without_password: state.dca_without_password,
-        jobs_to_run: state.jobs_to_run.clone(),
+        jobs_to_run,
     })
 }
 
@@ -152,47 +202,91 @@ async fn get_pending_mfa(state: State<'_, Mutex<BoursoState>>) -> Result<Option<
 #[tauri::command]
 async fn get_accounts(
     state: State<'_, Mutex<BoursoState>>,
+    app: AppHandle,
 ) -> Result<Vec<bourso_api::account::Account>, ()> {
-    let state = state.lock().await;
     let accounts = state
+        .lock()
+        .await
         .client
         .get_accounts(None)
         .await
         .expect("error while getting accounts");
+
+    // The accounts are fetched when the app starts, take the snapshot of the
+    // day if the scheduler did not. It needs the client for the positions,
+    // so it is taken in the background once the accounts are returned.
+    let snapshot_accounts = accounts.clone();
+    tauri::async_runtime::spawn(async move {
+        let store = match app.store(scheduler::HISTORY_FILE_PATH) {
+            Ok(store) => store,
+            Err(e) => {
+                log::error!("Error opening the history store: {}", e);
+                return;
+            }
+        };
+        if !snapshot::snapshot_due(&store).unwrap_or(true) {
+            return;
+        }
+
+        let state = app.state::<Mutex<BoursoState>>();
+        let state = state.lock().await;
+        if let Err(e) =
+            snapshot::snapshot_if_due(&state.client, &store, Some(snapshot_accounts)).await
+        {
+            log::error!("Error taking the net worth snapshot: {}", e);
+        }
+    });
+
     Ok(accounts)
 }
 
+/// Transfer between two accounts, returns the ceiling exceeded when its
+/// action is to warn
 #[tauri::command]
 async fn transfer_funds(
     source_account_id: &str,
     target_account_id: &str,
     amount: f64,
     reason: &str,
+    confirmation: Option<limits::Confirmation>,
     state: State<'_, Mutex<BoursoState>>,
     app: AppHandle,
-) -> Result<(), String> {
-    let state = state.lock().await;
+) -> Result<Option<limits::LimitError>, String> {
+    let limits = limits::load_limits(&app.path().app_local_data_dir().unwrap())
+        .map_err(|e| e.to_string())?;
+    // Prefixed so that the UI can ask for a confirmation, like `qrcode:`
+    if let Err(e) = limits.check_transfer(amount, confirmation.as_ref()) {
+        return Err(format!("limit:{}", serde_json::json!(e)));
+    }
 
-    let accounts = state
-        .client
-        .get_accounts(None)
-        .await
-        .expect("error while getting accounts");
+    let store = app
+        .store(scheduler::HISTORY_FILE_PATH)
+        .map_err(|e| e.to_string())?;
+    let envelopes = envelope::load_envelopes(&app.path().app_local_data_dir().unwrap())
+        .map_err(|e| e.to_string())?;
+    let transfers = transfer::load_transfers(&store).map_err(|e| e.to_string())?;
+    let warning = match envelope::check_ceiling(&envelopes, &transfers, target_account_id, amount) {
+        Ok(warning) => warning,
+        Err(e) => return Err(format!("limit:{}", serde_json::json!(e))),
+    };
+    if let Some(warning) = &warning {
+        log::warn!("{}", warning);
+    }
 
-    let source_account = accounts
-        .iter()
-        .find(|a| a.id == source_account_id)
-        .expect("source account not found");
+    let state = state.lock().await;
 
-    let target_account = accounts
-        .iter()
-        .find(|a| a.id == target_account_id)
-        .expect("target account not found");
+    let (source_account, target_account) = transfer::find_transfer_accounts(
+        &state.client,
+        source_account_id,
+        target_account_id,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
 
     let stream = state.client.transfer_funds(
         amount,
-        source_account.clone(),
-        target_account.clone(),
+        source_account,
+        target_account,
         Some(reason.to_string()),
     );
 
@@ -210,7 +304,15 @@ async fn transfer_funds(
         }
     }
 
-    Ok(())
+    let transfer = transfer::TransferPassed::new(
+        source_account_id,
+        target_account_id,
+        amount,
+        Some(reason.to_string()),
+    );
+    transfer::push_transfer(&store, &transfer).map_err(|e| e.to_string())?;
+
+    Ok(warning)
 }
 
 #[tauri::command]
@@ -364,16 +466,43 @@ pub fn run() {
             delete_scheduled_job,
             deactivate_dca_scheduler,
             get_orders_cmd,
+            query_orders,
+            get_positions,
+            reconcile_positions,
+            get_tax_report,
+            set_pea_accounts,
+            export_tax_report,
+            import_orders_csv,
+            add_order_cmd,
+            update_order_cmd,
+            delete_order_cmd,
+            get_events,
+            add_event_cmd,
+            delete_event_cmd,
+            import_events_csv,
+            export_orders_cmd,
+            export_ledger,
+            get_ledger_settings,
+            set_ledger_settings,
             get_saved_assets,
             save_assets,
             get_trading_summary,
+            get_net_worth,
+            get_performance,
+            compare_benchmark,
+            get_rebalance_report,
             run_job_manually,
+            backtest_dca,
             skip_dca_job,
             new_order_cmd,
             check_mfa,
             get_pending_mfa,
             check_for_updates,
             update,
+            get_limits,
+            set_limits,
+            get_envelopes,
+            set_envelopes,
         ])
         .setup(|app| {
             app.manage(Mutex::new(BoursoState {
@@ -394,6 +523,7 @@ pub fn run() {
                                 if e.to_string().contains("password not found") {
                                     // by returning nothing, we open the desktop app
                                 } else {
+                                    eprintln!("Error: {:#}", e);
                                     exit(-1);
                                 }
                             }
@@ -0,0 +1,458 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, fmt::Display, fs, path::Path};
+use tauri::{command, AppHandle, Manager};
+
+const LIMITS_FILE_PATH: &str = "limits.json";
+/// Rounds of SHA-256 of the PIN hash, to slow down guessing a short PIN
+const PIN_HASH_ROUNDS: u32 = 100_000;
+
+/// Guardrails against typos in orders and transfers, e.g. 1000 shares
+/// instead of 10. They are enforced for orders placed from the UI, from the
+/// CLI and by scheduled jobs.
+///
+/// Hard limits reject the operation, soft thresholds require an explicit
+/// confirmation, or the PIN when one is set.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Limits {
+    /// Maximum value of a single order, in euros
+    #[serde(default)]
+    pub max_order_value: Option<f64>,
+    /// Maximum quantity of a single order, per symbol
+    #[serde(default)]
+    pub max_quantity: HashMap<String, u64>,
+    /// Maximum amount of a single transfer, in euros
+    #[serde(default)]
+    pub max_transfer_amount: Option<f64>,
+    /// Orders worth more than this need a confirmation, in euros
+    #[serde(default)]
+    pub confirm_order_value: Option<f64>,
+    /// Transfers above this amount need a confirmation, in euros
+    #[serde(default)]
+    pub confirm_transfer_amount: Option<f64>,
+    /// Salted hash of the PIN required to confirm, see `hash_pin`. A simple
+    /// confirmation is enough when unset.
+    #[serde(default)]
+    pub pin_hash: Option<String>,
+    /// PIN saved in clear by the previous versions, hashed on load
+    #[serde(default, rename = "pin", skip_serializing)]
+    legacy_pin: Option<String>,
+}
+
+/// Confirmation sent by the UI for operations above a soft threshold
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Confirmation {
+    #[serde(default)]
+    pub pin: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LimitError {
+    OrderValueExceeded {
+        value: f64,
+        max: f64,
+    },
+    QuantityExceeded {
+        symbol: String,
+        quantity: u64,
+        max: u64,
+    },
+    TransferAmountExceeded {
+        amount: f64,
+        max: f64,
+    },
+    /// The transfer goes over the contribution ceiling of the account
+    CeilingExceeded {
+        account: String,
+        amount: f64,
+        remaining: f64,
+    },
+    /// Above a soft threshold, `pin` tells whether the PIN is expected
+    ConfirmationRequired {
+        value: f64,
+        threshold: f64,
+        pin: bool,
+    },
+    WrongPin,
+}
+
+impl Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitError::OrderValueExceeded { value, max } => {
+                write!(
+                    f,
+                    "Order value {:.2}€ exceeds the limit of {:.2}€",
+                    value, max
+                )
+            }
+            LimitError::QuantityExceeded {
+                symbol,
+                quantity,
+                max,
+            } => write!(
+                f,
+                "Quantity {} of {} exceeds the limit of {}",
+                quantity, symbol, max
+            ),
+            LimitError::TransferAmountExceeded { amount, max } => write!(
+                f,
+                "Transfer amount {:.2}€ exceeds the limit of {:.2}€",
+                amount, max
+            ),
+            LimitError::CeilingExceeded {
+                account,
+                amount,
+                remaining,
+            } => write!(
+                f,
+                "Transfer of {:.2}€ exceeds the ceiling of account {}, {:.2}€ left",
+                amount, account, remaining
+            ),
+            LimitError::ConfirmationRequired {
+                value, threshold, ..
+            } => write!(
+                f,
+                "{:.2}€ is above {:.2}€ and needs a confirmation",
+                value, threshold
+            ),
+            LimitError::WrongPin => write!(f, "Wrong PIN"),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl Limits {
+    /// Check an order of `quantity` shares of `symbol` at `price`
+    pub fn check_order(
+        &self,
+        symbol: &str,
+        quantity: u64,
+        price: f64,
+        confirmation: Option<&Confirmation>,
+    ) -> Result<(), LimitError> {
+        if let Some(max) = self.max_quantity.get(symbol) {
+            if quantity > *max {
+                return Err(LimitError::QuantityExceeded {
+                    symbol: symbol.to_string(),
+                    quantity,
+                    max: *max,
+                });
+            }
+        }
+
+        let value = quantity as f64 * price;
+        if let Some(max) = self.max_order_value {
+            if value > max {
+                return Err(LimitError::OrderValueExceeded { value, max });
+            }
+        }
+
+        self.check_confirmation(value, self.confirm_order_value, confirmation)
+    }
+
+    pub fn check_transfer(
+        &self,
+        amount: f64,
+        confirmation: Option<&Confirmation>,
+    ) -> Result<(), LimitError> {
+        if let Some(max) = self.max_transfer_amount {
+            if amount > max {
+                return Err(LimitError::TransferAmountExceeded { amount, max });
+            }
+        }
+
+        self.check_confirmation(amount, self.confirm_transfer_amount, confirmation)
+    }
+
+    fn check_confirmation(
+        &self,
+        value: f64,
+        threshold: Option<f64>,
+        confirmation: Option<&Confirmation>,
+    ) -> Result<(), LimitError> {
+        let Some(threshold) = threshold.filter(|threshold| value > *threshold) else {
+            return Ok(());
+        };
+
+        match (confirmation, &self.pin_hash) {
+            (None, pin_hash) => Err(LimitError::ConfirmationRequired {
+                value,
+                threshold,
+                pin: pin_hash.is_some(),
+            }),
+            (Some(confirmation), Some(pin_hash))
+                if !confirmation
+                    .pin
+                    .as_ref()
+                    .is_some_and(|pin| verify_pin(pin, pin_hash)) =>
+            {
+                Err(LimitError::WrongPin)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn set_pin(&mut self, pin: &str) -> Result<()> {
+        self.pin_hash = Some(hash_pin(pin)?);
+        Ok(())
+    }
+}
+
+/// Hash `pin` with a random salt, as `salt$hash` in hexadecimal
+pub fn hash_pin(pin: &str) -> Result<String> {
+    let mut salt = [0u8; 16];
+    getrandom::fill(&mut salt).map_err(|e| anyhow::anyhow!("No random salt: {}", e))?;
+
+    Ok(format!("{}${}", to_hex(&salt), pin_digest(pin, &salt)))
+}
+
+/// Whether `pin` matches a hash of `hash_pin`
+pub fn verify_pin(pin: &str, pin_hash: &str) -> bool {
+    let Some((salt, hash)) = pin_hash.split_once('$') else {
+        return false;
+    };
+    let Some(salt) = from_hex(salt) else {
+        return false;
+    };
+
+    pin_digest(pin, &salt) == hash
+}
+
+fn pin_digest(pin: &str, salt: &[u8]) -> String {
+    let mut digest = Sha256::digest([salt, pin.as_bytes()].concat()).to_vec();
+    for _ in 1..PIN_HASH_ROUNDS {
+        digest = Sha256::digest([salt, &digest].concat()).to_vec();
+    }
+
+    to_hex(&digest)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+pub fn load_limits(app_local_data_dir: &Path) -> Result<Limits> {
+    let limits_path = app_local_data_dir.join(LIMITS_FILE_PATH);
+
+    if !fs::exists(&limits_path)? {
+        return Ok(Limits::default());
+    }
+
+    let content = fs::read_to_string(limits_path)?;
+
+    if content.trim().is_empty() {
+        return Ok(Limits::default());
+    }
+
+    let mut limits: Limits = serde_json::from_str(&content)?;
+    // Replace the PIN saved in clear by its hash
+    if let Some(pin) = limits.legacy_pin.take() {
+        limits.set_pin(&pin)?;
+        save_limits(app_local_data_dir, &limits)?;
+    }
+
+    Ok(limits)
+}
+
+pub fn save_limits(app_local_data_dir: &Path, limits: &Limits) -> Result<()> {
+    let limits_path = app_local_data_dir.join(LIMITS_FILE_PATH);
+
+    fs::write(limits_path, serde_json::to_string(limits)?)?;
+
+    Ok(())
+}
+
+/// Limits without the PIN, for the settings screen
+#[derive(Debug, Serialize)]
+pub struct LimitsSettings {
+    #[serde(flatten)]
+    pub limits: Limits,
+    pub pin_set: bool,
+}
+
+#[command]
+pub fn get_limits(app: AppHandle) -> Result<LimitsSettings, String> {
+    let app_local_data_dir = app.path().app_local_data_dir().unwrap();
+    let mut limits = load_limits(&app_local_data_dir).map_err(|e| e.to_string())?;
+
+    let pin_set = limits.pin_hash.take().is_some();
+
+    Ok(LimitsSettings { limits, pin_set })
+}
+
+/// Replace the limits, the current PIN is required when one is set. The
+/// stored PIN is kept unless `new_pin` is set, an empty `new_pin` removes
+/// it.
+#[command]
+pub fn set_limits(
+    app: AppHandle,
+    limits: Limits,
+    current_pin: Option<String>,
+    new_pin: Option<String>,
+) -> Result<(), String> {
+    let app_local_data_dir = app.path().app_local_data_dir().unwrap();
+    let current = load_limits(&app_local_data_dir).map_err(|e| e.to_string())?;
+
+    let limits = update_limits(&current, limits, current_pin, new_pin)?;
+
+    save_limits(&app_local_data_dir, &limits).map_err(|e| e.to_string())
+}
+
+/// Limits to save from the settings screen, which does not know the PIN
+fn update_limits(
+    current: &Limits,
+    mut limits: Limits,
+    current_pin: Option<String>,
+    new_pin: Option<String>,
+) -> Result<Limits, String> {
+    if let Some(pin_hash) = &current.pin_hash {
+        if !current_pin.is_some_and(|pin| verify_pin(&pin, pin_hash)) {
+            return Err(LimitError::WrongPin.to_string());
+        }
+    }
+
+    limits.legacy_pin = None;
+    match new_pin.as_deref().map(str::trim) {
+        None => limits.pin_hash = current.pin_hash.clone(),
+        Some("") => limits.pin_hash = None,
+        Some(pin) => limits.set_pin(pin).map_err(|e| e.to_string())?,
+    }
+
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits {
+            max_order_value: Some(5000.0),
+            max_quantity: HashMap::from([("1rTCW8".to_string(), 50)]),
+            max_transfer_amount: Some(10000.0),
+            confirm_order_value: Some(1000.0),
+            confirm_transfer_amount: None,
+            pin_hash: None,
+            legacy_pin: None,
+        }
+    }
+
+    #[test]
+    fn test_order_limits() {
+        let limits = limits();
+
+        assert!(limits.check_order("1rTCW8", 10, 50.0, None).is_ok());
+        assert_eq!(
+            limits.check_order("1rTCW8", 1000, 1.0, None),
+            Err(LimitError::QuantityExceeded {
+                symbol: "1rTCW8".to_string(),
+                quantity: 1000,
+                max: 50
+            })
+        );
+        assert_eq!(
+            limits.check_order("1rTPSP5", 1000, 10.0, None),
+            Err(LimitError::OrderValueExceeded {
+                value: 10000.0,
+                max: 5000.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_confirmation_threshold() {
+        let mut limits = limits();
+
+        assert!(matches!(
+            limits.check_order("1rTCW8", 40, 50.0, None),
+            Err(LimitError::ConfirmationRequired { pin: false, .. })
+        ));
+        assert!(limits
+            .check_order("1rTCW8", 40, 50.0, Some(&Confirmation::default()))
+            .is_ok());
+
+        limits.set_pin("1234").unwrap();
+        assert_eq!(
+            limits.check_order("1rTCW8", 40, 50.0, Some(&Confirmation::default())),
+            Err(LimitError::WrongPin)
+        );
+        let confirmation = Confirmation {
+            pin: Some("1234".to_string()),
+        };
+        assert!(limits
+            .check_order("1rTCW8", 40, 50.0, Some(&confirmation))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_pin() {
+        let pin_hash = hash_pin("1234").unwrap();
+        assert!(!pin_hash.contains("1234"));
+        assert!(verify_pin("1234", &pin_hash));
+        assert!(!verify_pin("4321", &pin_hash));
+        // Salted
+        assert_ne!(hash_pin("1234").unwrap(), pin_hash);
+
+        // The legacy PIN is read but never written back in clear
+        let legacy: Limits = serde_json::from_str(r#"{"pin": "1234"}"#).unwrap();
+        assert_eq!(legacy.legacy_pin.as_deref(), Some("1234"));
+        assert!(!serde_json::to_string(&legacy).unwrap().contains("1234"));
+    }
+
+    #[test]
+    fn test_update_limits() {
+        let mut current = limits();
+        current.set_pin("1234").unwrap();
+
+        // The settings screen saves the limits without the PIN
+        let mut settings = current.clone();
+        settings.pin_hash = None;
+        settings.max_order_value = Some(2000.0);
+        assert!(update_limits(&current, settings.clone(), None, None).is_err());
+
+        let updated =
+            update_limits(&current, settings.clone(), Some("1234".to_string()), None).unwrap();
+        assert_eq!(updated.max_order_value, Some(2000.0));
+        assert_eq!(updated.pin_hash, current.pin_hash);
+
+        let updated = update_limits(
+            &current,
+            settings.clone(),
+            Some("1234".to_string()),
+            Some("0000".to_string()),
+        )
+        .unwrap();
+        assert!(verify_pin("0000", updated.pin_hash.as_ref().unwrap()));
+
+        let updated = update_limits(
+            &current,
+            settings,
+            Some("1234".to_string()),
+            Some(String::new()),
+        )
+        .unwrap();
+        assert_eq!(updated.pin_hash, None);
+    }
+
+    #[test]
+    fn test_transfer_limits() {
+        let limits = limits();
+
+        assert!(limits.check_transfer(500.0, None).is_ok());
+        assert!(limits.check_transfer(20000.0, None).is_err());
+    }
+}
@@ -1,10 +1,16 @@
 use anyhow::Result;
+use bourso_api::client::trade::order::{OrderData, OrderType};
 use chrono::{Local, DateTime};
 use serde::{Deserialize, Serialize};
-use tauri::{async_runtime::Mutex, command, AppHandle, Manager};
-use tauri_plugin_store::StoreExt;
+use std::{fmt::Display, str::FromStr};
+use tauri::{async_runtime::Mutex, command, AppHandle, Manager, Wry};
+use tauri_plugin_store::{Store, StoreExt};
 
-use crate::{scheduler::HISTORY_FILE_PATH, BoursoState};
+use crate::{
+    limits::{load_limits, Confirmation, LimitError},
+    scheduler::HISTORY_FILE_PATH,
+    BoursoState,
+};
 
 /// Bourso is saving the orders for a year, let's save them longer with
 /// this struct that gets saved locally
@@ -14,6 +20,210 @@ pub struct OrderPassed {
     pub price: f64,
     pub timestamp: Option<i64>,
     pub args: OrderArgs,
+    /// Where the record comes from, orders saved before it was tracked were
+    /// passed with the app
+    #[serde(default)]
+    pub source: OrderSource,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Changes made to the record after it was saved
+    #[serde(default)]
+    pub edits: Vec<OrderEdit>,
+    /// Scheduled job that placed the order
+    #[serde(default)]
+    pub job_id: Option<String>,
+    /// Brokerage fees in euros, unknown for the orders passed before they
+    /// were tracked
+    #[serde(default)]
+    pub fees: f64,
+    /// Bourso did not return the execution price, `price` is the quote at
+    /// the time of the order
+    #[serde(default)]
+    pub estimated_price: bool,
+}
+
+impl OrderPassed {
+    pub fn new(
+        id: String,
+        price: f64,
+        timestamp: Option<i64>,
+        args: OrderArgs,
+        source: OrderSource,
+    ) -> Self {
+        OrderPassed {
+            id,
+            price,
+            timestamp,
+            args,
+            source,
+            note: None,
+            tags: vec![],
+            edits: vec![],
+            job_id: None,
+            fees: 0.0,
+            estimated_price: false,
+        }
+    }
+
+    /// Shares of the order. The orders by amount of the first versions did
+    /// not record their quantity, it is the amount over the price as when
+    /// they were placed.
+    pub fn executed_quantity(&self) -> u64 {
+        self.args.quantity.unwrap_or_else(|| {
+            self.args
+                .amount
+                .map(|amount| qty_for_budget(amount, self.price))
+                .unwrap_or_default()
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSource {
+    /// Placed from the orders panel
+    #[default]
+    App,
+    /// Placed by a scheduled job
+    Dca,
+    /// Added by hand, e.g. an order placed on the website
+    Manual,
+    /// Imported from an export of the website
+    Imported,
+}
+
+/// Change of a field of an order record
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OrderEdit {
+    pub timestamp: i64,
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl Display for OrderSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OrderSide::Buy => "buy",
+                OrderSide::Sell => "sell",
+            }
+        )
+    }
+}
+
+impl FromStr for OrderSide {
+    type Err = OrderError;
+
+    fn from_str(side: &str) -> core::result::Result<Self, Self::Err> {
+        match side {
+            "buy" => Ok(OrderSide::Buy),
+            "sell" => Ok(OrderSide::Sell),
+            _ => Err(OrderError::UnknownSide {
+                side: side.to_string(),
+            }),
+        }
+    }
+}
+
+impl From<OrderSide> for bourso_api::client::trade::order::OrderSide {
+    fn from(side: OrderSide) -> Self {
+        match side {
+            OrderSide::Buy => bourso_api::client::trade::order::OrderSide::Buy,
+            OrderSide::Sell => bourso_api::client::trade::order::OrderSide::Sell,
+        }
+    }
+}
+
+/// Errors returned to the UI when placing an order
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OrderError {
+    UnknownSide { side: String },
+    ZeroQuantity,
+    /// The quantity does not fit in the type expected by Bourso
+    QuantityOverflow { quantity: u64 },
+    MarketClosed,
+    /// The order is above one of the configured limits
+    Limit { limit: LimitError },
+    Bourso { message: String },
+    Storage { message: String },
+}
+
+impl OrderError {
+    fn storage(e: impl std::fmt::Debug) -> Self {
+        OrderError::Storage {
+            message: format!("{:?}", e),
+        }
+    }
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::UnknownSide { side } => write!(f, "Unknown order side: {}", side),
+            OrderError::ZeroQuantity => write!(f, "Quantity must be greater than 0"),
+            OrderError::QuantityOverflow { quantity } => {
+                write!(f, "Quantity {} is too large", quantity)
+            }
+            OrderError::MarketClosed => write!(f, "Market is closed"),
+            OrderError::Limit { limit } => write!(f, "{}", limit),
+            OrderError::Bourso { message } => write!(f, "Error from Bourso: {}", message),
+            OrderError::Storage { message } => write!(f, "Error while saving order: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// Order validated at the IPC boundary, ready to be sent to Bourso
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRequest {
+    pub account: String,
+    pub symbol: String,
+    pub quantity: u64,
+    pub side: OrderSide,
+    /// Worst price accepted, the order is sent at market without it
+    pub limit: Option<f64>,
+}
+
+impl OrderRequest {
+    pub fn new(
+        account: String,
+        symbol: String,
+        quantity: u64,
+        side: OrderSide,
+    ) -> core::result::Result<Self, OrderError> {
+        if quantity == 0 {
+            return Err(OrderError::ZeroQuantity);
+        }
+        // Rejected here rather than when the order is sent to Bourso
+        checked_quantity::<usize>(quantity)?;
+
+        Ok(OrderRequest {
+            account,
+            symbol,
+            quantity,
+            side,
+            limit: None,
+        })
+    }
+
+    /// Send the order as a limit order at `limit`
+    pub fn with_limit(self, limit: Option<f64>) -> Self {
+        OrderRequest { limit, ..self }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -28,33 +238,36 @@ pub struct OrderArgs {
     ///
     /// Either quantity or amount should be set
     pub amount: Option<f64>,
-    pub side: String,
+    pub side: OrderSide,
 }
 
 impl OrderArgs {
-    /// Get the quantity of shares to buy/sell
+    /// Get the quantity of shares to buy/sell along with the last price
     ///
     /// Since the user can set either quantity or amount, we need to check
     /// which one is set and return the corresponding value.
     ///
     /// To calculate the quantity from the amount, we need to get the price
-    /// from the web client.
-    pub async fn get_qty(&self, web_client: &bourso_api::client::BoursoWebClient) -> Result<u64> {
+    /// from the web client. `carried` is added to the amount, see
+    /// `qty_at_price`.
+    pub async fn get_qty(
+        &self,
+        web_client: &bourso_api::client::BoursoWebClient,
+        carried: f64,
+    ) -> Result<(u64, f64)> {
+        let price = self.get_price(web_client).await?;
+        let quantity = self.qty_at_price(price, carried)?;
+
+        Ok((quantity, price))
+    }
+
+    /// Get the quantity of shares to buy/sell at a known `price`
+    ///
+    /// `carried` is added to the amount of money, it is the cash left
+    /// unspent by the previous runs of a job.
+    pub fn qty_at_price(&self, price: f64, carried: f64) -> Result<u64> {
         if let Some(amount) = self.amount {
-            let quote = web_client.instrument_quote(&self.symbol).await;
-            match quote {
-                Ok(quote) => {
-                    let qty = (amount / quote.last).floor();
-                    return Ok(qty as u64);
-                }
-                Err(e) => {
-                    return Err(anyhow::anyhow!(
-                        "Error while getting price for {}: {:?}",
-                        self.symbol,
-                        e
-                    ));
-                }
-            }
+            return Ok(qty_for_budget(amount + carried, price));
         }
 
         if let Some(quantity) = self.quantity {
@@ -63,34 +276,62 @@ impl OrderArgs {
 
         Err(anyhow::anyhow!("Either quantity or amount should be set"))
     }
+
+    /// Get the last price of the order's symbol
+    pub async fn get_price(&self, web_client: &bourso_api::client::BoursoWebClient) -> Result<f64> {
+        match web_client.instrument_quote(&self.symbol).await {
+            Ok(quote) => Ok(quote.last),
+            Err(e) => Err(anyhow::anyhow!(
+                "Error while getting price for {}: {:?}",
+                self.symbol,
+                e
+            )),
+        }
+    }
+}
+
+/// Number of whole shares that `budget` can buy at `price`
+pub fn qty_for_budget(budget: f64, price: f64) -> u64 {
+    if budget <= 0.0 || price <= 0.0 {
+        return 0;
+    }
+    (budget / price).floor() as u64
+}
+
+/// Convert a quantity to the type expected by Bourso
+fn checked_quantity<T: TryFrom<u64>>(quantity: u64) -> core::result::Result<T, OrderError> {
+    quantity
+        .try_into()
+        .map_err(|_| OrderError::QuantityOverflow { quantity })
 }
 
 pub async fn new_order(
     web_client: &bourso_api::client::BoursoWebClient,
-    account: &str,
-    symbol: &str,
-    quantity: u64,
-    side: &str,
-) -> Result<(String, Option<f64>)> {
+    request: &OrderRequest,
+) -> core::result::Result<(String, Option<f64>), OrderError> {
+    let quantity = checked_quantity(request.quantity)?;
+
     let order_details = web_client
         .order(
-            match side {
-                "buy" => bourso_api::client::trade::order::OrderSide::Buy,
-                "sell" => bourso_api::client::trade::order::OrderSide::Sell,
-                _ => unimplemented!(),
-            },
+            request.side.into(),
             &bourso_api::account::Account {
-                id: account.to_string(),
+                id: request.account.to_string(),
                 name: "".to_owned(),
                 balance: 0,
                 bank_name: "".to_owned(),
                 kind: bourso_api::account::AccountKind::Trading,
             },
-            symbol,
-            quantity.try_into().unwrap(),
-            None,
+            &request.symbol,
+            quantity,
+            request.limit.map(|limit| OrderData {
+                order_type: OrderType::Limit,
+                limit: Some(limit),
+            }),
         )
-        .await?;
+        .await
+        .map_err(|e| OrderError::Bourso {
+            message: format!("{:?}", e),
+        })?;
 
     Ok(order_details)
 }
@@ -102,51 +343,93 @@ pub async fn new_order_cmd(
     symbol: String,
     quantity: u64,
     side: String,
-) -> core::result::Result<OrderPassed, String> {
+    confirmation: Option<Confirmation>,
+) -> core::result::Result<OrderPassed, OrderError> {
+    let request = OrderRequest::new(account, symbol, quantity, side.parse()?)?;
+
+    let app_local_data_dir = app.path().app_local_data_dir().unwrap();
+    let limits = load_limits(&app_local_data_dir).map_err(OrderError::storage)?;
+
     let state = app.state::<Mutex<BoursoState>>();
     let st = state.lock().await;
     let web_client = &st.client;
 
-    match web_client.is_market_open(&symbol).await {
+    match web_client.is_market_open(&request.symbol).await {
         Ok(false) => {
-            return Err("Market is closed".to_string());
+            return Err(OrderError::MarketClosed);
         }
         Err(e) => {
-            return Err(format!("Error while checking if market is open: {:?}", e));
+            return Err(OrderError::Bourso {
+                message: format!("Error while checking if market is open: {:?}", e),
+            });
         }
         _ => {}
     }
 
-    let order_details = new_order(web_client, &account, &symbol, quantity, &side)
+    let quote = web_client
+        .instrument_quote(&request.symbol)
         .await
-        .expect("Error while creating order");
-
-    let order = OrderPassed {
-        id: order_details.0,
-        price: order_details.1.unwrap(),
-        timestamp: DateTime::from_timestamp(Local::now().timestamp(), 0)
-            .map(|dt| dt.timestamp()),
-        args: OrderArgs {
-            account: account.to_string(),
-            symbol: symbol.to_string(),
-            quantity: Some(quantity),
+        .map_err(|e| OrderError::Bourso {
+            message: format!("Error while getting price for {}: {:?}", request.symbol, e),
+        })?;
+    limits
+        .check_order(
+            &request.symbol,
+            request.quantity,
+            quote.last,
+            confirmation.as_ref(),
+        )
+        .map_err(|limit| OrderError::Limit { limit })?;
+
+    let (id, price) = new_order(web_client, &request).await?;
+
+    // The order is placed at this point, an error would make the user place
+    // it again, so it is recorded at the quote and flagged
+    let mut order = OrderPassed::new(
+        id,
+        price.unwrap_or(quote.last),
+        DateTime::from_timestamp(Local::now().timestamp(), 0).map(|dt| dt.timestamp()),
+        OrderArgs {
+            account: request.account,
+            symbol: request.symbol,
+            quantity: Some(request.quantity),
             amount: None,
-            side: side.to_string(),
+            side: request.side,
         },
-    };
+        OrderSource::App,
+    );
+    if price.is_none() {
+        log::warn!(
+            "Order {} placed without an execution price, using the quote {}",
+            order.id,
+            quote.last
+        );
+        order.estimated_price = true;
+    }
 
-    let store = app
-        .store(HISTORY_FILE_PATH)
-        .expect("Error while getting store");
+    let store = app.store(HISTORY_FILE_PATH).map_err(OrderError::storage)?;
+    push_order(&store, &order).map_err(OrderError::storage)?;
 
-    let orders = store.get("orders").unwrap_or(serde_json::json!([]));
-    let mut orders: Vec<OrderPassed> =
-        serde_json::from_value(orders).expect("Error while parsing orders passed");
+    Ok(order)
+}
+
+/// Append an order to the local history
+pub fn push_order(store: &Store<Wry>, order: &OrderPassed) -> Result<()> {
+    let mut orders = load_orders(store)?;
     orders.push(order.clone());
-    store.set("orders", serde_json::json!(&orders));
-    store.save().unwrap();
+    save_orders(store, &orders)
+}
 
-    Ok(order)
+pub fn load_orders(store: &Store<Wry>) -> Result<Vec<OrderPassed>> {
+    let orders = store.get("orders").unwrap_or(serde_json::json!([]));
+    Ok(serde_json::from_value(orders)?)
+}
+
+pub fn save_orders(store: &Store<Wry>, orders: &[OrderPassed]) -> Result<()> {
+    store.set("orders", serde_json::json!(orders));
+    store.save()?;
+
+    Ok(())
 }
 
 #[command]
@@ -156,7 +439,55 @@ pub fn get_orders_cmd(app: AppHandle) -> core::result::Result<Vec<OrderPassed>,
 
 pub fn get_orders(app: AppHandle) -> Result<Vec<OrderPassed>> {
     let order_file = app.store(HISTORY_FILE_PATH)?;
-    let orders = order_file.get("orders").unwrap_or(serde_json::json!([]));
-    let orders: Vec<OrderPassed> = serde_json::from_value(orders)?;
-    Ok(orders)
+    load_orders(&order_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_side_from_str() {
+        assert_eq!("buy".parse::<OrderSide>(), Ok(OrderSide::Buy));
+        assert_eq!("sell".parse::<OrderSide>(), Ok(OrderSide::Sell));
+        assert_eq!(
+            "Buy".parse::<OrderSide>(),
+            Err(OrderError::UnknownSide {
+                side: "Buy".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_order_request() {
+        let request = OrderRequest::new(
+            "1234567890".to_string(),
+            "1rTCW8".to_string(),
+            3,
+            OrderSide::Buy,
+        )
+        .unwrap();
+        assert_eq!(request.quantity, 3);
+        assert_eq!(request.side, OrderSide::Buy);
+
+        assert_eq!(
+            OrderRequest::new(
+                "1234567890".to_string(),
+                "1rTCW8".to_string(),
+                0,
+                OrderSide::Sell
+            ),
+            Err(OrderError::ZeroQuantity)
+        );
+    }
+
+    #[test]
+    fn test_checked_quantity() {
+        assert_eq!(checked_quantity::<u8>(255), Ok(255));
+        assert_eq!(
+            checked_quantity::<u8>(256),
+            Err(OrderError::QuantityOverflow { quantity: 256 })
+        );
+        assert_eq!(checked_quantity::<usize>(1_000), Ok(1_000));
+    }
 }
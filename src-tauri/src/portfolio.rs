@@ -0,0 +1,539 @@
+use anyhow::Result;
+use bourso_api::client::BoursoWebClient;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use tauri::{async_runtime::Mutex, command, AppHandle, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::events::{load_events, AccountEvent, EventKind};
+use crate::order::{load_orders, OrderPassed, OrderSide};
+use crate::scheduler::HISTORY_FILE_PATH;
+use crate::BoursoState;
+
+/// How the cost of the shares sold is computed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CostMethod {
+    /// Prix de revient unitaire: weighted average cost of the shares held,
+    /// the method used by the French tax administration and by Bourso
+    #[default]
+    Pru,
+    /// The first shares bought are the first sold
+    Fifo,
+}
+
+/// Shares bought together, the unit cost includes the buy fees
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Lot {
+    pub timestamp: Option<i64>,
+    pub quantity: u64,
+    pub unit_cost: f64,
+}
+
+/// Shares sold by an order, with the gain computed with the cost method
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Disposal {
+    pub order_id: String,
+    pub timestamp: Option<i64>,
+    pub quantity: u64,
+    /// Gross amount of the sale, before the fees
+    pub proceeds: f64,
+    pub fees: f64,
+    /// Cost of the shares sold, buy fees included
+    pub acquisition_cost: f64,
+    pub gain: f64,
+}
+
+/// Position on a symbol of an account computed from the local history
+#[derive(Debug, Serialize, Clone)]
+pub struct Position {
+    pub account: String,
+    pub symbol: String,
+    pub quantity: u64,
+    /// Cost of the shares held, fees included, in euros
+    pub cost_basis: f64,
+    /// Cost of a share held, fees included
+    pub average_cost: f64,
+    /// Gain of the shares sold, net of the fees
+    pub realized_gain: f64,
+    /// Fees paid on all the orders of the position, and the fees recorded
+    /// apart, which are also deducted from the realized gain
+    pub fees: f64,
+    /// Dividends received, net of the withholding tax
+    pub dividends: f64,
+    /// Shares held, a single lot with the average cost for the PRU method
+    pub lots: Vec<Lot>,
+    pub disposals: Vec<Disposal>,
+    pub last_price: Option<f64>,
+    pub market_value: Option<f64>,
+    pub unrealized_gain: Option<f64>,
+    /// Inconsistencies of the history, e.g. more shares sold than bought
+    pub warnings: Vec<String>,
+}
+
+impl Position {
+    fn new(account: &str, symbol: &str) -> Self {
+        Position {
+            account: account.to_string(),
+            symbol: symbol.to_string(),
+            quantity: 0,
+            cost_basis: 0.0,
+            average_cost: 0.0,
+            realized_gain: 0.0,
+            fees: 0.0,
+            dividends: 0.0,
+            lots: vec![],
+            disposals: vec![],
+            last_price: None,
+            market_value: None,
+            unrealized_gain: None,
+            warnings: vec![],
+        }
+    }
+
+    /// Value the position at `price`
+    pub fn set_price(&mut self, price: f64) {
+        let market_value = self.quantity as f64 * price;
+        self.last_price = Some(price);
+        self.market_value = Some(market_value);
+        self.unrealized_gain = Some(market_value - self.cost_basis);
+    }
+}
+
+/// Running state of a position while the orders are replayed
+struct Book {
+    position: Position,
+    lots: VecDeque<Lot>,
+}
+
+impl Book {
+    fn buy(&mut self, order: &OrderPassed, quantity: u64) {
+        let cost = quantity as f64 * order.price + order.fees;
+        self.lots.push_back(Lot {
+            timestamp: order.timestamp,
+            quantity,
+            unit_cost: cost / quantity as f64,
+        });
+        self.position.quantity += quantity;
+        self.position.cost_basis += cost;
+    }
+
+    fn sell(&mut self, order: &OrderPassed, quantity: u64, method: CostMethod) {
+        let held = self.position.quantity;
+        if quantity > held {
+            self.position.warnings.push(format!(
+                "Order {} sells {} shares but only {} are held, the history is incomplete",
+                order.id, quantity, held
+            ));
+        }
+        let sold = quantity.min(held);
+        if sold == 0 {
+            return;
+        }
+
+        let cost = match method {
+            CostMethod::Pru => {
+                let cost = self.position.cost_basis * sold as f64 / held as f64;
+                // The shares held keep the same average cost
+                let mut remaining = sold;
+                while remaining > 0 {
+                    let Some(lot) = self.lots.front_mut() else {
+                        break;
+                    };
+                    let taken = remaining.min(lot.quantity);
+                    lot.quantity -= taken;
+                    remaining -= taken;
+                    if lot.quantity == 0 {
+                        self.lots.pop_front();
+                    }
+                }
+                cost
+            }
+            CostMethod::Fifo => {
+                let mut cost = 0.0;
+                let mut remaining = sold;
+                while remaining > 0 {
+                    let Some(lot) = self.lots.front_mut() else {
+                        break;
+                    };
+                    let taken = remaining.min(lot.quantity);
+                    cost += taken as f64 * lot.unit_cost;
+                    lot.quantity -= taken;
+                    remaining -= taken;
+                    if lot.quantity == 0 {
+                        self.lots.pop_front();
+                    }
+                }
+                cost
+            }
+        };
+
+        // The fees are spread over the shares sold when the history is
+        // incomplete
+        let fees = order.fees * sold as f64 / quantity as f64;
+        let proceeds = sold as f64 * order.price;
+        let gain = proceeds - fees - cost;
+        self.position.realized_gain += gain;
+        self.position.disposals.push(Disposal {
+            order_id: order.id.clone(),
+            timestamp: order.timestamp,
+            quantity: sold,
+            proceeds,
+            fees,
+            acquisition_cost: cost,
+            gain,
+        });
+        self.position.quantity -= sold;
+        self.position.cost_basis = match method {
+            CostMethod::Pru => self.position.cost_basis - cost,
+            CostMethod::Fifo => self
+                .lots
+                .iter()
+                .map(|lot| lot.quantity as f64 * lot.unit_cost)
+                .sum(),
+        };
+        if self.position.quantity == 0 {
+            self.position.cost_basis = 0.0;
+        }
+    }
+
+    /// `from` shares become `to` shares, the cost of the position is kept.
+    /// The fractional shares of a reverse split are sold by the broker, their
+    /// cash is not accounted.
+    fn split(&mut self, event: &AccountEvent, from: u64, to: u64) {
+        let scale = |quantity: u64| (quantity as u128 * to as u128 / from as u128) as u64;
+
+        for lot in self.lots.iter_mut() {
+            let quantity = scale(lot.quantity);
+            if quantity > 0 {
+                lot.unit_cost *= lot.quantity as f64 / quantity as f64;
+            }
+            lot.quantity = quantity;
+        }
+        self.lots.retain(|lot| lot.quantity > 0);
+
+        let quantity = scale(self.position.quantity);
+        if !(self.position.quantity as u128 * to as u128).is_multiple_of(from as u128) {
+            self.position.warnings.push(format!(
+                "Split {} leaves fractional shares, they are not accounted",
+                event.id
+            ));
+        }
+        self.position.quantity = quantity;
+        if quantity == 0 {
+            self.position.cost_basis = 0.0;
+        }
+    }
+
+    fn event(&mut self, event: &AccountEvent) {
+        match event.kind {
+            EventKind::Dividend { .. } => {
+                self.position.dividends += event.net_dividend().unwrap_or_default();
+            }
+            EventKind::Split { from, to } => self.split(event, from, to),
+            EventKind::Fee { amount } => {
+                self.position.fees += amount;
+                self.position.realized_gain -= amount;
+            }
+        }
+    }
+
+    fn finish(mut self, method: CostMethod) -> Position {
+        let position = &mut self.position;
+        if position.quantity > 0 {
+            position.average_cost = position.cost_basis / position.quantity as f64;
+        }
+        position.lots = match method {
+            CostMethod::Pru if position.quantity > 0 => vec![Lot {
+                timestamp: self.lots.front().and_then(|lot| lot.timestamp),
+                quantity: position.quantity,
+                unit_cost: position.average_cost,
+            }],
+            CostMethod::Pru => vec![],
+            CostMethod::Fifo => self.lots.into_iter().collect(),
+        };
+
+        self.position
+    }
+}
+
+/// Order or event of the history, replayed in chronological order
+enum Entry<'a> {
+    Order(&'a OrderPassed),
+    Event(&'a AccountEvent),
+}
+
+/// Replay the orders and the events of the securities in chronological
+/// order to compute the positions of each account, sorted by account and
+/// symbol. The positions sold out are kept for their realized gain. The
+/// events of an account without a symbol, e.g. custody fees, are ignored.
+pub fn compute_positions(
+    orders: &[OrderPassed],
+    events: &[AccountEvent],
+    method: CostMethod,
+) -> Vec<Position> {
+    let mut entries: Vec<(Option<i64>, Entry)> = orders
+        .iter()
+        .map(|o| (o.timestamp, Entry::Order(o)))
+        .chain(
+            events
+                .iter()
+                .filter(|e| e.symbol.is_some())
+                .map(|e| (Some(e.timestamp), Entry::Event(e))),
+        )
+        .collect();
+    entries.sort_by_key(|(timestamp, _)| *timestamp);
+
+    let mut books: BTreeMap<(&str, &str), Book> = BTreeMap::new();
+    for (_, entry) in entries {
+        let (account, symbol) = match entry {
+            Entry::Order(order) => (order.args.account.as_str(), order.args.symbol.as_str()),
+            Entry::Event(event) => (
+                event.account.as_str(),
+                event.symbol.as_deref().unwrap_or_default(),
+            ),
+        };
+        let book = books.entry((account, symbol)).or_insert_with(|| Book {
+            position: Position::new(account, symbol),
+            lots: VecDeque::new(),
+        });
+
+        let order = match entry {
+            Entry::Order(order) => order,
+            Entry::Event(event) => {
+                book.event(event);
+                continue;
+            }
+        };
+        book.position.fees += order.fees;
+
+        let quantity = order.executed_quantity();
+        if order.args.quantity.is_none() && quantity > 0 {
+            book.position.warnings.push(format!(
+                "Order {} has no quantity, {} share(s) estimated from its amount",
+                order.id, quantity
+            ));
+        }
+        if quantity == 0 {
+            book.position.warnings.push(format!(
+                "Order {} has no executed quantity and is ignored",
+                order.id
+            ));
+            continue;
+        }
+        match order.args.side {
+            OrderSide::Buy => book.buy(order, quantity),
+            OrderSide::Sell => book.sell(order, quantity, method),
+        }
+    }
+
+    books
+        .into_values()
+        .map(|book| book.finish(method))
+        .collect()
+}
+
+/// Value the positions held with the last quote of their symbol, the
+/// positions whose quote is unavailable are left unvalued
+pub async fn value_positions(web_client: &BoursoWebClient, positions: &mut [Position]) {
+    let mut quotes: BTreeMap<String, Option<f64>> = BTreeMap::new();
+
+    for position in positions.iter_mut().filter(|p| p.quantity > 0) {
+        if !quotes.contains_key(&position.symbol) {
+            let quote = match web_client.instrument_quote(&position.symbol).await {
+                Ok(quote) => Some(quote.last),
+                Err(e) => {
+                    log::warn!("Error while getting quote of {}: {:?}", position.symbol, e);
+                    None
+                }
+            };
+            quotes.insert(position.symbol.clone(), quote);
+        }
+
+        if let Some(price) = quotes[&position.symbol] {
+            position.set_price(price);
+        }
+    }
+}
+
+/// Positions computed from the history, valued with the live quotes
+#[command]
+pub async fn get_positions(
+    app: AppHandle,
+    state: State<'_, Mutex<BoursoState>>,
+    method: Option<CostMethod>,
+    account: Option<String>,
+) -> core::result::Result<Vec<Position>, String> {
+    let (orders, events) = history(&app, account.as_deref()).map_err(|e| e.to_string())?;
+    let mut positions = compute_positions(&orders, &events, method.unwrap_or_default());
+
+    let state = state.lock().await;
+    value_positions(&state.client, &mut positions).await;
+
+    Ok(positions)
+}
+
+fn history(
+    app: &AppHandle,
+    account: Option<&str>,
+) -> Result<(Vec<OrderPassed>, Vec<AccountEvent>)> {
+    let store = app.store(HISTORY_FILE_PATH)?;
+    let orders = load_orders(&store)?;
+    let events = load_events(&store)?;
+
+    Ok((
+        orders
+            .into_iter()
+            .filter(|o| account.is_none_or(|account| o.args.account == account))
+            .collect(),
+        events
+            .into_iter()
+            .filter(|e| account.is_none_or(|account| e.account == account))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{OrderArgs, OrderSource};
+
+    fn order(timestamp: i64, side: OrderSide, quantity: u64, price: f64, fees: f64) -> OrderPassed {
+        let mut order = OrderPassed::new(
+            timestamp.to_string(),
+            price,
+            Some(timestamp),
+            OrderArgs {
+                account: "1234567890".to_string(),
+                symbol: "1rTCW8".to_string(),
+                quantity: Some(quantity),
+                amount: None,
+                side,
+            },
+            OrderSource::App,
+        );
+        order.fees = fees;
+        order
+    }
+
+    fn history() -> Vec<OrderPassed> {
+        vec![
+            order(3, OrderSide::Sell, 15, 14.0, 2.0),
+            order(1, OrderSide::Buy, 10, 10.0, 1.0),
+            order(2, OrderSide::Buy, 10, 12.0, 1.0),
+        ]
+    }
+
+    #[test]
+    fn test_pru() {
+        let positions = compute_positions(&history(), &[], CostMethod::Pru);
+        let position = &positions[0];
+
+        // PRU of (101 + 121) / 20 = 11.10
+        assert_eq!(position.quantity, 5);
+        assert!((position.average_cost - 11.1).abs() < 1e-9);
+        assert!((position.cost_basis - 55.5).abs() < 1e-9);
+        // 15 * 14 - 2 - 15 * 11.10
+        assert!((position.realized_gain - 41.5).abs() < 1e-9);
+        assert_eq!(position.fees, 4.0);
+        assert_eq!(position.lots.len(), 1);
+        assert_eq!(position.disposals.len(), 1);
+        assert!((position.disposals[0].acquisition_cost - 166.5).abs() < 1e-9);
+        assert!(position.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_legacy_amount_order() {
+        // Orders by amount of the first versions, without their quantity
+        let mut legacy = order(4, OrderSide::Buy, 0, 15.0, 0.0);
+        legacy.args.quantity = None;
+        legacy.args.amount = Some(100.0);
+        let mut orders = history();
+        orders.push(legacy);
+
+        let positions = compute_positions(&orders, &[], CostMethod::Pru);
+
+        // floor(100 / 15) = 6 shares added to the 5 left
+        assert_eq!(positions[0].quantity, 11);
+        assert_eq!(positions[0].warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_fifo() {
+        let mut positions = compute_positions(&history(), &[], CostMethod::Fifo);
+        let position = &mut positions[0];
+
+        // The 5 shares left come from the second buy at 12.10
+        assert_eq!(position.quantity, 5);
+        assert!((position.cost_basis - 60.5).abs() < 1e-9);
+        // 15 * 14 - 2 - (101 + 5 * 12.10)
+        assert!((position.realized_gain - 46.5).abs() < 1e-9);
+        assert_eq!(position.lots[0].timestamp, Some(2));
+
+        position.set_price(13.0);
+        assert_eq!(position.market_value, Some(65.0));
+        assert!((position.unrealized_gain.unwrap() - 4.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_incomplete_history() {
+        let orders = vec![
+            order(1, OrderSide::Buy, 2, 10.0, 0.0),
+            order(2, OrderSide::Sell, 4, 12.0, 1.0),
+        ];
+
+        let position = &compute_positions(&orders, &[], CostMethod::Pru)[0];
+
+        assert_eq!(position.quantity, 0);
+        assert_eq!(position.cost_basis, 0.0);
+        // Only the 2 shares known are accounted, with half of the fees
+        assert!((position.realized_gain - 3.5).abs() < 1e-9);
+        assert_eq!(position.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_events() {
+        let event = |timestamp: i64, kind: EventKind| AccountEvent {
+            id: timestamp.to_string(),
+            account: "1234567890".to_string(),
+            symbol: Some("1rTCW8".to_string()),
+            timestamp,
+            kind,
+            source: OrderSource::Manual,
+            note: None,
+        };
+        let events = vec![
+            // Between the buys and the sell of the history
+            event(2, EventKind::Split { from: 1, to: 3 }),
+            event(
+                4,
+                EventKind::Dividend {
+                    gross: 10.0,
+                    withholding_tax: 1.5,
+                },
+            ),
+            event(5, EventKind::Fee { amount: 0.5 }),
+        ];
+        // Both buys before the split, the sale after it
+        let mut orders = history();
+        orders[2].timestamp = Some(1);
+
+        let positions = compute_positions(&orders, &events, CostMethod::Pru);
+        let position = &positions[0];
+
+        // 20 shares became 60 for the same cost of 222, 15 were sold
+        assert_eq!(position.quantity, 45);
+        assert!((position.cost_basis - 166.5).abs() < 1e-9);
+        assert!((position.realized_gain - (15.0 * 14.0 - 2.0 - 55.5 - 0.5)).abs() < 1e-9);
+        assert_eq!(position.dividends, 8.5);
+        assert_eq!(position.fees, 4.5);
+        assert!(position.warnings.is_empty());
+
+        let reverse = vec![event(4, EventKind::Split { from: 3, to: 1 })];
+        let position = &compute_positions(&history(), &reverse, CostMethod::Fifo)[0];
+        // 5 shares left become 1, the 2 fractional shares are lost
+        assert_eq!(position.quantity, 1);
+        assert!((position.cost_basis - 60.5).abs() < 1e-9);
+        assert_eq!(position.warnings.len(), 1);
+    }
+}
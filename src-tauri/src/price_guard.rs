@@ -0,0 +1,179 @@
+use anyhow::Result;
+use bourso_api::client::BoursoWebClient;
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::order::OrderSide;
+use crate::ticks::{get_daily_closes, previous_close, trailing_average, DailyClose};
+
+/// Per-job guard against buying or selling at an abnormal price, either
+/// because of a flash spike or a stale quote
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PriceGuard {
+    /// Maximum move of the last price from the reference price, in percent
+    pub max_move_percent: f64,
+    pub reference: PriceReference,
+    #[serde(default)]
+    pub action: GuardAction,
+    /// Maximum slippage accepted from the last price, in percent. The order
+    /// is sent as a limit order at the last price plus the slippage, and
+    /// dropped when a second quote taken right before sending it is already
+    /// beyond the limit.
+    #[serde(default, alias = "max_requote_percent")]
+    pub max_slippage_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PriceReference {
+    PreviousClose,
+    TrailingAverage { days: u16 },
+}
+
+/// What to do with the job when the price moved too much
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardAction {
+    /// Skip the job until the next period
+    #[default]
+    Skip,
+    /// Retry on the next scheduler run
+    Postpone,
+}
+
+/// Price moved beyond the guard
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceMove {
+    pub reference: f64,
+    pub change_percent: f64,
+}
+
+impl PriceGuard {
+    /// Check the last price against the reference price computed from the
+    /// end of day closes
+    pub async fn check(
+        &self,
+        web_client: &BoursoWebClient,
+        symbol: &str,
+        last: f64,
+    ) -> Result<Option<PriceMove>> {
+        let days = match self.reference {
+            PriceReference::PreviousClose => 5,
+            // Leave room for the week-ends and the current day
+            PriceReference::TrailingAverage { days } => days.saturating_mul(2).saturating_add(5),
+        };
+        let closes = get_daily_closes(web_client, symbol, days).await?;
+
+        let reference = self
+            .reference_price(&closes, Local::now().date_naive())
+            .ok_or_else(|| anyhow::anyhow!("Not enough price history for {}", symbol))?;
+
+        Ok(self.price_move(last, reference))
+    }
+
+    fn reference_price(&self, closes: &[DailyClose], today: NaiveDate) -> Option<f64> {
+        match self.reference {
+            PriceReference::PreviousClose => previous_close(closes, today),
+            PriceReference::TrailingAverage { days } => {
+                trailing_average(closes, today, days.into())
+            }
+        }
+    }
+
+    fn price_move(&self, last: f64, reference: f64) -> Option<PriceMove> {
+        let change_percent = (last - reference) / reference * 100.0;
+
+        if change_percent.abs() > self.max_move_percent {
+            Some(PriceMove {
+                reference,
+                change_percent,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Limit price of the order, if a maximum slippage is set. Rounded to the
+    /// cent within the slippage.
+    pub fn limit_price(&self, last: f64, side: OrderSide) -> Option<f64> {
+        self.max_slippage_percent.map(|slippage| match side {
+            OrderSide::Buy => (last * (1.0 + slippage / 100.0) * 100.0).floor() / 100.0,
+            OrderSide::Sell => (last * (1.0 - slippage / 100.0) * 100.0).ceil() / 100.0,
+        })
+    }
+}
+
+/// Whether `price` is worse than the `limit` for the order side
+pub fn beyond_limit(price: f64, limit: f64, side: OrderSide) -> bool {
+    match side {
+        OrderSide::Buy => price > limit,
+        OrderSide::Sell => price < limit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn closes(prices: &[f64]) -> Vec<DailyClose> {
+        let start = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, close)| DailyClose {
+                date: start + chrono::Days::new(i as u64),
+                close: *close,
+            })
+            .collect()
+    }
+
+    fn guard(reference: PriceReference) -> PriceGuard {
+        PriceGuard {
+            max_move_percent: 5.0,
+            reference,
+            action: GuardAction::Skip,
+            max_slippage_percent: Some(1.0),
+        }
+    }
+
+    #[test]
+    fn test_previous_close_reference() {
+        let closes = closes(&[100.0, 102.0, 104.0]);
+        let today = NaiveDate::from_ymd_opt(2025, 3, 5).unwrap();
+        let guard = guard(PriceReference::PreviousClose);
+
+        // Today's close is ignored, the previous one is 102
+        assert_eq!(guard.reference_price(&closes, today), Some(102.0));
+        assert!(guard.price_move(105.0, 102.0).is_none());
+
+        let price_move = guard.price_move(110.0, 102.0).unwrap();
+        assert!((price_move.change_percent - 7.84).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_trailing_average_reference() {
+        let closes = closes(&[90.0, 100.0, 110.0, 120.0]);
+        let today = NaiveDate::from_ymd_opt(2025, 3, 7).unwrap();
+
+        let three_days = guard(PriceReference::TrailingAverage { days: 3 });
+        assert_eq!(three_days.reference_price(&closes, today), Some(110.0));
+
+        // Not enough history
+        let ten_days = guard(PriceReference::TrailingAverage { days: 10 });
+        assert_eq!(ten_days.reference_price(&closes, today), None);
+    }
+
+    #[test]
+    fn test_limit_price() {
+        let guard = guard(PriceReference::PreviousClose);
+
+        let limit = guard.limit_price(100.0, OrderSide::Buy).unwrap();
+        assert_eq!(limit, 101.0);
+        assert!(!beyond_limit(100.9, limit, OrderSide::Buy));
+        assert!(beyond_limit(101.1, limit, OrderSide::Buy));
+
+        let limit = guard.limit_price(100.0, OrderSide::Sell).unwrap();
+        assert_eq!(limit, 99.0);
+        assert!(beyond_limit(98.9, limit, OrderSide::Sell));
+    }
+}
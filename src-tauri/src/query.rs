@@ -0,0 +1,263 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use tauri::{command, AppHandle};
+use tauri_plugin_store::StoreExt;
+
+use crate::history::OrderFilter;
+use crate::order::{load_orders, OrderPassed, OrderSide};
+use crate::scheduler::HISTORY_FILE_PATH;
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct OrderQuery {
+    #[serde(default, flatten)]
+    pub filter: OrderFilter,
+    #[serde(default)]
+    pub sort: SortField,
+    #[serde(default)]
+    pub descending: bool,
+    /// Id of the last order of the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortField {
+    #[default]
+    Date,
+    Symbol,
+    Quantity,
+    Price,
+    Amount,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderPage {
+    pub orders: Vec<OrderPassed>,
+    /// Cursor of the next page, unset on the last page
+    pub next_cursor: Option<String>,
+    /// Totals of all the orders matching the filter, not only of the page
+    pub totals: OrderTotals,
+}
+
+#[derive(Debug, Serialize, Default, PartialEq)]
+pub struct OrderTotals {
+    pub count: usize,
+    pub bought_quantity: u64,
+    pub sold_quantity: u64,
+    /// Amount spent on the buy orders, in euros
+    pub invested: f64,
+    /// Amount received from the sell orders, in euros
+    pub proceeds: f64,
+    /// Average buy price, only when the orders are on a single symbol
+    pub average_price: Option<f64>,
+}
+
+/// Query a page of the history, see `OrderQuery`
+#[command]
+pub fn query_orders(app: AppHandle, query: OrderQuery) -> core::result::Result<OrderPage, String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let orders = load_orders(&store).map_err(|e| e.to_string())?;
+
+    run_query(orders, &query).map_err(|e| e.to_string())
+}
+
+fn amount(order: &OrderPassed) -> f64 {
+    order.executed_quantity() as f64 * order.price
+}
+
+fn compare(a: &OrderPassed, b: &OrderPassed, field: SortField) -> Ordering {
+    match field {
+        SortField::Date => a.timestamp.cmp(&b.timestamp),
+        SortField::Symbol => a.args.symbol.cmp(&b.args.symbol),
+        SortField::Quantity => a.executed_quantity().cmp(&b.executed_quantity()),
+        SortField::Price => a.price.total_cmp(&b.price),
+        SortField::Amount => amount(a).total_cmp(&amount(b)),
+    }
+}
+
+pub fn run_query(orders: Vec<OrderPassed>, query: &OrderQuery) -> Result<OrderPage> {
+    let mut orders: Vec<OrderPassed> = orders
+        .into_iter()
+        .filter(|o| query.filter.matches(o))
+        .collect();
+
+    let totals = totals(&orders);
+
+    // The id breaks the ties so that the cursor position is stable
+    orders.sort_by(|a, b| {
+        let ordering = compare(a, b, query.sort).then_with(|| a.id.cmp(&b.id));
+        if query.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    let start = match &query.cursor {
+        Some(cursor) => {
+            orders
+                .iter()
+                .position(|o| o.id == *cursor)
+                .ok_or_else(|| anyhow::anyhow!("Order {} not found", cursor))?
+                + 1
+        }
+        None => 0,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+
+    let page: Vec<OrderPassed> = orders.into_iter().skip(start).take(limit + 1).collect();
+    let (page, next_cursor) = if page.len() > limit {
+        let page: Vec<OrderPassed> = page.into_iter().take(limit).collect();
+        let cursor = page.last().map(|o| o.id.clone());
+        (page, cursor)
+    } else {
+        (page, None)
+    };
+
+    Ok(OrderPage {
+        orders: page,
+        next_cursor,
+        totals,
+    })
+}
+
+fn totals(orders: &[OrderPassed]) -> OrderTotals {
+    let mut totals = OrderTotals {
+        count: orders.len(),
+        ..Default::default()
+    };
+
+    for order in orders {
+        let quantity = order.executed_quantity();
+        match order.args.side {
+            OrderSide::Buy => {
+                totals.bought_quantity += quantity;
+                totals.invested += amount(order);
+            }
+            OrderSide::Sell => {
+                totals.sold_quantity += quantity;
+                totals.proceeds += amount(order);
+            }
+        }
+    }
+
+    let single_symbol = orders
+        .first()
+        .is_some_and(|first| orders.iter().all(|o| o.args.symbol == first.args.symbol));
+    if single_symbol && totals.bought_quantity > 0 {
+        totals.average_price = Some(totals.invested / totals.bought_quantity as f64);
+    }
+
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{OrderArgs, OrderSource};
+
+    fn order(id: &str, symbol: &str, quantity: u64, price: f64, side: OrderSide) -> OrderPassed {
+        OrderPassed::new(
+            id.to_string(),
+            price,
+            Some(id.parse().unwrap()),
+            OrderArgs {
+                account: "1234567890".to_string(),
+                symbol: symbol.to_string(),
+                quantity: Some(quantity),
+                amount: None,
+                side,
+            },
+            OrderSource::App,
+        )
+    }
+
+    fn history() -> Vec<OrderPassed> {
+        vec![
+            order("1", "1rTCW8", 2, 10.0, OrderSide::Buy),
+            order("2", "1rTCW8", 3, 20.0, OrderSide::Buy),
+            order("3", "1rAAPL", 1, 150.0, OrderSide::Buy),
+            order("4", "1rTCW8", 1, 25.0, OrderSide::Sell),
+            order("5", "1rTCW8", 5, 12.0, OrderSide::Buy),
+        ]
+    }
+
+    #[test]
+    fn test_pagination() {
+        let mut query = OrderQuery {
+            descending: true,
+            limit: Some(2),
+            ..Default::default()
+        };
+
+        let first = run_query(history(), &query).unwrap();
+        let ids: Vec<&str> = first.orders.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["5", "4"]);
+        assert_eq!(first.next_cursor.as_deref(), Some("4"));
+
+        query.cursor = first.next_cursor;
+        let second = run_query(history(), &query).unwrap();
+        let ids: Vec<&str> = second.orders.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["3", "2"]);
+
+        query.cursor = second.next_cursor;
+        let last = run_query(history(), &query).unwrap();
+        assert_eq!(last.orders.len(), 1);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn test_totals() {
+        let query = OrderQuery {
+            filter: OrderFilter {
+                symbol: Some("1rTCW8".to_string()),
+                ..Default::default()
+            },
+            sort: SortField::Amount,
+            ..Default::default()
+        };
+
+        let page = run_query(history(), &query).unwrap();
+
+        assert_eq!(page.orders[0].id, "1");
+        assert_eq!(
+            page.totals,
+            OrderTotals {
+                count: 4,
+                bought_quantity: 10,
+                sold_quantity: 1,
+                invested: 140.0,
+                proceeds: 25.0,
+                average_price: Some(14.0),
+            }
+        );
+
+        // No average price across symbols
+        let page = run_query(history(), &OrderQuery::default()).unwrap();
+        assert_eq!(page.totals.average_price, None);
+    }
+
+    #[test]
+    fn test_sort_by_quantity() {
+        // Legacy order by amount, 4 shares for 40€ at 10€
+        let mut legacy = order("6", "1rTCW8", 0, 10.0, OrderSide::Buy);
+        legacy.args.quantity = None;
+        legacy.args.amount = Some(40.0);
+        let mut orders = history();
+        orders.push(legacy);
+
+        let query = OrderQuery {
+            sort: SortField::Quantity,
+            ..Default::default()
+        };
+        let page = run_query(orders, &query).unwrap();
+        let ids: Vec<&str> = page.orders.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["3", "4", "1", "2", "6", "5"]);
+    }
+}
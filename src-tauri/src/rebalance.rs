@@ -0,0 +1,339 @@
+//! Rebalancing of a trading account toward target weights. The allocation
+//! is computed from the positions of the trading summary valued at the live
+//! quotes, the positions outside of the targets are ignored.
+
+use anyhow::Result;
+use bourso_api::client::BoursoWebClient;
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime::Mutex, command, State};
+
+use crate::basket::{validate_weights, Weight};
+use crate::order::{qty_for_budget, OrderArgs, OrderSide};
+use crate::trading::{cash_from_summary, get_summary, positions_from_summary};
+use crate::BoursoState;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Rebalance {
+    pub account: String,
+    pub targets: Vec<Weight>,
+    /// Drift of a weight from its target, in percentage points, above which
+    /// the account is rebalanced
+    pub threshold: f64,
+    /// Only buy the underweight assets with the new contributions
+    #[serde(default)]
+    pub buy_only: bool,
+    /// Amount invested on each run in buy-only mode, in euros. The cash
+    /// available on the account is invested when unset.
+    #[serde(default)]
+    pub contribution: Option<f64>,
+    /// Only notify the orders that would be placed
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Position of a target valued at the last quote
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    pub symbol: String,
+    pub quantity: f64,
+    pub price: f64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Drift {
+    pub symbol: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub value: f64,
+    /// Current weight, in percent
+    pub weight: f64,
+    /// Target weight, in percent
+    pub target: f64,
+    /// Current weight minus the target, in percentage points
+    pub drift: f64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RebalanceOrder {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: u64,
+    pub price: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebalanceReport {
+    pub account: String,
+    /// Value of the targets held
+    pub total: f64,
+    /// Cash available on the account
+    pub cash: f64,
+    /// Largest drift from a target, in percentage points
+    pub max_drift: f64,
+    pub drifts: Vec<Drift>,
+    /// Orders getting back to the targets, the sales first
+    pub orders: Vec<RebalanceOrder>,
+}
+
+impl Rebalance {
+    pub fn validate(&self) -> Result<()> {
+        validate_weights(&self.targets)?;
+        if self.threshold < 0.0 {
+            return Err(anyhow::anyhow!("The drift threshold cannot be negative"));
+        }
+
+        Ok(())
+    }
+
+    /// Orders of the report, for `Job::place_legs`
+    pub fn order_args(&self, report: &RebalanceReport) -> Vec<OrderArgs> {
+        report
+            .orders
+            .iter()
+            .map(|order| OrderArgs {
+                account: self.account.clone(),
+                symbol: order.symbol.clone(),
+                quantity: Some(order.quantity),
+                amount: None,
+                side: order.side,
+            })
+            .collect()
+    }
+}
+
+/// Drift of the account from the targets, without placing any order
+#[command]
+pub async fn get_rebalance_report(
+    state: State<'_, Mutex<BoursoState>>,
+    rebalance: Rebalance,
+) -> core::result::Result<RebalanceReport, String> {
+    rebalance.validate().map_err(|e| e.to_string())?;
+
+    let state = state.lock().await;
+    report(&state.client, &rebalance)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Fetch the positions, the cash and the quotes of the targets and plan the
+/// orders
+pub async fn report(
+    web_client: &BoursoWebClient,
+    rebalance: &Rebalance,
+) -> Result<RebalanceReport> {
+    let summary = get_summary(web_client, &rebalance.account).await?;
+    let positions = positions_from_summary(&summary)?;
+    let cash = cash_from_summary(&summary).unwrap_or_default();
+
+    // A target not held yet has a quantity of 0, and a symbol unknown to
+    // Bourso, e.g. an ISIN, has no quote
+    let mut holdings = vec![];
+    for target in &rebalance.targets {
+        let quote = web_client
+            .instrument_quote(&target.symbol)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "No quote for {}, check that it is a Bourso symbol: {:?}",
+                    target.symbol,
+                    e
+                )
+            })?;
+        holdings.push(Holding {
+            symbol: target.symbol.clone(),
+            quantity: positions
+                .iter()
+                .filter(|p| p.symbol == target.symbol)
+                .map(|p| p.quantity)
+                .sum(),
+            price: quote.last,
+        });
+    }
+
+    Ok(plan(rebalance, &holdings, cash))
+}
+
+/// Compare the holdings with the targets. A full rebalance trades every
+/// asset back to its target once a drift exceeds the threshold. In buy-only
+/// mode the contribution is split across the underweight assets in
+/// proportion of what they miss, whatever the drift.
+pub fn plan(rebalance: &Rebalance, holdings: &[Holding], cash: f64) -> RebalanceReport {
+    let total_weight: f64 = rebalance.targets.iter().map(|t| t.weight).sum();
+    let total: f64 = holdings.iter().map(|h| h.quantity * h.price).sum();
+
+    let drifts: Vec<Drift> = rebalance
+        .targets
+        .iter()
+        .filter_map(|target| {
+            let holding = holdings.iter().find(|h| h.symbol == target.symbol)?;
+            let value = holding.quantity * holding.price;
+            let weight = if total > 0.0 {
+                value / total * 100.0
+            } else {
+                0.0
+            };
+            let target = target.weight / total_weight * 100.0;
+            Some(Drift {
+                symbol: holding.symbol.clone(),
+                quantity: holding.quantity,
+                price: holding.price,
+                value,
+                weight,
+                target,
+                drift: weight - target,
+            })
+        })
+        .collect();
+    let max_drift = drifts.iter().map(|d| d.drift.abs()).fold(0.0, f64::max);
+
+    let mut orders = vec![];
+    if rebalance.buy_only {
+        let contribution = rebalance.contribution.unwrap_or(cash).max(0.0);
+        let after = total + contribution;
+        let missing: Vec<f64> = drifts
+            .iter()
+            .map(|d| (after * d.target / 100.0 - d.value).max(0.0))
+            .collect();
+        let total_missing: f64 = missing.iter().sum();
+
+        if total_missing > 0.0 {
+            for (drift, missing) in drifts.iter().zip(missing) {
+                let amount = contribution * missing / total_missing;
+                let quantity = qty_for_budget(amount, drift.price);
+                if quantity > 0 {
+                    orders.push(RebalanceOrder {
+                        symbol: drift.symbol.clone(),
+                        side: OrderSide::Buy,
+                        quantity,
+                        price: drift.price,
+                    });
+                }
+            }
+        }
+    } else if max_drift > rebalance.threshold {
+        for drift in &drifts {
+            let difference = total * drift.target / 100.0 - drift.value;
+            let side = if difference > 0.0 {
+                OrderSide::Buy
+            } else {
+                OrderSide::Sell
+            };
+            let quantity = qty_for_budget(difference.abs(), drift.price);
+            if quantity > 0 {
+                orders.push(RebalanceOrder {
+                    symbol: drift.symbol.clone(),
+                    side,
+                    quantity,
+                    price: drift.price,
+                });
+            }
+        }
+        // The sales free the cash of the purchases
+        orders.sort_by_key(|o| o.side == OrderSide::Buy);
+    }
+
+    RebalanceReport {
+        account: rebalance.account.clone(),
+        total,
+        cash,
+        max_drift,
+        drifts,
+        orders,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rebalance(buy_only: bool, contribution: Option<f64>) -> Rebalance {
+        Rebalance {
+            account: "1234567890".to_string(),
+            targets: vec![
+                Weight {
+                    symbol: "1rTCW8".to_string(),
+                    weight: 80.0,
+                },
+                Weight {
+                    symbol: "1rPAEEM".to_string(),
+                    weight: 20.0,
+                },
+            ],
+            threshold: 5.0,
+            buy_only,
+            contribution,
+            dry_run: false,
+        }
+    }
+
+    fn holding(symbol: &str, quantity: f64, price: f64) -> Holding {
+        Holding {
+            symbol: symbol.to_string(),
+            quantity,
+            price,
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        // 70/30 instead of 80/20
+        let holdings = vec![
+            holding("1rTCW8", 70.0, 100.0),
+            holding("1rPAEEM", 60.0, 50.0),
+        ];
+
+        let report = plan(&rebalance(false, None), &holdings, 0.0);
+        assert_eq!(report.total, 10_000.0);
+        assert!((report.max_drift - 10.0).abs() < 1e-9);
+        assert_eq!(
+            report.orders,
+            vec![
+                RebalanceOrder {
+                    symbol: "1rPAEEM".to_string(),
+                    side: OrderSide::Sell,
+                    quantity: 20,
+                    price: 50.0,
+                },
+                RebalanceOrder {
+                    symbol: "1rTCW8".to_string(),
+                    side: OrderSide::Buy,
+                    quantity: 10,
+                    price: 100.0,
+                },
+            ]
+        );
+
+        // Below the threshold
+        let mut within = rebalance(false, None);
+        within.threshold = 15.0;
+        assert!(plan(&within, &holdings, 0.0).orders.is_empty());
+
+        // 1000€ only go to the underweight asset
+        let report = plan(&rebalance(true, Some(1_000.0)), &holdings, 0.0);
+        assert_eq!(
+            report.orders,
+            vec![RebalanceOrder {
+                symbol: "1rTCW8".to_string(),
+                side: OrderSide::Buy,
+                quantity: 10,
+                price: 100.0,
+            }]
+        );
+
+        // A new asset, not held yet, is the most underweight
+        let holdings = vec![
+            holding("1rTCW8", 80.0, 100.0),
+            holding("1rPAEEM", 0.0, 50.0),
+        ];
+        let report = plan(&rebalance(true, Some(1_000.0)), &holdings, 0.0);
+        assert_eq!(
+            report.orders,
+            vec![RebalanceOrder {
+                symbol: "1rPAEEM".to_string(),
+                side: OrderSide::Buy,
+                quantity: 20,
+                price: 50.0,
+            }]
+        );
+    }
+}
@@ -0,0 +1,482 @@
+use bourso_api::account::AccountKind;
+use chrono::Local;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use tauri::{async_runtime::Mutex, command, AppHandle, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::events::{load_events, AccountEvent, EventKind};
+use crate::history::{order_date, ManualOrder};
+use crate::order::{load_orders, OrderPassed, OrderSide, OrderSource};
+use crate::portfolio::{compute_positions, CostMethod, Position};
+use crate::scheduler::HISTORY_FILE_PATH;
+use crate::trading::{get_summary_positions, SummaryPosition};
+use crate::BoursoState;
+
+/// Usual split ratios, either way. Other ratios between the quantities are
+/// more likely missing orders, e.g. 5 shares becoming 10.
+const SPLIT_RATIOS: [u64; 10] = [2, 3, 4, 5, 8, 10, 20, 25, 50, 100];
+/// Gap between the buying price of Bourso and the split average cost of the
+/// history still taken for a split, in percent
+const SPLIT_COST_TOLERANCE: f64 = 2.0;
+
+/// Why the history and Bourso disagree on a position
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Cause {
+    /// Shares bought on the website or before the app was used, and not
+    /// imported
+    MissingOrders,
+    /// Shares sold or transferred out on the website
+    SoldOutside,
+    /// The quantities differ by a usual split ratio, e.g. 1 share became
+    /// 10, and the buying price of Bourso is the split average cost
+    Split { ratio: f64 },
+    /// A split recorded in the history is not applied on Bourso yet
+    RecordedSplit { event_id: String },
+    /// The last order of the app was only partly executed
+    PartialFill { order_id: String, quantity: u64 },
+    /// Orders passed after the last movement reported by Bourso, they may
+    /// not be settled yet
+    Unsettled { order_ids: Vec<String> },
+}
+
+/// Position on which the history and Bourso disagree
+#[derive(Debug, Serialize, Clone)]
+pub struct Discrepancy {
+    pub account: String,
+    pub symbol: String,
+    pub label: String,
+    pub local_quantity: u64,
+    pub bourso_quantity: u64,
+    /// Shares held on Bourso minus shares in the history
+    pub difference: i64,
+    pub local_average_cost: f64,
+    pub bourso_buying_price: Option<f64>,
+    /// Most likely first
+    pub causes: Vec<Cause>,
+    /// Manual order that brings the history in line with Bourso, unset when
+    /// a recorded split explains the gap
+    pub correction: Option<ManualOrder>,
+}
+
+/// Compare the positions of the history with the ones held on Bourso
+#[command]
+pub async fn reconcile_positions(
+    app: AppHandle,
+    state: State<'_, Mutex<BoursoState>>,
+    account: Option<String>,
+) -> core::result::Result<Vec<Discrepancy>, String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let orders = load_orders(&store).map_err(|e| e.to_string())?;
+    let events = load_events(&store).map_err(|e| e.to_string())?;
+
+    let state = state.lock().await;
+    let accounts = state
+        .client
+        .get_accounts(Some(AccountKind::Trading))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let now = Local::now().timestamp();
+    let mut discrepancies = vec![];
+    for trading_account in accounts
+        .iter()
+        .filter(|a| account.as_ref().is_none_or(|id| a.id == *id))
+    {
+        let bourso = get_summary_positions(&state.client, &trading_account.id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let orders: Vec<OrderPassed> = orders
+            .iter()
+            .filter(|o| o.args.account == trading_account.id)
+            .cloned()
+            .collect();
+        let events: Vec<AccountEvent> = events
+            .iter()
+            .filter(|e| e.account == trading_account.id)
+            .cloned()
+            .collect();
+
+        discrepancies.extend(reconcile(
+            &trading_account.id,
+            &orders,
+            &events,
+            &bourso,
+            now,
+        ));
+    }
+
+    Ok(discrepancies)
+}
+
+/// Discrepancies of an account, sorted by symbol. The splits recorded in
+/// `events` are applied to the history.
+pub fn reconcile(
+    account: &str,
+    orders: &[OrderPassed],
+    events: &[AccountEvent],
+    bourso: &[SummaryPosition],
+    now: i64,
+) -> Vec<Discrepancy> {
+    let local = compute_positions(orders, events, CostMethod::Pru);
+
+    let symbols: BTreeSet<&str> = local
+        .iter()
+        .map(|p| p.symbol.as_str())
+        .chain(bourso.iter().map(|p| p.symbol.as_str()))
+        .collect();
+
+    symbols
+        .into_iter()
+        .filter_map(|symbol| {
+            let position = local.iter().find(|p| p.symbol == symbol);
+            let held = bourso.iter().find(|p| p.symbol == symbol);
+            let symbol_orders: Vec<&OrderPassed> =
+                orders.iter().filter(|o| o.args.symbol == symbol).collect();
+            let symbol_events: Vec<&AccountEvent> = events
+                .iter()
+                .filter(|e| e.symbol.as_deref() == Some(symbol))
+                .collect();
+
+            discrepancy(
+                account,
+                symbol,
+                position,
+                held,
+                &symbol_orders,
+                &symbol_events,
+                now,
+            )
+        })
+        .collect()
+}
+
+fn discrepancy(
+    account: &str,
+    symbol: &str,
+    position: Option<&Position>,
+    held: Option<&SummaryPosition>,
+    orders: &[&OrderPassed],
+    events: &[&AccountEvent],
+    now: i64,
+) -> Option<Discrepancy> {
+    let local_quantity = position.map(|p| p.quantity).unwrap_or_default();
+    let bourso_quantity = held.map(|p| p.quantity.round() as u64).unwrap_or_default();
+    if local_quantity == bourso_quantity {
+        return None;
+    }
+    let difference = bourso_quantity as i64 - local_quantity as i64;
+
+    let mut causes = vec![];
+
+    let last_movement = held.and_then(|p| p.last_movement_date);
+    let unsettled: Vec<String> = orders
+        .iter()
+        .filter(|o| {
+            let date = o.timestamp.and_then(order_date);
+            match (date, last_movement) {
+                (Some(date), Some(last_movement)) => date > last_movement,
+                _ => false,
+            }
+        })
+        .map(|o| o.id.clone())
+        .collect();
+    if !unsettled.is_empty() {
+        causes.push(Cause::Unsettled {
+            order_ids: unsettled,
+        });
+    }
+
+    // The history applies the recorded splits, Bourso may not have yet
+    let recorded_split = events.iter().find(|e| match e.kind {
+        EventKind::Split { from, to } => local_quantity * from == bourso_quantity * to,
+        _ => false,
+    });
+    if let Some(event) = recorded_split {
+        causes.push(Cause::RecordedSplit {
+            event_id: event.id.clone(),
+        });
+    } else if let (Some(position), Some(held)) = (position, held) {
+        if let Some(ratio) = split_ratio(position, held, local_quantity, bourso_quantity) {
+            causes.push(Cause::Split { ratio });
+        }
+    }
+
+    if difference > 0 {
+        causes.push(Cause::MissingOrders);
+    } else {
+        let last_order = orders
+            .iter()
+            .filter(|o| o.args.side == OrderSide::Buy)
+            .filter(|o| matches!(o.source, OrderSource::App | OrderSource::Dca))
+            .max_by_key(|o| o.timestamp);
+        if let Some(order) = last_order {
+            let quantity = order.executed_quantity();
+            if quantity > difference.unsigned_abs() {
+                causes.push(Cause::PartialFill {
+                    order_id: order.id.clone(),
+                    quantity: quantity - difference.unsigned_abs(),
+                });
+            }
+        }
+        causes.push(Cause::SoldOutside);
+    }
+
+    let correction = recorded_split
+        .is_none()
+        .then(|| correction(account, symbol, position, held, difference, now));
+
+    Some(Discrepancy {
+        account: account.to_string(),
+        symbol: symbol.to_string(),
+        label: held.map(|p| p.label.clone()).unwrap_or_default(),
+        local_quantity,
+        bourso_quantity,
+        difference,
+        local_average_cost: position.map(|p| p.average_cost).unwrap_or_default(),
+        bourso_buying_price: held.map(|p| p.buying_price),
+        causes,
+        correction,
+    })
+}
+
+/// Ratio of a split turning the position of the history into the one of
+/// Bourso. Only the usual ratios whose buying price matches the split
+/// average cost are taken, otherwise the shares were more likely bought or
+/// sold outside the app.
+fn split_ratio(
+    position: &Position,
+    held: &SummaryPosition,
+    local_quantity: u64,
+    bourso_quantity: u64,
+) -> Option<f64> {
+    if local_quantity == 0 || bourso_quantity == 0 {
+        return None;
+    }
+    let (big, small) = if bourso_quantity > local_quantity {
+        (bourso_quantity, local_quantity)
+    } else {
+        (local_quantity, bourso_quantity)
+    };
+    if big % small != 0 || !SPLIT_RATIOS.contains(&(big / small)) {
+        return None;
+    }
+
+    let ratio = bourso_quantity as f64 / local_quantity as f64;
+    let split_cost = position.average_cost / ratio;
+    if split_cost <= 0.0
+        || ((held.buying_price - split_cost) / split_cost * 100.0).abs() > SPLIT_COST_TOLERANCE
+    {
+        return None;
+    }
+
+    Some(ratio)
+}
+
+/// Missing shares are bought at the price that gives the buying price of
+/// Bourso, extra shares are sold at the last price
+fn correction(
+    account: &str,
+    symbol: &str,
+    position: Option<&Position>,
+    held: Option<&SummaryPosition>,
+    difference: i64,
+    now: i64,
+) -> ManualOrder {
+    let quantity = difference.unsigned_abs();
+    let cost_basis = position.map(|p| p.cost_basis).unwrap_or_default();
+
+    let (side, price) = if difference > 0 {
+        let price = held
+            .map(|p| (p.quantity * p.buying_price - cost_basis) / quantity as f64)
+            .filter(|price| *price > 0.0)
+            .or(held.map(|p| p.buying_price))
+            .unwrap_or_default();
+        (OrderSide::Buy, price)
+    } else {
+        let price = held
+            .map(|p| p.last)
+            .or(position.and_then(|p| p.last_price))
+            .unwrap_or(position.map(|p| p.average_cost).unwrap_or_default());
+        (OrderSide::Sell, price)
+    };
+
+    ManualOrder {
+        id: None,
+        account: account.to_string(),
+        symbol: symbol.to_string(),
+        side,
+        quantity,
+        price: (price * 10_000.0).round() / 10_000.0,
+        timestamp: now,
+        fees: 0.0,
+        note: Some("Reconciliation with Bourso".to_string()),
+        tags: vec!["reconciliation".to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::OrderArgs;
+    use chrono::NaiveDate;
+
+    const NOW: i64 = 1_717_200_000;
+
+    fn order(id: &str, symbol: &str, quantity: u64, price: f64, timestamp: i64) -> OrderPassed {
+        OrderPassed::new(
+            id.to_string(),
+            price,
+            Some(timestamp),
+            OrderArgs {
+                account: "1234567890".to_string(),
+                symbol: symbol.to_string(),
+                quantity: Some(quantity),
+                amount: None,
+                side: OrderSide::Buy,
+            },
+            OrderSource::Dca,
+        )
+    }
+
+    fn held(symbol: &str, quantity: f64, buying_price: f64) -> SummaryPosition {
+        SummaryPosition {
+            symbol: symbol.to_string(),
+            label: symbol.to_string(),
+            quantity,
+            buying_price,
+            last: 12.0,
+            last_movement_date: NaiveDate::from_ymd_opt(2024, 5, 20),
+        }
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let orders = vec![
+            order("a", "1rTCW8", 10, 10.0, NOW - 86_400 * 30),
+            order("b", "1rAAPL", 2, 150.0, NOW - 86_400 * 30),
+            order("c", "1rMSFT", 5, 300.0, NOW - 86_400 * 30),
+        ];
+        let bourso = vec![
+            // 5 shares bought on the website at 13€
+            held("1rTCW8", 15.0, 11.0),
+            // 2 shares split in 20
+            held("1rAAPL", 20.0, 15.0),
+            // Bought on the website only
+            held("1rORCL", 3.0, 100.0),
+        ];
+
+        let discrepancies = reconcile("1234567890", &orders, &[], &bourso, NOW);
+        let by_symbol = |symbol: &str| discrepancies.iter().find(|d| d.symbol == symbol).unwrap();
+
+        assert_eq!(discrepancies.len(), 4);
+
+        let missing = by_symbol("1rTCW8");
+        assert_eq!(missing.difference, 5);
+        assert_eq!(missing.causes, vec![Cause::MissingOrders]);
+        let correction = missing.correction.as_ref().unwrap();
+        assert_eq!(correction.side, OrderSide::Buy);
+        assert_eq!(correction.quantity, 5);
+        assert_eq!(correction.price, 13.0);
+
+        let split = by_symbol("1rAAPL");
+        assert_eq!(split.causes[0], Cause::Split { ratio: 10.0 });
+        // Offered in case the shares were bought outside the app
+        assert!(split.correction.is_some());
+
+        let sold = by_symbol("1rMSFT");
+        assert_eq!(sold.difference, -5);
+        assert_eq!(sold.causes.last(), Some(&Cause::SoldOutside));
+        assert_eq!(sold.correction.as_ref().unwrap().side, OrderSide::Sell);
+
+        assert_eq!(
+            by_symbol("1rORCL").correction.as_ref().unwrap().price,
+            100.0
+        );
+    }
+
+    #[test]
+    fn test_split_ratios() {
+        let orders = vec![
+            order("a", "1rAAPL", 1, 30.0, NOW - 86_400 * 30),
+            order("b", "1rTCW8", 5, 10.0, NOW - 86_400 * 30),
+        ];
+
+        // 1 share at 30€ split in 3 at 10€
+        let bourso = vec![held("1rAAPL", 3.0, 10.0)];
+        let discrepancies = reconcile("1234567890", &orders, &[], &bourso, NOW);
+        assert_eq!(discrepancies[0].causes[0], Cause::Split { ratio: 3.0 });
+
+        // 2 shares bought on the website at 22.5€
+        let bourso = vec![held("1rAAPL", 3.0, 25.0)];
+        let discrepancies = reconcile("1234567890", &orders, &[], &bourso, NOW);
+        assert_eq!(discrepancies[0].causes, vec![Cause::MissingOrders]);
+        assert_eq!(discrepancies[0].correction.as_ref().unwrap().quantity, 2);
+
+        // 5 shares bought on the website at the same price, not a split
+        let bourso = vec![held("1rTCW8", 10.0, 10.0)];
+        let discrepancies = reconcile("1234567890", &orders, &[], &bourso, NOW);
+        let discrepancy = discrepancies.iter().find(|d| d.symbol == "1rTCW8").unwrap();
+        assert_eq!(discrepancy.causes, vec![Cause::MissingOrders]);
+        assert_eq!(discrepancy.correction.as_ref().unwrap().quantity, 5);
+
+        // A ratio of 7 is not a usual split
+        let orders = vec![order("a", "1rAAPL", 3, 10.0, NOW - 86_400 * 30)];
+        let bourso = vec![held("1rAAPL", 21.0, 10.0 / 7.0)];
+        let discrepancies = reconcile("1234567890", &orders, &[], &bourso, NOW);
+        assert!(!matches!(discrepancies[0].causes[0], Cause::Split { .. }));
+    }
+
+    #[test]
+    fn test_recorded_split() {
+        let orders = vec![order("a", "1rAAPL", 2, 150.0, NOW - 86_400 * 30)];
+        let events = vec![AccountEvent {
+            id: "split".to_string(),
+            account: "1234567890".to_string(),
+            symbol: Some("1rAAPL".to_string()),
+            timestamp: NOW - 86_400,
+            kind: EventKind::Split { from: 1, to: 10 },
+            source: OrderSource::Manual,
+            note: None,
+        }];
+        // Bourso still shows the shares before the split
+        let bourso = vec![held("1rAAPL", 2.0, 150.0)];
+
+        let discrepancies = reconcile("1234567890", &orders, &events, &bourso, NOW);
+
+        assert_eq!(discrepancies[0].local_quantity, 20);
+        assert_eq!(
+            discrepancies[0].causes[0],
+            Cause::RecordedSplit {
+                event_id: "split".to_string()
+            }
+        );
+        assert!(discrepancies[0].correction.is_none());
+    }
+
+    #[test]
+    fn test_partial_fill() {
+        let orders = vec![
+            order("a", "1rTCW8", 10, 10.0, NOW - 86_400 * 30),
+            order("b", "1rTCW8", 4, 10.0, NOW),
+        ];
+        let bourso = vec![held("1rTCW8", 11.0, 10.0)];
+
+        let discrepancies = reconcile("1234567890", &orders, &[], &bourso, NOW);
+
+        assert_eq!(
+            discrepancies[0].causes,
+            vec![
+                Cause::Unsettled {
+                    order_ids: vec!["b".to_string()]
+                },
+                Cause::PartialFill {
+                    order_id: "b".to_string(),
+                    quantity: 1
+                },
+                Cause::SoldOutside,
+            ]
+        );
+    }
+}
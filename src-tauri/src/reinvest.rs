@@ -0,0 +1,237 @@
+//! Reinvestment of the cash credited to a trading account, e.g. the
+//! dividends of distributing ETFs. The new cash is detected from the cash of
+//! the trading summary between two runs of the job, net of the transfers and
+//! the orders made by the app in between.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::basket::{split_amount, validate_weights, Weight};
+use crate::order::{OrderArgs, OrderPassed, OrderSide, OrderSource};
+use crate::transfer::TransferPassed;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Reinvestment {
+    pub account: String,
+    /// Minimum new cash to reinvest, in euros
+    pub threshold: f64,
+    /// Securities bought with the new cash, in proportion of their weights
+    pub targets: Vec<Weight>,
+    /// Only notify the orders that would be placed
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Cash of the account at the previous run, in euros
+    #[serde(default)]
+    pub last_cash: Option<f64>,
+    /// Time of the previous run, in seconds
+    #[serde(default)]
+    pub last_checked: Option<i64>,
+}
+
+/// What a run found on the account
+#[derive(Debug, PartialEq)]
+pub enum Detection {
+    /// First run, the cash is the reference of the next runs
+    Initialized,
+    /// Not enough new cash since the previous run
+    BelowThreshold { increase: f64 },
+    /// New cash to reinvest, before checking the cash available
+    NewCash { increase: f64 },
+}
+
+impl Reinvestment {
+    pub fn validate(&self) -> Result<()> {
+        validate_weights(&self.targets)?;
+        if self.threshold <= 0.0 {
+            return Err(anyhow::anyhow!("The threshold must be positive"));
+        }
+
+        Ok(())
+    }
+
+    /// Compare `cash` with the cash of the previous run plus the `recorded`
+    /// flows of the app since then. Below the threshold the reference only
+    /// goes down, so that small credits add up until they are worth
+    /// reinvesting.
+    pub fn detect(&mut self, cash: f64, recorded: f64, now: i64) -> Detection {
+        let last_cash = self.last_cash.filter(|_| self.last_checked.is_some());
+        self.last_checked = Some(now);
+        let Some(last_cash) = last_cash else {
+            self.last_cash = Some(cash);
+            return Detection::Initialized;
+        };
+
+        let expected = last_cash + recorded;
+        let increase = cash - expected;
+        if increase < self.threshold {
+            self.last_cash = Some(expected.min(cash));
+            return Detection::BelowThreshold { increase };
+        }
+
+        self.last_cash = Some(cash);
+        Detection::NewCash { increase }
+    }
+
+    /// Amount-based buy orders splitting `cash` across the targets
+    pub fn orders(&self, cash: f64) -> Vec<OrderArgs> {
+        split_amount(&self.account, &self.targets, cash)
+    }
+}
+
+/// Cash moved on `account` by the app after `since`: the transfers to and
+/// from the account, and the orders placed by the app and its jobs, the
+/// sales positive
+pub fn recorded_flows(
+    account: &str,
+    since: i64,
+    orders: &[OrderPassed],
+    transfers: &[TransferPassed],
+) -> f64 {
+    let transferred: f64 = transfers
+        .iter()
+        .filter(|t| t.timestamp > since)
+        .map(|t| {
+            if t.to == account {
+                t.amount
+            } else if t.from == account {
+                -t.amount
+            } else {
+                0.0
+            }
+        })
+        .sum();
+
+    let traded: f64 = orders
+        .iter()
+        .filter(|o| o.args.account == account)
+        .filter(|o| matches!(o.source, OrderSource::App | OrderSource::Dca))
+        .filter(|o| o.timestamp.is_some_and(|timestamp| timestamp > since))
+        .map(|o| {
+            let value = o
+                .args
+                .quantity
+                .map(|quantity| quantity as f64 * o.price)
+                .or(o.args.amount)
+                .unwrap_or_default();
+            match o.args.side {
+                OrderSide::Buy => -value - o.fees,
+                OrderSide::Sell => value - o.fees,
+            }
+        })
+        .sum();
+
+    transferred + traded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reinvestment() {
+        let mut reinvestment = Reinvestment {
+            account: "1234567890".to_string(),
+            threshold: 50.0,
+            targets: vec![
+                Weight {
+                    symbol: "1rTCW8".to_string(),
+                    weight: 3.0,
+                },
+                Weight {
+                    symbol: "1rPAEEM".to_string(),
+                    weight: 1.0,
+                },
+            ],
+            dry_run: false,
+            last_cash: None,
+            last_checked: None,
+        };
+        assert!(reinvestment.validate().is_ok());
+
+        assert_eq!(reinvestment.detect(1_000.0, 0.0, 1), Detection::Initialized);
+        // A fee, then two small dividends add up
+        assert_eq!(
+            reinvestment.detect(980.0, 0.0, 2),
+            Detection::BelowThreshold { increase: -20.0 }
+        );
+        assert_eq!(
+            reinvestment.detect(1_010.0, 0.0, 3),
+            Detection::BelowThreshold { increase: 30.0 }
+        );
+        assert_eq!(
+            reinvestment.detect(1_040.0, 0.0, 4),
+            Detection::NewCash { increase: 60.0 }
+        );
+        assert_eq!(reinvestment.last_cash, Some(1_040.0));
+
+        let orders = reinvestment.orders(60.0);
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].amount, Some(45.0));
+        assert_eq!(orders[1].amount, Some(15.0));
+        assert!(orders.iter().all(|o| o.side == OrderSide::Buy));
+
+        reinvestment.targets.clear();
+        assert!(reinvestment.validate().is_err());
+    }
+
+    #[test]
+    fn test_recorded_flows() {
+        let account = "1234567890";
+        let mut reinvestment = Reinvestment {
+            account: account.to_string(),
+            threshold: 50.0,
+            targets: vec![],
+            dry_run: false,
+            last_cash: Some(100.0),
+            last_checked: Some(1_000),
+        };
+
+        // The valuation went up with the market, the cash did not move
+        assert_eq!(
+            reinvestment.detect(100.0, 0.0, 2_000),
+            Detection::BelowThreshold { increase: 0.0 }
+        );
+
+        // The monthly top-up and the DCA order it paid for
+        let transfers = vec![TransferPassed {
+            id: "top-up".to_string(),
+            from: "0987654321".to_string(),
+            to: account.to_string(),
+            amount: 500.0,
+            timestamp: 2_500,
+            reason: None,
+        }];
+        let mut order = OrderPassed::new(
+            "1".to_string(),
+            100.0,
+            Some(2_600),
+            OrderArgs {
+                account: account.to_string(),
+                symbol: "1rTCW8".to_string(),
+                quantity: Some(3),
+                amount: None,
+                side: OrderSide::Buy,
+            },
+            OrderSource::Dca,
+        );
+        order.fees = 2.0;
+        // Placed on the website, not recorded by the app
+        let manual = OrderPassed {
+            source: OrderSource::Manual,
+            ..order.clone()
+        };
+        let recorded = recorded_flows(account, 2_000, &[order, manual], &transfers);
+        assert_eq!(recorded, 500.0 - 302.0);
+        assert_eq!(
+            reinvestment.detect(298.0, recorded, 3_000),
+            Detection::BelowThreshold { increase: 0.0 }
+        );
+
+        // A dividend
+        assert_eq!(
+            reinvestment.detect(358.0, 0.0, 4_000),
+            Detection::NewCash { increase: 60.0 }
+        );
+        assert_eq!(recorded_flows(account, 3_000, &[], &transfers), 0.0);
+    }
+}
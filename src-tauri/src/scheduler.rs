@@ -1,7 +1,22 @@
-use crate::order::{new_order, OrderArgs, OrderPassed};
+use crate::basket::Basket;
+use crate::envelope::{check_ceiling, load_envelopes, Envelope};
+use crate::limits::{load_limits, Confirmation, LimitError, Limits};
+use crate::order::{
+    load_orders, new_order, push_order, OrderArgs, OrderPassed, OrderRequest, OrderSide,
+    OrderSource,
+};
+use crate::price_guard::{beyond_limit, GuardAction, PriceGuard, PriceMove};
+use crate::rebalance::{report, Rebalance};
+use crate::reinvest::{recorded_flows, Detection, Reinvestment};
+use crate::snapshot::snapshot_if_due;
+use crate::split::SplitOrder;
+use crate::trading::{get_cash_balance, get_summary_positions};
+use crate::transfer::{load_transfers, push_transfer, transfer};
+use crate::trigger::{scale_quantity, Trigger};
+use crate::value_averaging::ValueAveraging;
 use crate::BoursoState;
 use anyhow::Result;
-use chrono::{DateTime, Datelike, Local, Utc};
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveDate, Utc};
 use log::debug;
 use notify_rust::Notification;
 #[cfg(target_os = "macos")]
@@ -16,6 +31,10 @@ use tauri_plugin_store::{Store, StoreExt};
 
 pub const HISTORY_FILE_PATH: &str = "history.json";
 
+/// Extra cash transferred when topping up a trading account, covers the
+/// price moving between the quote and the execution, and the order fees
+const TOP_UP_MARGIN: f64 = 0.02;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum Schedule {
@@ -24,6 +43,31 @@ pub enum Schedule {
     Monthly { day: u32 },
 }
 
+impl Schedule {
+    /// Whether `date` is the scheduled day, the days after the end of a
+    /// month run on its last day. The days of the week go from Monday, 1,
+    /// to Sunday, 7, the first weekly jobs used 0 for Monday.
+    pub fn runs_on(&self, date: NaiveDate) -> bool {
+        match self {
+            Schedule::Daily => true,
+            Schedule::Weekly { day } => {
+                date.weekday().number_from_monday() == (*day).clamp(1, 7) as u32
+            }
+            Schedule::Monthly { day } => {
+                let last_day = (date + Duration::days(1)).day() == 1;
+                date.day() == *day || (last_day && date.day() < *day)
+            }
+        }
+    }
+
+    /// Last scheduled day on or before `date`
+    pub fn last_due(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (0..=31)
+            .filter_map(|days| date.checked_sub_days(Days::new(days)))
+            .find(|date| self.runs_on(*date))
+    }
+}
+
 impl Display for Schedule {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -50,6 +94,12 @@ pub struct Transfer {
 pub enum Command {
     Order(OrderArgs),
     Transfer(Transfer),
+    Split(SplitOrder),
+    Reinvest(Reinvestment),
+    Basket(Basket),
+    Rebalance(Rebalance),
+    #[serde(rename = "value_averaging")]
+    ValueAveraging(ValueAveraging),
 }
 
 impl Display for Command {
@@ -63,6 +113,11 @@ impl Display for Command {
                 }
             }
             Command::Transfer(transfer_args) => transfer_args.amount.clone(),
+            Command::Split(split) => split.total_amount.to_string(),
+            Command::Reinvest(reinvest) => reinvest.threshold.to_string(),
+            Command::Basket(basket) => basket.amount.to_string(),
+            Command::Rebalance(rebalance) => rebalance.threshold.to_string(),
+            Command::ValueAveraging(strategy) => strategy.increment.to_string(),
         };
 
         write!(
@@ -78,6 +133,23 @@ impl Display for Command {
                 Command::Transfer(transfer_args) => {
                     format!("transfer_{}_{}", transfer_args.from, transfer_args.to)
                 }
+                Command::Split(split) => {
+                    format!("split_{}_{}_{}", split.side, qty_or_amount, split.symbol)
+                }
+                Command::Reinvest(reinvest) => {
+                    format!("reinvest_{}_{}", qty_or_amount, reinvest.account)
+                }
+                Command::Basket(basket) => {
+                    let symbols: Vec<&str> =
+                        basket.legs.iter().map(|l| l.symbol.as_str()).collect();
+                    format!("basket_{}_{}", qty_or_amount, symbols.join("_"))
+                }
+                Command::Rebalance(rebalance) => {
+                    format!("rebalance_{}_{}", qty_or_amount, rebalance.account)
+                }
+                Command::ValueAveraging(strategy) => {
+                    format!("valueaveraging_{}_{}", qty_or_amount, strategy.symbol)
+                }
             }
         )
     }
@@ -89,33 +161,224 @@ pub struct Job {
     pub schedule: Schedule,
     pub last_run: i64,
     pub command: Command,
+    /// Carry the cash left unspent by amount-based orders over to the next run
+    #[serde(default)]
+    pub carry_over: bool,
+    /// Unspent cash carried over from the previous runs, in euros
+    #[serde(default)]
+    pub leftover: f64,
+    /// Account to transfer the missing cash from when the trading account
+    /// cannot afford the order, the job is skipped when unset
+    #[serde(default)]
+    pub top_up_from: Option<String>,
+    /// Outcome of the last run, displayed in the job details
+    #[serde(default)]
+    pub last_outcome: Option<JobOutcome>,
+    /// Skip or postpone the order when the price looks abnormal
+    #[serde(default)]
+    pub price_guard: Option<PriceGuard>,
+    /// Market condition the order waits for, or scales the order with
+    #[serde(default)]
+    pub trigger: Option<Trigger>,
+}
+
+/// Outcome of a job run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobOutcome {
+    /// The order was placed
+    Executed { quantity: u64, price: f64 },
+    /// The budget could not buy a single share and was carried over
+    CarriedOver { leftover: f64 },
+    /// The trading account did not have enough cash for the order
+    InsufficientFunds { required: f64, available: f64 },
+    /// The price moved too much from the reference price of the guard
+    PriceMoved {
+        price: f64,
+        reference: f64,
+        change_percent: f64,
+        postponed: bool,
+    },
+    /// The price went beyond the limit of the guard before sending the order
+    BeyondLimit { price: f64, limit: f64 },
+    /// The order or the top-up is above the configured limits
+    LimitExceeded { reason: LimitError },
+    /// The scheduled transfer was made
+    Transferred { amount: f64 },
+    /// The order would have been placed, in dry-run mode
+    Planned { quantity: u64, price: f64 },
+    /// The job, or the order of a leg, failed
+    Failed { error: String },
+    /// Not enough new cash on the account to reinvest
+    NoNewCash { increase: f64, threshold: f64 },
+    /// The new cash was split across the orders of the legs
+    Reinvested {
+        cash: f64,
+        legs: Vec<LegOutcome>,
+        dry_run: bool,
+    },
+    /// The orders of the legs of a basket were placed
+    Basket { amount: f64, legs: Vec<LegOutcome> },
+    /// The allocation is within the drift threshold of the targets
+    Balanced { max_drift: f64, threshold: f64 },
+    /// The orders getting back to the targets were placed
+    Rebalanced {
+        max_drift: f64,
+        legs: Vec<LegOutcome>,
+        dry_run: bool,
+    },
+    /// The position is on its value averaging path, nothing to trade
+    OnTarget { target: f64, value: f64 },
+    /// The condition of the trigger does not hold, evaluated again on the
+    /// next scheduler run
+    NotTriggered { reason: String },
+}
+
+/// Outcome of one of the orders of a job placing several orders
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LegOutcome {
+    pub symbol: String,
+    /// Amount allocated to the leg, in euros
+    pub amount: f64,
+    pub outcome: JobOutcome,
 }
 
 impl Job {
-    #[allow(dead_code)]
     pub fn new(schedule: Schedule, command: Command) -> Self {
         Job {
             id: format!("{}{}", schedule, command),
             schedule,
             last_run: Local::now().timestamp(),
             command,
+            carry_over: false,
+            leftover: 0.0,
+            top_up_from: None,
+            last_outcome: None,
+            price_guard: None,
+            trigger: None,
         }
     }
 }
 
 impl Job {
-    fn should_run(&self, now: DateTime<Utc>, last_run: DateTime<Utc>) -> bool {
-        match &self.schedule {
-            Schedule::Daily => now.date_naive() > last_run.date_naive(),
-            Schedule::Weekly { day: _ } => {
-                now.date_naive() > last_run.date_naive()
-                    || now.iso_week().week() > last_run.iso_week().week()
+    pub fn should_run(&self, now: DateTime<Utc>, last_run: DateTime<Utc>) -> bool {
+        if let Command::Split(split) = &self.command {
+            if split.is_complete() {
+                return false;
             }
-            Schedule::Monthly { day: _ } => {
-                now.date_naive() > last_run.date_naive()
-                    || now.date_naive().month() > last_run.date_naive().month()
+            if let Some(elapsed) = split.interval_elapsed(now, last_run) {
+                return elapsed;
             }
         }
+
+        let today = now.date_naive();
+        if today <= last_run.date_naive() {
+            return false;
+        }
+        if self.schedule.runs_on(today) {
+            return true;
+        }
+
+        // A job that did not complete on its scheduled day, e.g. on a closed
+        // market, keeps being retried until the next one
+        self.waiting()
+            && self
+                .schedule
+                .last_due(today)
+                .is_some_and(|due| due > last_run.date_naive())
+    }
+
+    /// Whether the last run did not complete the job, which runs again
+    fn waiting(&self) -> bool {
+        matches!(
+            self.last_outcome,
+            Some(
+                JobOutcome::NotTriggered { .. }
+                    | JobOutcome::PriceMoved {
+                        postponed: true,
+                        ..
+                    }
+                    | JobOutcome::LimitExceeded {
+                        reason: LimitError::ConfirmationRequired { .. } | LimitError::WrongPin
+                    }
+                    | JobOutcome::Failed { .. }
+            )
+        )
+    }
+
+    /// Mark the job as run for the current period
+    fn complete(&mut self, outcome: JobOutcome) {
+        self.last_run = Local::now().timestamp();
+        self.last_outcome = Some(outcome);
+    }
+
+    /// Record a limit hit. Above a soft threshold the job stays due until it
+    /// is confirmed from the UI, above a hard limit it is skipped.
+    fn limit_exceeded(&mut self, reason: LimitError) {
+        let already_notified = matches!(
+            &self.last_outcome,
+            Some(JobOutcome::LimitExceeded { reason: last }) if *last == reason
+        );
+        if !already_notified {
+            notify(&format!("Job {} was not run: {}", self.id, reason));
+        }
+
+        let needs_confirmation = matches!(
+            reason,
+            LimitError::ConfirmationRequired { .. } | LimitError::WrongPin
+        );
+        let outcome = JobOutcome::LimitExceeded { reason };
+        if needs_confirmation {
+            self.last_outcome = Some(outcome);
+        } else {
+            self.complete(outcome);
+        }
+    }
+
+    /// Record a price beyond the guard. A postponed job is retried on every
+    /// scheduler run, a skipped one waits for the next period.
+    fn price_moved(&mut self, symbol: &str, price: f64, price_move: PriceMove, postponed: bool) {
+        // Only notify the first time the job is postponed
+        let already_postponed = matches!(
+            self.last_outcome,
+            Some(JobOutcome::PriceMoved {
+                postponed: true,
+                ..
+            })
+        );
+        if !already_postponed {
+            notify(&format!(
+                "{} order {}: price {:.2}€ moved {:.2}% from {:.2}€",
+                symbol,
+                if postponed { "postponed" } else { "skipped" },
+                price,
+                price_move.change_percent,
+                price_move.reference
+            ));
+        }
+
+        let outcome = JobOutcome::PriceMoved {
+            price,
+            reference: price_move.reference,
+            change_percent: price_move.change_percent,
+            postponed,
+        };
+        if postponed {
+            // Keep last_run so the job runs again on the next scheduler run
+            self.last_outcome = Some(outcome);
+        } else {
+            self.complete(outcome);
+        }
+    }
+
+    /// Whether the job is due but waits for a confirmation from the UI
+    pub fn awaiting_confirmation(&self) -> bool {
+        matches!(
+            self.last_outcome,
+            Some(JobOutcome::LimitExceeded {
+                reason: LimitError::ConfirmationRequired { .. } | LimitError::WrongPin
+            })
+        ) && self.should_run_now()
     }
 
     pub fn should_run_now(&self) -> bool {
@@ -129,77 +392,908 @@ impl Job {
         &mut self,
         store: Arc<Store<Wry>>,
         web_client: &bourso_api::client::BoursoWebClient,
+        limits: &Limits,
+        envelopes: &[Envelope],
+        confirmation: Option<&Confirmation>,
     ) -> Result<()> {
-        match &self.command {
+        match self.command.clone() {
             Command::Order(order) => {
-                let market_open = match web_client.is_market_open(&order.symbol).await {
-                    Ok(market_open) => market_open,
-                    Err(e) => {
-                        log::error!("Error checking market status: {}", e);
-                        return Err(anyhow::anyhow!("Error checking market status"));
+                let Some(order) = self.check_trigger(order, web_client).await? else {
+                    return Ok(());
+                };
+
+                // With carry-over, the unspent cash of the previous runs is added
+                // to this run's budget so that the invested total matches the
+                // amount set by the user over time.
+                let carried = if self.carry_over { self.leftover } else { 0.0 };
+                let budget = order
+                    .amount
+                    .filter(|_| self.carry_over)
+                    .map(|amount| amount + carried);
+
+                let placement = self
+                    .place_order(
+                        &order,
+                        carried,
+                        &store,
+                        web_client,
+                        limits,
+                        envelopes,
+                        confirmation,
+                    )
+                    .await?;
+
+                self.settle_order(placement, budget, &order.symbol)?;
+            }
+            Command::Split(mut split) => {
+                let slice = split.next_slice();
+
+                match self
+                    .place_order(
+                        &slice,
+                        0.0,
+                        &store,
+                        web_client,
+                        limits,
+                        envelopes,
+                        confirmation,
+                    )
+                    .await?
+                {
+                    Placement::Placed { quantity, price } => {
+                        split.record_slice(quantity, price);
                     }
+                    Placement::TooLow => {
+                        // The amount of the slice is spread over the next ones
+                        split.record_slice(0, 0.0);
+                        self.complete(JobOutcome::CarriedOver {
+                            leftover: split.remaining_amount(),
+                        });
+                    }
+                    Placement::Skipped => {}
+                }
+
+                if split.is_complete() {
+                    notify(&format!(
+                        "{} split order completed: {} shares for {:.2}€",
+                        split.symbol, split.executed_quantity, split.executed_amount
+                    ));
+                }
+                self.command = Command::Split(split);
+            }
+            Command::Reinvest(mut reinvest) => {
+                reinvest.validate()?;
+                self.reinvest(
+                    &mut reinvest,
+                    &store,
+                    web_client,
+                    limits,
+                    envelopes,
+                    confirmation,
+                )
+                .await?;
+                self.command = Command::Reinvest(reinvest);
+            }
+            Command::Basket(basket) => {
+                basket.validate()?;
+                let Some(legs) = self
+                    .place_legs(
+                        &basket.orders(),
+                        false,
+                        &store,
+                        web_client,
+                        limits,
+                        envelopes,
+                        confirmation,
+                    )
+                    .await?
+                else {
+                    return Ok(());
                 };
 
-                if !market_open {
-                    return Err(anyhow::anyhow!("Market is closed"));
+                notify(&format!(
+                    "Basket of {:.2}€: {}",
+                    basket.amount,
+                    legs_summary(&legs)
+                ));
+                self.complete(JobOutcome::Basket {
+                    amount: basket.amount,
+                    legs,
+                });
+            }
+            Command::Rebalance(rebalance) => {
+                rebalance.validate()?;
+                let report = report(web_client, &rebalance).await?;
+                if report.orders.is_empty() {
+                    debug!(
+                        "Job {}: drift of {:.2} points within {:.2}",
+                        self.id, report.max_drift, rebalance.threshold
+                    );
+                    self.complete(JobOutcome::Balanced {
+                        max_drift: report.max_drift,
+                        threshold: rebalance.threshold,
+                    });
+                    return Ok(());
                 }
 
-                let quantity = order.get_qty(web_client).await?;
+                let Some(legs) = self
+                    .place_legs(
+                        &rebalance.order_args(&report),
+                        rebalance.dry_run,
+                        &store,
+                        web_client,
+                        limits,
+                        envelopes,
+                        confirmation,
+                    )
+                    .await?
+                else {
+                    return Ok(());
+                };
+
+                notify(&format!(
+                    "{} rebalanced{}, drift of {:.2} points: {}",
+                    rebalance.account,
+                    if rebalance.dry_run { " (dry run)" } else { "" },
+                    report.max_drift,
+                    legs_summary(&legs)
+                ));
+                self.complete(JobOutcome::Rebalanced {
+                    max_drift: report.max_drift,
+                    legs,
+                    dry_run: rebalance.dry_run,
+                });
+            }
+            Command::ValueAveraging(mut strategy) => {
+                strategy.validate()?;
+                let quantity: f64 = get_summary_positions(web_client, &strategy.account)
+                    .await?
+                    .iter()
+                    .filter(|p| p.symbol == strategy.symbol)
+                    .map(|p| p.quantity)
+                    .sum();
+                let price = web_client
+                    .instrument_quote(&strategy.symbol)
+                    .await
+                    .map_err(|e| {
+                        anyhow::anyhow!(
+                            "Error while getting price for {}: {:?}",
+                            strategy.symbol,
+                            e
+                        )
+                    })?
+                    .last;
+
+                strategy.anchor(quantity * price);
+                let step = strategy.step(quantity * price);
+                debug!(
+                    "Job {}: position worth {:.2} for a target of {:.2}, {:.2} to trade",
+                    self.id, step.value, step.target, step.amount
+                );
+                let last_run = self.last_run;
+                match strategy.order(&step, price) {
+                    Some(order) => {
+                        let placement = self
+                            .place_order(
+                                &order,
+                                0.0,
+                                &store,
+                                web_client,
+                                limits,
+                                envelopes,
+                                confirmation,
+                            )
+                            .await?;
+                        // The next target catches up with the amount missed
+                        if let Placement::TooLow = placement {
+                            self.complete(JobOutcome::CarriedOver {
+                                leftover: round_cents(step.amount),
+                            });
+                        }
+                    }
+                    None => self.complete(JobOutcome::OnTarget {
+                        target: step.target,
+                        value: step.value,
+                    }),
+                }
 
-                let order_details = new_order(
+                // The period is over unless the job waits for a confirmation
+                // or a better price
+                if self.last_run != last_run {
+                    strategy.periods += 1;
+                }
+                self.command = Command::ValueAveraging(strategy);
+            }
+            Command::Transfer(transfer_args) => {
+                let amount: f64 = transfer_args.amount.parse().map_err(|_| {
+                    anyhow::anyhow!("Invalid transfer amount: {}", transfer_args.amount)
+                })?;
+
+                if let Err(reason) = limits.check_transfer(amount, confirmation) {
+                    self.limit_exceeded(reason);
+                    return Ok(());
+                }
+                if !self.check_ceiling(&store, envelopes, &transfer_args.to, amount)? {
+                    return Ok(());
+                }
+
+                let passed = transfer(
                     web_client,
+                    &transfer_args.from,
+                    &transfer_args.to,
+                    amount,
+                    Some(format!("Scheduled transfer {}", self.id)),
+                )
+                .await?;
+                push_transfer(&store, &passed)?;
+                notify(&format!(
+                    "{:.2}€ transferred from {} to {}",
+                    amount, transfer_args.from, transfer_args.to
+                ));
+                self.complete(JobOutcome::Transferred { amount });
+            }
+        };
+        Ok(())
+    }
+
+    /// Evaluate the trigger of the job, returns the order scaled by the
+    /// trigger, or `None` when the trigger does not fire. The job is then
+    /// left due so that it is evaluated again on the next scheduler run.
+    async fn check_trigger(
+        &mut self,
+        order: OrderArgs,
+        web_client: &bourso_api::client::BoursoWebClient,
+    ) -> Result<Option<OrderArgs>> {
+        let Some(mut trigger) = self.trigger.clone() else {
+            return Ok(Some(order));
+        };
+        trigger.validate()?;
+        // Nothing fires on a closed market
+        check_market(web_client, &order.symbol).await?;
+
+        let price = order.get_price(web_client).await?;
+        let evaluation = trigger.evaluate(web_client, &order.symbol, price).await;
+        let factor = trigger.factor(&evaluation);
+        debug!(
+            "Job {} trigger {}: {}",
+            self.id,
+            if evaluation.fired {
+                "fired"
+            } else {
+                "not fired"
+            },
+            evaluation.reason
+        );
+        let reason = evaluation.reason.clone();
+        trigger.record(evaluation);
+        self.trigger = Some(trigger);
+
+        let Some(factor) = factor else {
+            self.last_outcome = Some(JobOutcome::NotTriggered { reason });
+            return Ok(None);
+        };
+        if factor != 1.0 {
+            notify(&format!(
+                "{} order scaled by {}: {}",
+                order.symbol, factor, reason
+            ));
+        }
+
+        Ok(Some(OrderArgs {
+            quantity: order
+                .quantity
+                .map(|quantity| scale_quantity(quantity, factor)),
+            amount: order.amount.map(|amount| round_cents(amount * factor)),
+            ..order
+        }))
+    }
+
+    /// Buy the targets of `reinvest` with the cash credited to its account
+    /// since the previous run. The transfers and the orders recorded by the
+    /// app are not new cash, and the new cash is capped by the cash
+    /// available.
+    async fn reinvest(
+        &mut self,
+        reinvest: &mut Reinvestment,
+        store: &Store<Wry>,
+        web_client: &bourso_api::client::BoursoWebClient,
+        limits: &Limits,
+        envelopes: &[Envelope],
+        confirmation: Option<&Confirmation>,
+    ) -> Result<()> {
+        let available = get_cash_balance(web_client, &reinvest.account).await?;
+        let recorded = recorded_flows(
+            &reinvest.account,
+            reinvest.last_checked.unwrap_or_default(),
+            &load_orders(store)?,
+            &load_transfers(store)?,
+        );
+
+        let previous = (reinvest.last_cash, reinvest.last_checked);
+        let cash = match reinvest.detect(available, recorded, Local::now().timestamp()) {
+            Detection::Initialized => 0.0,
+            Detection::BelowThreshold { increase } => increase,
+            Detection::NewCash { increase } => increase.min(available),
+        };
+        if cash < reinvest.threshold {
+            debug!(
+                "Job {}: {:.2}€ of new cash, below {:.2}€",
+                self.id, cash, reinvest.threshold
+            );
+            self.complete(JobOutcome::NoNewCash {
+                increase: cash,
+                threshold: reinvest.threshold,
+            });
+            return Ok(());
+        }
+
+        let Some(legs) = self
+            .place_legs(
+                &reinvest.orders(cash),
+                reinvest.dry_run,
+                store,
+                web_client,
+                limits,
+                envelopes,
+                confirmation,
+            )
+            .await?
+        else {
+            // Nothing was bought, the cash is detected again on the next run
+            (reinvest.last_cash, reinvest.last_checked) = previous;
+            return Ok(());
+        };
+
+        notify(&format!(
+            "{:.2}€ of new cash on {}{}: {}",
+            cash,
+            reinvest.account,
+            if reinvest.dry_run { " (dry run)" } else { "" },
+            legs_summary(&legs)
+        ));
+        self.complete(JobOutcome::Reinvested {
+            cash,
+            legs,
+            dry_run: reinvest.dry_run,
+        });
+
+        Ok(())
+    }
+
+    /// Carry the unspent budget of an order over to the next run, and start
+    /// the cooldown of the trigger once the order is placed. A period skipped
+    /// for the cash or the price carries its whole budget, a postponed one
+    /// is retried with the same budget.
+    fn settle_order(
+        &mut self,
+        placement: Placement,
+        budget: Option<f64>,
+        symbol: &str,
+    ) -> Result<()> {
+        if let (Placement::Placed { .. }, Some(trigger)) = (&placement, &mut self.trigger) {
+            trigger.start_cooldown();
+        }
+
+        match (placement, budget) {
+            (Placement::Placed { quantity, price }, Some(budget)) => {
+                self.leftover = round_cents(budget - quantity as f64 * price);
+            }
+            (Placement::TooLow, Some(budget)) => {
+                self.leftover = round_cents(budget);
+                debug!(
+                    "Job {} carried over {} to the next run",
+                    self.id, self.leftover
+                );
+                notify(&format!(
+                    "{:.2}€ carried over to the next {} order",
+                    self.leftover, symbol
+                ));
+                self.complete(JobOutcome::CarriedOver {
+                    leftover: self.leftover,
+                });
+            }
+            (Placement::TooLow, None) => {
+                return Err(anyhow::anyhow!(
+                    "Amount is too low to buy a single share of {}",
+                    symbol
+                ));
+            }
+            (Placement::Skipped, Some(budget))
+                if matches!(
+                    self.last_outcome,
+                    Some(
+                        JobOutcome::InsufficientFunds { .. }
+                            | JobOutcome::PriceMoved {
+                                postponed: false,
+                                ..
+                            }
+                    )
+                ) =>
+            {
+                self.leftover = round_cents(budget);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Check a transfer to `account` against the ceiling of its envelope,
+    /// returns whether the transfer can be made
+    fn check_ceiling(
+        &mut self,
+        store: &Store<Wry>,
+        envelopes: &[Envelope],
+        account: &str,
+        amount: f64,
+    ) -> Result<bool> {
+        let transfers = load_transfers(store)?;
+
+        match check_ceiling(envelopes, &transfers, account, amount) {
+            Ok(None) => Ok(true),
+            Ok(Some(warning)) => {
+                notify(&format!("Job {}: {}", self.id, warning));
+                Ok(true)
+            }
+            Err(reason) => {
+                self.limit_exceeded(reason);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Place an order for the job, going through the market, price, limits
+    /// and cash checks. The job outcome is set unless the amount is too low
+    /// to buy a single share, which is left to the caller.
+    #[allow(clippy::too_many_arguments)]
+    async fn place_order(
+        &mut self,
+        order: &OrderArgs,
+        carried: f64,
+        store: &Store<Wry>,
+        web_client: &bourso_api::client::BoursoWebClient,
+        limits: &Limits,
+        envelopes: &[Envelope],
+        confirmation: Option<&Confirmation>,
+    ) -> Result<Placement> {
+        check_market(web_client, &order.symbol).await?;
+
+        let (quantity, price) = order.get_qty(web_client, carried).await?;
+
+        let mut limit = None;
+        if let Some(guard) = &self.price_guard {
+            if let Some(price_move) = guard.check(web_client, &order.symbol, price).await? {
+                let postponed = guard.action == GuardAction::Postpone;
+                debug!(
+                    "Job {} price guard triggered: {} is {:.2}% away from {}",
+                    self.id, price, price_move.change_percent, price_move.reference
+                );
+                self.price_moved(&order.symbol, price, price_move, postponed);
+                return Ok(Placement::Skipped);
+            }
+            limit = guard.limit_price(price, order.side);
+        }
+
+        if quantity == 0 {
+            return Ok(Placement::TooLow);
+        }
+
+        if let Err(reason) = limits.check_order(&order.symbol, quantity, price, confirmation) {
+            self.limit_exceeded(reason);
+            return Ok(Placement::Skipped);
+        }
+
+        if order.side == OrderSide::Buy {
+            let required = quantity as f64 * price;
+            let bought = format!("{} {}", quantity, order.symbol);
+            if !self
+                .ensure_cash(
                     &order.account,
+                    required,
+                    &bought,
                     &order.symbol,
-                    quantity,
-                    &order.side,
+                    store,
+                    web_client,
+                    limits,
+                    envelopes,
+                    confirmation,
                 )
-                .await?;
+                .await?
+            {
+                return Ok(Placement::Skipped);
+            }
+        }
+
+        // The order is sent as a limit order, and not at all when the price
+        // is already beyond the limit
+        if let Some(limit) = limit {
+            let price = order.get_price(web_client).await?;
+            if beyond_limit(price, limit, order.side) {
+                notify(&format!(
+                    "{} order skipped: price {:.2}€ beyond limit {:.2}€",
+                    order.symbol, price, limit
+                ));
+                self.complete(JobOutcome::BeyondLimit { price, limit });
+                return Ok(Placement::Skipped);
+            }
+        }
+
+        let executed_price = self
+            .send_order(order, quantity, price, limit, store, web_client)
+            .await?;
+
+        debug!("Running job: {} with last_run: {}", self.id, self.last_run);
+        // Update last run to now
+        self.complete(JobOutcome::Executed {
+            quantity,
+            price: executed_price,
+        });
+        debug!("Updated job: {} with last_run: {}", self.id, self.last_run);
 
-                let order = OrderPassed {
-                    id: order_details.0,
-                    price: order_details.1.unwrap(),
-                    timestamp: DateTime::from_timestamp(Local::now().timestamp(), 0)
-                        .map(|dt| dt.timestamp()),
-                    args: OrderArgs {
-                        account: order.account.to_string(),
-                        symbol: order.symbol.to_string(),
-                        quantity: order.quantity,
-                        amount: order.amount,
-                        side: order.side.to_string(),
+        notify(&format!("{} {} were bought", quantity, order.symbol));
+
+        Ok(Placement::Placed {
+            quantity,
+            price: executed_price,
+        })
+    }
+
+    /// Make sure the trading `account` has `required` euros, topping it up
+    /// from `top_up_from` when set. Returns whether the orders can be placed,
+    /// the job outcome tells why otherwise.
+    #[allow(clippy::too_many_arguments)]
+    async fn ensure_cash(
+        &mut self,
+        account: &str,
+        required: f64,
+        bought: &str,
+        note: &str,
+        store: &Store<Wry>,
+        web_client: &bourso_api::client::BoursoWebClient,
+        limits: &Limits,
+        envelopes: &[Envelope],
+        confirmation: Option<&Confirmation>,
+    ) -> Result<bool> {
+        let available = get_cash_balance(web_client, account).await?;
+        if required <= available {
+            return Ok(true);
+        }
+
+        let Some(source) = self.top_up_from.clone() else {
+            debug!(
+                "Job {} skipped: {} needed, {} available",
+                self.id, required, available
+            );
+            notify(&format!(
+                "Insufficient funds to buy {}: {:.2}€ needed, {:.2}€ available",
+                bought, required, available
+            ));
+            self.complete(JobOutcome::InsufficientFunds {
+                required,
+                available,
+            });
+            return Ok(false);
+        };
+
+        let top_up = ceil_cents((required - available) * (1.0 + TOP_UP_MARGIN));
+        if let Err(reason) = limits.check_transfer(top_up, confirmation) {
+            self.limit_exceeded(reason);
+            return Ok(false);
+        }
+        if !self.check_ceiling(store, envelopes, account, top_up)? {
+            return Ok(false);
+        }
+
+        debug!("Job {} topping up {} from {}", self.id, top_up, source);
+        let top_up_transfer = transfer(
+            web_client,
+            &source,
+            account,
+            top_up,
+            Some(format!("DCA {}", note)),
+        )
+        .await?;
+        push_transfer(store, &top_up_transfer)?;
+        notify(&format!("{:.2}€ transferred to buy {}", top_up, bought));
+
+        Ok(true)
+    }
+
+    /// Send the order, as a limit order at `limit` when set, and record it in
+    /// the history, returns the execution price
+    async fn send_order(
+        &self,
+        order: &OrderArgs,
+        quantity: u64,
+        price: f64,
+        limit: Option<f64>,
+        store: &Store<Wry>,
+        web_client: &bourso_api::client::BoursoWebClient,
+    ) -> Result<f64> {
+        let request = OrderRequest::new(
+            order.account.to_string(),
+            order.symbol.to_string(),
+            quantity,
+            order.side,
+        )?
+        .with_limit(limit);
+        let (id, executed_price) = new_order(web_client, &request).await?;
+
+        // The order is placed at this point, failing the job would make it
+        // run again on the next scheduler run and place a second order
+        let estimated_price = executed_price.is_none();
+        if estimated_price {
+            log::warn!(
+                "Order {} placed without an execution price, using the quote {}",
+                id,
+                price
+            );
+        }
+        let executed_price = executed_price.unwrap_or(price);
+
+        let mut passed = OrderPassed::new(
+            id,
+            executed_price,
+            DateTime::from_timestamp(Local::now().timestamp(), 0).map(|dt| dt.timestamp()),
+            OrderArgs {
+                account: order.account.to_string(),
+                symbol: order.symbol.to_string(),
+                // Executed quantity, also for amount-based orders
+                quantity: Some(quantity),
+                amount: order.amount,
+                side: order.side,
+            },
+            OrderSource::Dca,
+        );
+        passed.job_id = Some(self.id.clone());
+        passed.estimated_price = estimated_price;
+
+        push_order(store, &passed)?;
+
+        Ok(executed_price)
+    }
+
+    /// Place the orders of a multi-order job as one unit. The markets and
+    /// the cash are checked once for all the legs, nothing is placed when a
+    /// check fails and the job outcome tells why, `None` is returned. A leg
+    /// failing once the first order is placed does not stop the others.
+    /// With `dry_run`, the orders are only planned.
+    #[allow(clippy::too_many_arguments)]
+    async fn place_legs(
+        &mut self,
+        orders: &[OrderArgs],
+        dry_run: bool,
+        store: &Store<Wry>,
+        web_client: &bourso_api::client::BoursoWebClient,
+        limits: &Limits,
+        envelopes: &[Envelope],
+        confirmation: Option<&Confirmation>,
+    ) -> Result<Option<Vec<LegOutcome>>> {
+        if !dry_run {
+            for order in orders {
+                check_market(web_client, &order.symbol).await?;
+            }
+        }
+
+        // Quantity, price and guard limit of the legs to place
+        let mut planned = vec![];
+        let mut legs = vec![];
+        for order in orders {
+            let (quantity, price) = order.get_qty(web_client, 0.0).await?;
+
+            let mut limit = None;
+            if let Some(guard) = &self.price_guard {
+                if let Some(price_move) = guard.check(web_client, &order.symbol, price).await? {
+                    // A postponed job waits for all its legs to be within the
+                    // guard, nothing is placed until then
+                    if guard.action == GuardAction::Postpone {
+                        self.price_moved(&order.symbol, price, price_move, true);
+                        return Ok(None);
+                    }
+                    legs.push(LegOutcome {
+                        symbol: order.symbol.clone(),
+                        amount: order.amount.unwrap_or_default(),
+                        outcome: JobOutcome::PriceMoved {
+                            price,
+                            reference: price_move.reference,
+                            change_percent: price_move.change_percent,
+                            postponed: false,
+                        },
+                    });
+                    continue;
+                }
+                limit = guard.limit_price(price, order.side);
+            }
+
+            if quantity == 0 {
+                legs.push(LegOutcome {
+                    symbol: order.symbol.clone(),
+                    amount: order.amount.unwrap_or_default(),
+                    outcome: JobOutcome::Failed {
+                        error: format!("Amount too low to buy a single share of {}", order.symbol),
                     },
-                };
+                });
+                continue;
+            }
+            if let Err(reason) = limits.check_order(&order.symbol, quantity, price, confirmation) {
+                self.limit_exceeded(reason);
+                return Ok(None);
+            }
+            planned.push((order, quantity, price, limit));
+        }
 
-                let orders = store.get("orders").unwrap_or(serde_json::json!([]));
-                let mut orders: Vec<OrderPassed> = serde_json::from_value(orders)?;
-                orders.push(order.clone());
-                store.set("orders", serde_json::json!(&orders));
-                store.save().unwrap();
+        if dry_run {
+            legs.extend(
+                planned
+                    .iter()
+                    .map(|(order, quantity, price, _)| LegOutcome {
+                        symbol: order.symbol.clone(),
+                        amount: order.amount.unwrap_or(*quantity as f64 * price),
+                        outcome: JobOutcome::Planned {
+                            quantity: *quantity,
+                            price: *price,
+                        },
+                    }),
+            );
+            return Ok(Some(legs));
+        }
 
-                debug!("Running job: {} with last_run: {}", self.id, self.last_run);
-                // Update last run to now
-                self.last_run = DateTime::from_timestamp(Local::now().timestamp(), 0)
-                    .unwrap()
-                    .timestamp();
-                debug!("Updated job: {} with last_run: {}", self.id, self.last_run);
+        // The sales are expected first and their proceeds to pay for the
+        // purchases of the same account
+        let mut required = std::collections::HashMap::<&str, f64>::new();
+        for (order, quantity, price, _) in &planned {
+            let value = *quantity as f64 * price;
+            *required.entry(order.account.as_str()).or_default() += match order.side {
+                OrderSide::Buy => value,
+                OrderSide::Sell => -value,
+            };
+        }
+        for (account, required) in required {
+            if required <= 0.0 {
+                continue;
+            }
+            let bought = planned
+                .iter()
+                .filter(|(order, ..)| order.account == account && order.side == OrderSide::Buy)
+                .map(|(order, quantity, ..)| format!("{} {}", quantity, order.symbol))
+                .collect::<Vec<_>>()
+                .join(", ");
+            if !self
+                .ensure_cash(
+                    account,
+                    required,
+                    &bought,
+                    "basket",
+                    store,
+                    web_client,
+                    limits,
+                    envelopes,
+                    confirmation,
+                )
+                .await?
+            {
+                return Ok(None);
+            }
+        }
 
-                #[cfg(target_os = "macos")]
-                {
-                    let terminal_id = get_bundle_identifier_or_default("Terminal");
-                    set_application(&terminal_id)
-                        .map_err(|f| format!("{}", f))
-                        .unwrap();
+        for (order, quantity, price, limit) in planned {
+            let outcome = match self
+                .place_leg(order, quantity, price, limit, store, web_client)
+                .await
+            {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    log::error!(
+                        "Error placing the {} leg of job {}: {}",
+                        order.symbol,
+                        self.id,
+                        e
+                    );
+                    JobOutcome::Failed {
+                        error: e.to_string(),
+                    }
                 }
-                let _ = Notification::new()
-                    .summary("bourso-desktop")
-                    .body(&format!("{} {} were bought", quantity, order.args.symbol))
-                    .icon("firefox")
-                    .show()
-                    .unwrap();
-            }
-            _ => todo!(),
-        };
-        Ok(())
+            };
+            legs.push(LegOutcome {
+                symbol: order.symbol.clone(),
+                amount: order.amount.unwrap_or(quantity as f64 * price),
+                outcome,
+            });
+        }
+
+        Ok(Some(legs))
+    }
+
+    async fn place_leg(
+        &self,
+        order: &OrderArgs,
+        quantity: u64,
+        price: f64,
+        limit: Option<f64>,
+        store: &Store<Wry>,
+        web_client: &bourso_api::client::BoursoWebClient,
+    ) -> Result<JobOutcome> {
+        if let Some(limit) = limit {
+            let price = order.get_price(web_client).await?;
+            if beyond_limit(price, limit, order.side) {
+                return Ok(JobOutcome::BeyondLimit { price, limit });
+            }
+        }
+
+        let price = self
+            .send_order(order, quantity, price, limit, store, web_client)
+            .await?;
+        Ok(JobOutcome::Executed { quantity, price })
+    }
+}
+
+async fn check_market(
+    web_client: &bourso_api::client::BoursoWebClient,
+    symbol: &str,
+) -> Result<()> {
+    let market_open = match web_client.is_market_open(symbol).await {
+        Ok(market_open) => market_open,
+        Err(e) => {
+            log::error!("Error checking market status: {}", e);
+            return Err(anyhow::anyhow!("Error checking market status"));
+        }
+    };
+
+    if !market_open {
+        return Err(anyhow::anyhow!("Market is closed"));
     }
+
+    Ok(())
+}
+
+/// Result of `Job::place_order`
+enum Placement {
+    Placed { quantity: u64, price: f64 },
+    /// The amount cannot buy a single share
+    TooLow,
+    /// The order was not placed, the job outcome tells why
+    Skipped,
+}
+
+/// Quantity traded on each leg, for the notifications
+fn legs_summary(legs: &[LegOutcome]) -> String {
+    legs.iter()
+        .map(|leg| match &leg.outcome {
+            JobOutcome::Executed { quantity, .. } | JobOutcome::Planned { quantity, .. } => {
+                format!("{} {}", quantity, leg.symbol)
+            }
+            _ => format!("{} not traded", leg.symbol),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Round an amount of euros to the cent
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Round an amount of euros up to the next cent
+fn ceil_cents(amount: f64) -> f64 {
+    (amount * 100.0).ceil() / 100.0
+}
+
+/// Show a desktop notification, the Tauri notification plugin is not
+/// available when running from the CLI
+fn notify(body: &str) {
+    #[cfg(target_os = "macos")]
+    {
+        let terminal_id = get_bundle_identifier_or_default("Terminal");
+        set_application(&terminal_id)
+            .map_err(|f| format!("{}", f))
+            .unwrap();
+    }
+    let _ = Notification::new()
+        .summary("bourso-desktop")
+        .body(body)
+        .icon("firefox")
+        .show()
+        .unwrap();
 }
 
 pub fn load_jobs(app_local_data_dir: &Path) -> Result<Vec<Job>> {
@@ -260,9 +1354,14 @@ pub fn delete_job(app_local_data_dir: &Path, job_id: &str) -> Result<()> {
     Ok(())
 }
 
-/// Run a specific job from the UI
+/// Run a specific job from the UI, `confirmation` is given by the user for
+/// jobs above a soft limit
 #[tauri::command]
-pub async fn run_job_manually(app: AppHandle, job: Job) -> std::result::Result<(), String> {
+pub async fn run_job_manually(
+    app: AppHandle,
+    job: Job,
+    confirmation: Option<Confirmation>,
+) -> std::result::Result<Option<JobOutcome>, String> {
     let state = app.state::<Mutex<BoursoState>>();
     let st = state.lock().await;
     let web_client = &st.client;
@@ -272,17 +1371,28 @@ pub async fn run_job_manually(app: AppHandle, job: Job) -> std::result::Result<(
         .expect("Could not open jobs store");
 
     let app_local_data_dir = app.path().app_local_data_dir().unwrap();
+    let limits = load_limits(&app_local_data_dir).map_err(|e| e.to_string())?;
+    let envelopes = load_envelopes(&app_local_data_dir).map_err(|e| e.to_string())?;
 
     let mut job = job.clone();
 
-    match job.run(history_store, web_client).await {
+    match job
+        .run(
+            history_store,
+            web_client,
+            &limits,
+            &envelopes,
+            confirmation.as_ref(),
+        )
+        .await
+    {
         Ok(_) => {
             debug!("Job {} run successfully", job.id);
             // Update last run to now
             match save_job(&app_local_data_dir, job.clone()) {
                 Ok(_) => {
                     debug!("Jobs saved successfully");
-                    Ok(())
+                    Ok(job.last_outcome)
                 }
                 Err(e) => Err(e.to_string()),
             }
@@ -364,24 +1474,41 @@ pub async fn run(app: &mut App) -> Result<()> {
     web_client.init_session().await?;
     web_client.login(&client_id, &password).await?;
 
+    let limits = load_limits(&path)?;
+    let envelopes = load_envelopes(&path)?;
+
     for job in &mut jobs {
         if job.should_run_now() {
             let jobs_store = app.store(HISTORY_FILE_PATH)?;
-            match job.run(jobs_store, &web_client).await {
+            match job
+                .run(jobs_store, &web_client, &limits, &envelopes, None)
+                .await
+            {
                 Ok(_) => (),
                 Err(e) => {
                     log::error!("Error running job: {}", e);
+                    // Retried on the next scheduler runs
+                    job.last_outcome = Some(JobOutcome::Failed {
+                        error: e.to_string(),
+                    });
                 }
             }
         }
     }
 
+    let store = app.store(HISTORY_FILE_PATH)?;
+    if let Err(e) = snapshot_if_due(&web_client, &store, None).await {
+        log::error!("Error taking the net worth snapshot: {}", e);
+    }
+
     save_jobs(&path, jobs)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::order::qty_for_budget;
+    use crate::trigger::{Condition, Evaluation, TriggerAction};
     use chrono::{Duration, TimeZone};
 
     // Helper function to create a timestamp for a specific date
@@ -403,7 +1530,7 @@ mod tests {
             symbol: "BTC".to_string(),
             quantity: Some(1),
             amount: None,
-            side: "buy".to_string(),
+            side: OrderSide::Buy,
         }
     }
 
@@ -456,4 +1583,153 @@ mod tests {
         let second_of_month = make_datetime(2025, 2, 2);
         assert!(!job.should_run(second_of_month, last_month));
     }
+
+    #[test]
+    fn test_retry_after_scheduled_day() {
+        let mut job = Job::new(
+            Schedule::Monthly { day: 1 },
+            Command::Order(create_test_order()),
+        );
+        let last_month = make_datetime(2025, 1, 1);
+        // The 1st of February 2025 is a Saturday
+        let saturday = make_datetime(2025, 2, 1);
+        let monday = make_datetime(2025, 2, 3);
+        assert!(!job.should_run(monday, last_month));
+
+        job.last_outcome = Some(JobOutcome::Failed {
+            error: "Market is closed".to_string(),
+        });
+        assert!(job.should_run(saturday, last_month));
+        assert!(job.should_run(monday, last_month));
+        // Not before the scheduled day, nor once run
+        assert!(!job.should_run(make_datetime(2025, 1, 31), last_month));
+        assert!(!job.should_run(make_datetime(2025, 2, 4), monday));
+
+        assert_eq!(
+            Schedule::Monthly { day: 31 }.last_due(NaiveDate::from_ymd_opt(2025, 3, 2).unwrap()),
+            NaiveDate::from_ymd_opt(2025, 2, 28)
+        );
+    }
+
+    #[test]
+    fn test_price_moved() {
+        let mut job = Job::new(
+            Schedule::Monthly { day: 1 },
+            Command::Order(create_test_order()),
+        );
+        job.last_run = 0;
+        let price_move = PriceMove {
+            reference: 100.0,
+            change_percent: 12.0,
+        };
+
+        // Postponed, the job stays due until the price gets back
+        job.price_moved("BTC", 112.0, price_move.clone(), true);
+        assert_eq!(job.last_run, 0);
+        assert!(job.waiting());
+
+        // Skipped, the job waits for the next period
+        job.price_moved("BTC", 112.0, price_move, false);
+        assert_ne!(job.last_run, 0);
+        assert!(!job.waiting());
+    }
+
+    #[test]
+    fn test_trigger_cooldown_after_placement() {
+        let mut job = Job::new(Schedule::Daily, Command::Order(create_test_order()));
+        let mut trigger = Trigger {
+            condition: Condition::BelowAverage { days: 200 },
+            action: TriggerAction::RunWhenMet,
+            cooldown_days: 7,
+            last_fired: None,
+            evaluations: vec![],
+        };
+        let now = Local::now().timestamp();
+        trigger.record(Evaluation {
+            timestamp: now,
+            price: Some(90.0),
+            reference: Some(100.0),
+            fired: true,
+            reason: String::new(),
+        });
+        job.trigger = Some(trigger);
+
+        // The order failed or was postponed, the retry can fire again
+        job.settle_order(Placement::Skipped, None, "BTC").unwrap();
+        assert!(!job.trigger.as_ref().unwrap().in_cooldown(now));
+
+        job.settle_order(
+            Placement::Placed {
+                quantity: 1,
+                price: 90.0,
+            },
+            None,
+            "BTC",
+        )
+        .unwrap();
+        assert!(job.trigger.as_ref().unwrap().in_cooldown(now));
+    }
+
+    #[test]
+    fn test_carry_over_job() {
+        let mut job = Job::new(
+            Schedule::Daily,
+            Command::Order(OrderArgs {
+                quantity: None,
+                amount: Some(50.0),
+                ..create_test_order()
+            }),
+        );
+        job.carry_over = true;
+        // Budget of the run, as computed by `Job::run`
+        let budget = |job: &Job| Some(50.0 + job.leftover);
+
+        // 50€ cannot buy a 60€ share
+        job.settle_order(Placement::TooLow, budget(&job), "BTC")
+            .unwrap();
+        assert_eq!(job.leftover, 50.0);
+
+        let placed = Placement::Placed {
+            quantity: 1,
+            price: 60.0,
+        };
+        job.settle_order(placed, budget(&job), "BTC").unwrap();
+        assert_eq!(job.leftover, 40.0);
+
+        // Skipped for the cash, the amount of the period is kept
+        job.last_outcome = Some(JobOutcome::InsufficientFunds {
+            required: 60.0,
+            available: 0.0,
+        });
+        job.settle_order(Placement::Skipped, budget(&job), "BTC")
+            .unwrap();
+        assert_eq!(job.leftover, 90.0);
+
+        // Postponed, the retry runs with the same budget
+        job.last_outcome = Some(JobOutcome::PriceMoved {
+            price: 70.0,
+            reference: 60.0,
+            change_percent: 16.7,
+            postponed: true,
+        });
+        job.settle_order(Placement::Skipped, budget(&job), "BTC")
+            .unwrap();
+        assert_eq!(job.leftover, 90.0);
+    }
+
+    #[test]
+    fn test_carry_over_budget() {
+        // 50€ per period on a 60€ share
+        let (amount, price) = (50.0, 60.0);
+
+        // First period: nothing can be bought, everything is carried over
+        let budget = amount;
+        assert_eq!(qty_for_budget(budget, price), 0);
+
+        // Second period: the carried over cash allows buying one share
+        let budget = amount + budget;
+        let qty = qty_for_budget(budget, price);
+        assert_eq!(qty, 1);
+        assert_eq!(round_cents(budget - qty as f64 * price), 40.0);
+    }
 }
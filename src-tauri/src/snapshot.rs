@@ -0,0 +1,302 @@
+use anyhow::Result;
+use bourso_api::{
+    account::{Account, AccountKind},
+    client::BoursoWebClient,
+};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{command, AppHandle, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::scheduler::HISTORY_FILE_PATH;
+use crate::trading::{cash_from_summary, get_summary, positions_from_summary};
+
+/// Position of a trading account valued on the day of the snapshot
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PositionValue {
+    pub symbol: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountSnapshot {
+    pub id: String,
+    pub name: String,
+    pub kind: AccountKind,
+    /// Balance in euros, the loans are counted as negative
+    pub value: f64,
+    /// Cash available on a trading account
+    #[serde(default)]
+    pub cash: Option<f64>,
+    #[serde(default)]
+    pub positions: Vec<PositionValue>,
+}
+
+/// Balances of all the accounts on a day
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Snapshot {
+    pub date: NaiveDate,
+    pub timestamp: i64,
+    pub accounts: Vec<AccountSnapshot>,
+}
+
+pub fn load_snapshots(store: &Store<Wry>) -> Result<Vec<Snapshot>> {
+    let snapshots = store.get("snapshots").unwrap_or(serde_json::json!([]));
+    Ok(serde_json::from_value(snapshots)?)
+}
+
+fn save_snapshots(store: &Store<Wry>, snapshots: &[Snapshot]) -> Result<()> {
+    store.set("snapshots", serde_json::json!(snapshots));
+    store.save()?;
+
+    Ok(())
+}
+
+/// Whether no snapshot was taken today
+pub fn snapshot_due(store: &Store<Wry>) -> Result<bool> {
+    let today = Local::now().date_naive();
+    Ok(!load_snapshots(store)?.iter().any(|s| s.date == today))
+}
+
+/// Take a snapshot of the accounts unless one was already taken today.
+/// `accounts` avoids fetching them again when the caller already has them.
+pub async fn snapshot_if_due(
+    web_client: &BoursoWebClient,
+    store: &Store<Wry>,
+    accounts: Option<Vec<Account>>,
+) -> Result<bool> {
+    if !snapshot_due(store)? {
+        return Ok(false);
+    }
+    let mut snapshots = load_snapshots(store)?;
+
+    let accounts = match accounts {
+        Some(accounts) => accounts,
+        None => web_client.get_accounts(None).await?,
+    };
+    snapshots.push(take_snapshot(web_client, accounts).await?);
+    save_snapshots(store, &snapshots)?;
+
+    Ok(true)
+}
+
+async fn take_snapshot(web_client: &BoursoWebClient, accounts: Vec<Account>) -> Result<Snapshot> {
+    let now = Local::now();
+    let mut snapshots = vec![];
+
+    for account in accounts {
+        let balance = account.balance as f64 / 100.0;
+        let mut snapshot = AccountSnapshot {
+            id: account.id.clone(),
+            name: account.name.clone(),
+            value: match account.kind {
+                AccountKind::Loans => -balance.abs(),
+                _ => balance,
+            },
+            kind: account.kind,
+            cash: None,
+            positions: vec![],
+        };
+
+        // The balance of a trading account is its valuation, the positions
+        // are kept to follow them over time
+        if matches!(snapshot.kind, AccountKind::Trading) {
+            match get_summary(web_client, &account.id).await {
+                Ok(summary) => {
+                    snapshot.cash = cash_from_summary(&summary);
+                    let positions = positions_from_summary(&summary).unwrap_or_else(|e| {
+                        log::warn!("Error while reading positions of {}: {:?}", account.id, e);
+                        vec![]
+                    });
+                    snapshot.positions = positions
+                        .into_iter()
+                        .map(|p| PositionValue {
+                            value: p.quantity * p.last,
+                            symbol: p.symbol,
+                            quantity: p.quantity,
+                            price: p.last,
+                        })
+                        .collect();
+                }
+                Err(e) => log::warn!("Error while getting summary of {}: {:?}", account.id, e),
+            }
+        }
+
+        snapshots.push(snapshot);
+    }
+
+    Ok(Snapshot {
+        date: now.date_naive(),
+        timestamp: now.timestamp(),
+        accounts: snapshots,
+    })
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Point {
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Series {
+    pub id: String,
+    pub label: String,
+    pub points: Vec<Point>,
+}
+
+/// Net worth over time, per account, per kind of account and in total
+#[derive(Debug, Serialize)]
+pub struct NetWorth {
+    pub accounts: Vec<Series>,
+    pub kinds: Vec<Series>,
+    pub total: Vec<Point>,
+}
+
+#[command]
+pub fn get_net_worth(
+    app: AppHandle,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> core::result::Result<NetWorth, String> {
+    let store = app.store(HISTORY_FILE_PATH).map_err(|e| e.to_string())?;
+    let snapshots = load_snapshots(&store).map_err(|e| e.to_string())?;
+
+    Ok(net_worth(&snapshots, from, to))
+}
+
+pub fn net_worth(
+    snapshots: &[Snapshot],
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> NetWorth {
+    let mut snapshots: Vec<&Snapshot> = snapshots
+        .iter()
+        .filter(|s| from.is_none_or(|from| s.date >= from) && to.is_none_or(|to| s.date <= to))
+        .collect();
+    snapshots.sort_by_key(|s| s.date);
+
+    let mut accounts: BTreeMap<String, Series> = BTreeMap::new();
+    let mut kinds: BTreeMap<String, Series> = BTreeMap::new();
+    let mut total = vec![];
+
+    for snapshot in snapshots {
+        let mut by_kind: BTreeMap<String, f64> = BTreeMap::new();
+
+        for account in &snapshot.accounts {
+            accounts
+                .entry(account.id.clone())
+                .or_insert_with(|| Series {
+                    id: account.id.clone(),
+                    label: account.name.clone(),
+                    points: vec![],
+                })
+                .points
+                .push(Point {
+                    date: snapshot.date,
+                    value: account.value,
+                });
+            *by_kind.entry(format!("{:?}", account.kind)).or_default() += account.value;
+        }
+
+        for (kind, value) in by_kind {
+            kinds
+                .entry(kind.clone())
+                .or_insert_with(|| Series {
+                    id: kind.clone(),
+                    label: kind,
+                    points: vec![],
+                })
+                .points
+                .push(Point {
+                    date: snapshot.date,
+                    value,
+                });
+        }
+
+        total.push(Point {
+            date: snapshot.date,
+            value: snapshot.accounts.iter().map(|a| a.value).sum(),
+        });
+    }
+
+    NetWorth {
+        accounts: accounts.into_values().collect(),
+        kinds: kinds.into_values().collect(),
+        total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: &str, kind: AccountKind, value: f64) -> AccountSnapshot {
+        AccountSnapshot {
+            id: id.to_string(),
+            name: id.to_string(),
+            kind,
+            value,
+            cash: None,
+            positions: vec![],
+        }
+    }
+
+    fn snapshot(day: u32, accounts: Vec<AccountSnapshot>) -> Snapshot {
+        Snapshot {
+            date: NaiveDate::from_ymd_opt(2024, 5, day).unwrap(),
+            timestamp: 0,
+            accounts,
+        }
+    }
+
+    #[test]
+    fn test_net_worth() {
+        let snapshots = vec![
+            snapshot(
+                2,
+                vec![
+                    account("checking", AccountKind::Banking, 1_000.0),
+                    account("livret", AccountKind::Savings, 5_000.0),
+                    account("pea", AccountKind::Trading, 10_000.0),
+                ],
+            ),
+            snapshot(
+                1,
+                vec![
+                    account("checking", AccountKind::Banking, 2_000.0),
+                    account("pea", AccountKind::Trading, 9_000.0),
+                    account("loan", AccountKind::Loans, -3_000.0),
+                ],
+            ),
+        ];
+
+        let series = net_worth(&snapshots, None, None);
+
+        assert_eq!(
+            series.total,
+            vec![
+                Point {
+                    date: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+                    value: 8_000.0
+                },
+                Point {
+                    date: NaiveDate::from_ymd_opt(2024, 5, 2).unwrap(),
+                    value: 16_000.0
+                },
+            ]
+        );
+        assert_eq!(series.accounts.len(), 4);
+        // The accounts missing from a snapshot have no point on that day
+        let livret = series.accounts.iter().find(|s| s.id == "livret").unwrap();
+        assert_eq!(livret.points.len(), 1);
+        let trading = series.kinds.iter().find(|s| s.id == "Trading").unwrap();
+        assert_eq!(trading.points[1].value, 10_000.0);
+
+        let filtered = net_worth(&snapshots, NaiveDate::from_ymd_opt(2024, 5, 2), None);
+        assert_eq!(filtered.total.len(), 1);
+    }
+}
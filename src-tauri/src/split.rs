@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::order::{OrderArgs, OrderSide};
+
+/// Large order spread over several slices to limit the market impact and
+/// average the execution price
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SplitOrder {
+    pub account: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    /// Total amount to invest over all the slices, in euros
+    pub total_amount: f64,
+    pub slices: u32,
+    /// Minutes between two slices within a session, one slice is placed per
+    /// scheduled session when unset
+    #[serde(default)]
+    pub interval_minutes: Option<u32>,
+    /// Amount already executed, in euros
+    #[serde(default)]
+    pub executed_amount: f64,
+    #[serde(default)]
+    pub executed_quantity: u64,
+    #[serde(default)]
+    pub executed_slices: u32,
+}
+
+impl SplitOrder {
+    pub fn remaining_amount(&self) -> f64 {
+        (self.total_amount - self.executed_amount).max(0.0)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.executed_slices >= self.slices || self.remaining_amount() <= 0.0
+    }
+
+    /// Order for the next slice, the remaining amount is spread evenly over
+    /// the remaining slices so that slices too small to buy a share are
+    /// caught up by the next ones
+    pub fn next_slice(&self) -> OrderArgs {
+        let remaining_slices = self.slices.saturating_sub(self.executed_slices).max(1);
+
+        OrderArgs {
+            account: self.account.clone(),
+            symbol: self.symbol.clone(),
+            quantity: None,
+            amount: Some(self.remaining_amount() / remaining_slices as f64),
+            side: self.side,
+        }
+    }
+
+    pub fn record_slice(&mut self, quantity: u64, price: f64) {
+        self.executed_quantity += quantity;
+        self.executed_amount += quantity as f64 * price;
+        self.executed_slices += 1;
+    }
+
+    /// Whether the next slice of an intraday split is due
+    pub fn interval_elapsed(&self, now: DateTime<Utc>, last_run: DateTime<Utc>) -> Option<bool> {
+        self.interval_minutes
+            .map(|minutes| now - last_run >= chrono::Duration::minutes(minutes as i64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(total_amount: f64, slices: u32) -> SplitOrder {
+        SplitOrder {
+            account: "1234567890".to_string(),
+            symbol: "1rTCW8".to_string(),
+            side: OrderSide::Buy,
+            total_amount,
+            slices,
+            interval_minutes: None,
+            executed_amount: 0.0,
+            executed_quantity: 0,
+            executed_slices: 0,
+        }
+    }
+
+    #[test]
+    fn test_next_slice() {
+        let mut order = split(1000.0, 4);
+        assert_eq!(order.next_slice().amount, Some(250.0));
+
+        // 3 shares at 80€, the unspent 10€ go to the next slices
+        order.record_slice(3, 80.0);
+        assert_eq!(order.remaining_amount(), 760.0);
+        assert_eq!(order.next_slice().amount, Some(760.0 / 3.0));
+
+        // Too low to buy a share
+        order.record_slice(0, 0.0);
+        assert_eq!(order.next_slice().amount, Some(380.0));
+        assert!(!order.is_complete());
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut order = split(500.0, 2);
+        order.record_slice(2, 100.0);
+        assert!(!order.is_complete());
+        order.record_slice(3, 98.0);
+        assert!(order.is_complete());
+        assert_eq!(order.executed_quantity, 5);
+
+        // Target reached before the last slice
+        let mut order = split(200.0, 4);
+        order.record_slice(2, 100.0);
+        assert!(order.is_complete());
+    }
+}
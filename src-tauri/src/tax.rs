@@ -0,0 +1,454 @@
+use anyhow::Result;
+use bourso_api::account::{Account, AccountKind};
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
+    path::Path,
+};
+use tauri::{async_runtime::Mutex, command, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::envelope::load_envelopes;
+use crate::events::{load_events, AccountEvent, EventKind};
+use crate::export::{csv_field, xml_escape};
+use crate::history::order_date;
+use crate::order::{load_orders, OrderPassed};
+use crate::portfolio::{compute_positions, CostMethod};
+use crate::scheduler::HISTORY_FILE_PATH;
+use crate::BoursoState;
+
+const PEA_ACCOUNTS_FILE_PATH: &str = "pea_accounts.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaxReportFormat {
+    Csv,
+    /// Printable summary, to be saved as PDF from the browser
+    Html,
+}
+
+/// Disposals of a security during the tax year
+#[derive(Debug, Serialize, Default, Clone, PartialEq)]
+pub struct TaxLine {
+    pub symbol: String,
+    pub disposals: usize,
+    pub quantity: u64,
+    /// Gross amount of the sales, "prix de cession"
+    pub proceeds: f64,
+    /// Fees of the sales, "frais de cession"
+    pub fees: f64,
+    /// PRU of the shares sold, acquisition fees included
+    pub acquisition_cost: f64,
+    pub net_gain: f64,
+}
+
+impl TaxLine {
+    fn add(&mut self, other: &TaxLine) {
+        self.disposals += other.disposals;
+        self.quantity += other.quantity;
+        self.proceeds += other.proceeds;
+        self.fees += other.fees;
+        self.acquisition_cost += other.acquisition_cost;
+        self.net_gain += other.net_gain;
+    }
+}
+
+#[derive(Debug, Serialize, Default, PartialEq)]
+pub struct TaxSection {
+    pub lines: Vec<TaxLine>,
+    pub total: TaxLine,
+}
+
+/// Realized gains of a tax year, to fill the form 2074
+#[derive(Debug, Serialize)]
+pub struct TaxReport {
+    pub year: i32,
+    /// Securities accounts (CTO), to declare
+    pub taxable: TaxSection,
+    /// PEA accounts, exempt while the plan is open, for information
+    pub pea: TaxSection,
+    pub warnings: Vec<String>,
+    /// Accounts of the history, to tag the PEA accounts
+    pub accounts: Vec<String>,
+    pub pea_accounts: Vec<String>,
+}
+
+/// Bourso names its PEA accounts "PEA" or "PEA-PME"
+pub fn is_pea(account: &Account) -> bool {
+    account.name.to_uppercase().contains("PEA")
+}
+
+/// PEA accounts tagged by the user, `None` until the tags are saved
+pub fn load_pea_accounts(app_local_data_dir: &Path) -> Result<Option<Vec<String>>> {
+    let pea_accounts_path = app_local_data_dir.join(PEA_ACCOUNTS_FILE_PATH);
+
+    if !fs::exists(&pea_accounts_path)? {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(pea_accounts_path)?;
+
+    if content.trim().is_empty() {
+        return Ok(Some(vec![]));
+    }
+
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+pub fn save_pea_accounts(app_local_data_dir: &Path, accounts: &[String]) -> Result<()> {
+    let pea_accounts_path = app_local_data_dir.join(PEA_ACCOUNTS_FILE_PATH);
+
+    fs::write(pea_accounts_path, serde_json::to_string(accounts)?)?;
+
+    Ok(())
+}
+
+/// The PEA accounts are tagged by the user, so that a closed or renamed PEA
+/// is still reported as one. The first time, the accounts with a PEA
+/// envelope and the live accounts named as a PEA are tagged.
+async fn pea_accounts(
+    app: &AppHandle,
+    state: &State<'_, Mutex<BoursoState>>,
+) -> Result<Vec<String>> {
+    let app_local_data_dir = app.path().app_local_data_dir()?;
+    if let Some(accounts) = load_pea_accounts(&app_local_data_dir)? {
+        return Ok(accounts);
+    }
+
+    let mut accounts: Vec<String> = load_envelopes(&app_local_data_dir)?
+        .into_iter()
+        .filter(|envelope| envelope.kind.is_pea())
+        .map(|envelope| envelope.account)
+        .collect();
+    let state = state.lock().await;
+    accounts.extend(
+        state
+            .client
+            .get_accounts(Some(AccountKind::Trading))
+            .await?
+            .into_iter()
+            .filter(is_pea)
+            .map(|account| account.id),
+    );
+    accounts.sort();
+    accounts.dedup();
+    save_pea_accounts(&app_local_data_dir, &accounts)?;
+
+    Ok(accounts)
+}
+
+fn history(app: &AppHandle) -> Result<(Vec<OrderPassed>, Vec<AccountEvent>)> {
+    let store = app.store(HISTORY_FILE_PATH)?;
+    Ok((load_orders(&store)?, load_events(&store)?))
+}
+
+#[command]
+pub async fn get_tax_report(
+    app: AppHandle,
+    state: State<'_, Mutex<BoursoState>>,
+    year: i32,
+) -> core::result::Result<TaxReport, String> {
+    let (orders, events) = history(&app).map_err(|e| e.to_string())?;
+    let pea = pea_accounts(&app, &state)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(tax_report(&orders, &events, year, &pea))
+}
+
+#[command]
+pub fn set_pea_accounts(app: AppHandle, accounts: Vec<String>) -> core::result::Result<(), String> {
+    let app_local_data_dir = app.path().app_local_data_dir().unwrap();
+
+    save_pea_accounts(&app_local_data_dir, &accounts).map_err(|e| e.to_string())
+}
+
+/// Write the report of `year` to `path`
+#[command]
+pub async fn export_tax_report(
+    app: AppHandle,
+    state: State<'_, Mutex<BoursoState>>,
+    year: i32,
+    path: String,
+    format: TaxReportFormat,
+) -> core::result::Result<TaxReport, String> {
+    let report = get_tax_report(app, state, year).await?;
+
+    let content = match format {
+        TaxReportFormat::Csv => to_csv(&report),
+        TaxReportFormat::Html => to_html(&report),
+    };
+    fs::write(Path::new(&path), content).map_err(|e| e.to_string())?;
+
+    Ok(report)
+}
+
+/// The PRU of a security is computed over all the securities accounts of the
+/// household, the PEA accounts are computed separately. The whole history is
+/// replayed so that the shares bought the previous years are accounted.
+pub fn tax_report(
+    orders: &[OrderPassed],
+    events: &[AccountEvent],
+    year: i32,
+    pea_accounts: &[String],
+) -> TaxReport {
+    let accounts = orders
+        .iter()
+        .map(|o| o.args.account.clone())
+        .chain(events.iter().map(|e| e.account.clone()))
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect();
+
+    let (pea, taxable): (Vec<OrderPassed>, Vec<OrderPassed>) = orders
+        .iter()
+        .cloned()
+        .partition(|o| pea_accounts.contains(&o.args.account));
+    let taxable: Vec<OrderPassed> = taxable
+        .into_iter()
+        .map(|mut order| {
+            order.args.account = String::new();
+            order
+        })
+        .collect();
+
+    // A split recorded on several securities accounts applies once to the
+    // merged position
+    let (pea_events, taxable_events): (Vec<AccountEvent>, Vec<AccountEvent>) = events
+        .iter()
+        .cloned()
+        .partition(|e| pea_accounts.contains(&e.account));
+    let mut splits = HashSet::new();
+    let taxable_events: Vec<AccountEvent> = taxable_events
+        .into_iter()
+        .filter(|e| match e.kind {
+            EventKind::Split { from, to } => {
+                splits.insert((e.symbol.clone(), order_date(e.timestamp), from, to))
+            }
+            _ => true,
+        })
+        .map(|mut event| {
+            event.account = String::new();
+            event
+        })
+        .collect();
+
+    let mut warnings = vec![];
+    let taxable = section(&taxable, &taxable_events, year, &mut warnings);
+    let pea = section(&pea, &pea_events, year, &mut warnings);
+
+    TaxReport {
+        year,
+        taxable,
+        pea,
+        warnings,
+        accounts,
+        pea_accounts: pea_accounts.to_vec(),
+    }
+}
+
+fn section(
+    orders: &[OrderPassed],
+    events: &[AccountEvent],
+    year: i32,
+    warnings: &mut Vec<String>,
+) -> TaxSection {
+    let mut lines: BTreeMap<String, TaxLine> = BTreeMap::new();
+
+    for position in compute_positions(orders, events, CostMethod::Pru) {
+        let disposals: Vec<_> = position
+            .disposals
+            .iter()
+            .filter(|d| {
+                d.timestamp
+                    .and_then(order_date)
+                    .is_some_and(|date| date.year() == year)
+            })
+            .collect();
+        if disposals.is_empty() {
+            continue;
+        }
+        warnings.extend(position.warnings.iter().cloned());
+
+        let line = lines
+            .entry(position.symbol.clone())
+            .or_insert_with(|| TaxLine {
+                symbol: position.symbol.clone(),
+                ..Default::default()
+            });
+        for disposal in disposals {
+            line.add(&TaxLine {
+                symbol: String::new(),
+                disposals: 1,
+                quantity: disposal.quantity,
+                proceeds: disposal.proceeds,
+                fees: disposal.fees,
+                acquisition_cost: disposal.acquisition_cost,
+                net_gain: disposal.gain,
+            });
+        }
+    }
+
+    let lines: Vec<TaxLine> = lines.into_values().collect();
+    let mut total = TaxLine {
+        symbol: "Total".to_string(),
+        ..Default::default()
+    };
+    for line in &lines {
+        total.add(line);
+    }
+
+    TaxSection { lines, total }
+}
+
+fn to_csv(report: &TaxReport) -> String {
+    let mut csv = String::from(
+        "section,symbol,disposals,quantity,proceeds,fees,net_proceeds,acquisition_cost,net_gain\n",
+    );
+
+    for (name, section) in [("cto", &report.taxable), ("pea", &report.pea)] {
+        for line in section.lines.iter().chain([&section.total]) {
+            csv.push_str(&format!(
+                "{},{},{},{},{:.2},{:.2},{:.2},{:.2},{:.2}\n",
+                name,
+                csv_field(&line.symbol),
+                line.disposals,
+                line.quantity,
+                line.proceeds,
+                line.fees,
+                line.proceeds - line.fees,
+                line.acquisition_cost,
+                line.net_gain
+            ));
+        }
+    }
+
+    csv
+}
+
+fn html_table(section: &TaxSection) -> String {
+    if section.lines.is_empty() {
+        return "<p>Aucune cession.</p>\n".to_string();
+    }
+
+    let mut html = String::from(
+        "<table>\n<tr><th>Valeur</th><th>Cessions</th><th>Quantité</th>\
+         <th>Prix de cession</th><th>Frais de cession</th><th>Prix de cession net</th>\
+         <th>Prix de revient</th><th>Plus ou moins-value</th></tr>\n",
+    );
+    for line in section.lines.iter().chain([&section.total]) {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td><td>{:.2}</td>\
+             <td>{:.2}</td><td>{:.2}</td><td>{:.2}</td></tr>\n",
+            xml_escape(&line.symbol),
+            line.disposals,
+            line.quantity,
+            line.proceeds,
+            line.fees,
+            line.proceeds - line.fees,
+            line.acquisition_cost,
+            line.net_gain
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html
+}
+
+fn to_html(report: &TaxReport) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"fr\">\n<head><meta charset=\"utf-8\">\
+         <title>Plus-values {year}</title>\n<style>\
+         body {{ font-family: sans-serif; }} table {{ border-collapse: collapse; }} \
+         th, td {{ border: 1px solid #999; padding: 4px 8px; text-align: right; }} \
+         tr:last-child {{ font-weight: bold; }}\
+         </style></head>\n<body>\n<h1>Plus-values de cession {year}</h1>\n\
+         <p>Prix de revient calculé au PRU, frais d'acquisition inclus. \
+         Montants en euros, à arrondir à l'euro sur le formulaire 2074.</p>\n\
+         <h2>Compte-titres</h2>\n",
+        year = report.year
+    );
+    html.push_str(&html_table(&report.taxable));
+    html.push_str("<h2>PEA (pour information)</h2>\n");
+    html.push_str(&html_table(&report.pea));
+
+    if !report.warnings.is_empty() {
+        html.push_str("<h2>Avertissements</h2>\n<ul>\n");
+        for warning in &report.warnings {
+            html.push_str(&format!("<li>{}</li>\n", xml_escape(warning)));
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{OrderArgs, OrderSide, OrderSource};
+    use chrono::{Local, TimeZone};
+
+    fn order(
+        account: &str,
+        date: (i32, u32, u32),
+        side: OrderSide,
+        quantity: u64,
+        price: f64,
+    ) -> OrderPassed {
+        let timestamp = Local
+            .with_ymd_and_hms(date.0, date.1, date.2, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        let mut order = OrderPassed::new(
+            format!("{}-{}", account, timestamp),
+            price,
+            Some(timestamp),
+            OrderArgs {
+                account: account.to_string(),
+                symbol: "FR0010315770".to_string(),
+                quantity: Some(quantity),
+                amount: None,
+                side,
+            },
+            OrderSource::App,
+        );
+        order.fees = 1.0;
+        order
+    }
+
+    #[test]
+    fn test_tax_report() {
+        let orders = vec![
+            // The PRU is shared by the two securities accounts
+            order("cto1", (2023, 3, 1), OrderSide::Buy, 10, 10.0),
+            order("cto2", (2023, 6, 1), OrderSide::Buy, 10, 20.0),
+            order("cto1", (2023, 9, 1), OrderSide::Sell, 5, 18.0),
+            order("cto2", (2024, 2, 1), OrderSide::Sell, 10, 25.0),
+            order("pea", (2024, 1, 1), OrderSide::Buy, 4, 10.0),
+            order("pea", (2024, 5, 1), OrderSide::Sell, 4, 12.0),
+        ];
+
+        let report = tax_report(&orders, &[], 2024, &["pea".to_string()]);
+
+        // PRU of (101 + 201) / 20 = 15.10
+        let line = &report.taxable.lines[0];
+        assert_eq!(line.disposals, 1);
+        assert_eq!(line.quantity, 10);
+        assert_eq!(line.proceeds, 250.0);
+        assert_eq!(line.fees, 1.0);
+        assert!((line.acquisition_cost - 151.0).abs() < 1e-9);
+        assert!((line.net_gain - 98.0).abs() < 1e-9);
+        assert_eq!(report.taxable.total.net_gain, line.net_gain);
+
+        assert_eq!(report.pea.lines.len(), 1);
+        assert!((report.pea.total.net_gain - 6.0).abs() < 1e-9);
+        assert_eq!(report.accounts, ["cto1", "cto2", "pea"]);
+
+        let csv = to_csv(&report);
+        assert!(csv.contains("cto,FR0010315770,1,10,250.00,1.00,249.00,151.00,98.00"));
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use bourso_api::client::{trade::tick::GetTicksEOD, BoursoWebClient};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// End of day close of an instrument
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DailyClose {
+    pub date: NaiveDate,
+    pub close: f64,
+}
+
+/// Get the last `length` end of day closes of a symbol, oldest first
+pub async fn get_daily_closes(
+    web_client: &BoursoWebClient,
+    symbol: &str,
+    length: u16,
+) -> Result<Vec<DailyClose>> {
+    let ticks = match web_client.get_ticks(symbol, length.into(), 0).await {
+        Ok(ticks) => ticks,
+        Err(e) => {
+            return Err(anyhow::anyhow!(
+                "Error while getting ticks for {}: {:?}",
+                symbol,
+                e
+            ))
+        }
+    };
+
+    parse_daily_closes(&ticks)
+}
+
+/// Extract the closes from the ticks, in the quote tab each tick date `d` is
+/// the number of days since the Unix epoch and `c` is the close price
+fn parse_daily_closes(ticks: &GetTicksEOD) -> Result<Vec<DailyClose>> {
+    let ticks = serde_json::to_value(ticks)?;
+    let quotes = ticks["d"]["QuoteTab"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("No quotes found in ticks"))?;
+
+    let mut closes: Vec<DailyClose> = quotes
+        .iter()
+        .filter_map(|quote| {
+            let days = quote["d"].as_u64()?;
+            let close = quote["c"].as_f64()?;
+            let date = NaiveDate::from_ymd_opt(1970, 1, 1)?.checked_add_days(Days::new(days))?;
+            Some(DailyClose { date, close })
+        })
+        .collect();
+    closes.sort_by_key(|c| c.date);
+
+    Ok(closes)
+}
+
+/// Last close strictly before `date`
+pub fn previous_close(closes: &[DailyClose], date: NaiveDate) -> Option<f64> {
+    closes.iter().rev().find(|c| c.date < date).map(|c| c.close)
+}
+
+/// Simple moving average of the last `days` closes strictly before `date`
+pub fn trailing_average(closes: &[DailyClose], date: NaiveDate, days: usize) -> Option<f64> {
+    let window: Vec<f64> = closes
+        .iter()
+        .rev()
+        .filter(|c| c.date < date)
+        .take(days)
+        .map(|c| c.close)
+        .collect();
+
+    if days == 0 || window.len() < days {
+        return None;
+    }
+
+    Some(window.iter().sum::<f64>() / window.len() as f64)
+}
@@ -1,4 +1,10 @@
-use bourso_api::{account::Account, client::trade::TradingSummaryItem};
+use anyhow::Result;
+use bourso_api::{
+    account::Account,
+    client::{trade::TradingSummaryItem, BoursoWebClient},
+};
+use chrono::NaiveDate;
+use serde_json::Value;
 use tauri::{async_runtime::Mutex, command, State};
 
 use crate::BoursoState;
@@ -22,3 +28,181 @@ pub async fn get_trading_summary(
         Err(e) => Err(format!("Error while getting trading summary: {:?}", e)),
     }
 }
+
+/// Get the cash available on a trading account, in euros
+///
+/// The balance returned by `get_accounts` for a trading account is its
+/// total valuation, the cash is only exposed in the trading summary.
+pub async fn get_cash_balance(web_client: &BoursoWebClient, account_id: &str) -> Result<f64> {
+    let summary = get_summary(web_client, account_id).await?;
+
+    cash_from_summary(&summary)
+        .ok_or_else(|| anyhow::anyhow!("Cash balance not found for account {}", account_id))
+}
+
+pub fn cash_from_summary(summary: &[TradingSummaryItem]) -> Option<f64> {
+    cash_from_items(&summary_items(summary))
+}
+
+/// Sections of the trading summary as JSON, e.g. `account` or `positions`
+fn summary_items(summary: &[TradingSummaryItem]) -> Vec<Value> {
+    summary
+        .iter()
+        .filter_map(|item| serde_json::to_value(item).ok())
+        .collect()
+}
+
+fn cash_from_items(items: &[Value]) -> Option<f64> {
+    items
+        .iter()
+        .find(|item| item["id"] == "account")
+        .and_then(|item| item["account"]["cash"]["value"].as_f64())
+}
+
+/// Position held on Bourso, as reported by the trading summary
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummaryPosition {
+    pub symbol: String,
+    pub label: String,
+    pub quantity: f64,
+    /// Average buying price computed by Bourso
+    pub buying_price: f64,
+    pub last: f64,
+    pub last_movement_date: Option<NaiveDate>,
+}
+
+/// Get the positions held on a trading account
+pub async fn get_summary_positions(
+    web_client: &BoursoWebClient,
+    account_id: &str,
+) -> Result<Vec<SummaryPosition>> {
+    let summary = get_summary(web_client, account_id).await?;
+
+    positions_from_summary(&summary)
+}
+
+pub async fn get_summary(
+    web_client: &BoursoWebClient,
+    account_id: &str,
+) -> Result<Vec<TradingSummaryItem>> {
+    web_client
+        .get_trading_summary(Account {
+            id: account_id.to_string(),
+            ..Account::default()
+        })
+        .await
+}
+
+/// Positions of the trading summary. A position that cannot be read fails
+/// the whole summary rather than being left out of it.
+pub fn positions_from_summary(summary: &[TradingSummaryItem]) -> Result<Vec<SummaryPosition>> {
+    positions_from_items(&summary_items(summary))
+}
+
+fn positions_from_items(items: &[Value]) -> Result<Vec<SummaryPosition>> {
+    let Some(positions) = items.iter().find(|item| item["id"] == "positions") else {
+        return Ok(vec![]);
+    };
+    let positions = positions["positions"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("Positions of the trading summary not found"))?;
+
+    positions
+        .iter()
+        .enumerate()
+        .map(|(i, position)| {
+            let symbol = position["symbol"].as_str().ok_or_else(|| {
+                anyhow::anyhow!("Position {} of the trading summary has no symbol", i)
+            })?;
+            let quantity = position["quantity"]["value"].as_f64().ok_or_else(|| {
+                anyhow::anyhow!("Position {} of the trading summary has no quantity", symbol)
+            })?;
+
+            Ok(SummaryPosition {
+                symbol: symbol.to_string(),
+                label: position["label"].as_str().unwrap_or_default().to_string(),
+                quantity,
+                buying_price: position["buyingPrice"]["value"]
+                    .as_f64()
+                    .unwrap_or_default(),
+                last: position["last"]["value"].as_f64().unwrap_or_default(),
+                last_movement_date: position["lastMovementDate"]
+                    .as_str()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trading summary of a PEA, trimmed to the fields read by the app
+    const SUMMARY: &str = r#"[
+        {
+            "id": "account",
+            "account": {
+                "cash": { "value": 152.37, "currency": "EUR" },
+                "valuation": { "value": 5348.12, "currency": "EUR" }
+            }
+        },
+        {
+            "id": "positions",
+            "positions": [
+                {
+                    "symbol": "1rTCW8",
+                    "label": "AMUNDI MSCI WORLD UCITS ETF",
+                    "quantity": { "value": 9 },
+                    "buyingPrice": { "value": 480.1234 },
+                    "last": { "value": 545.2 },
+                    "lastMovementDate": "2024-05-02"
+                },
+                {
+                    "symbol": "1rPPAEEM",
+                    "label": "AMUNDI MSCI EMERGING MARKETS",
+                    "quantity": { "value": 40.0 },
+                    "buyingPrice": { "value": 5.01 },
+                    "last": { "value": 5.4 }
+                }
+            ]
+        }
+    ]"#;
+
+    fn items(json: &str) -> Vec<Value> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_summary() {
+        let items = items(SUMMARY);
+
+        assert_eq!(cash_from_items(&items), Some(152.37));
+
+        let positions = positions_from_items(&items).unwrap();
+        assert_eq!(positions.len(), 2);
+        assert_eq!(
+            positions[0],
+            SummaryPosition {
+                symbol: "1rTCW8".to_string(),
+                label: "AMUNDI MSCI WORLD UCITS ETF".to_string(),
+                quantity: 9.0,
+                buying_price: 480.1234,
+                last: 545.2,
+                last_movement_date: NaiveDate::from_ymd_opt(2024, 5, 2),
+            }
+        );
+        assert_eq!(positions[1].last_movement_date, None);
+    }
+
+    #[test]
+    fn test_unreadable_summary() {
+        // The quantity moved or renamed
+        let unreadable = SUMMARY.replace(r#""quantity": { "value": 9 }"#, r#""quantity": 9"#);
+        assert!(positions_from_items(&items(&unreadable)).is_err());
+
+        let empty = items(r#"[{ "id": "account", "account": {} }]"#);
+        assert_eq!(cash_from_items(&empty), None);
+        assert!(positions_from_items(&empty).unwrap().is_empty());
+    }
+}
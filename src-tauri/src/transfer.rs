@@ -0,0 +1,98 @@
+use anyhow::Result;
+use bourso_api::{account::Account, client::BoursoWebClient};
+use chrono::Local;
+use futures_util::{pin_mut, StreamExt};
+use serde::{Deserialize, Serialize};
+use tauri::Wry;
+use tauri_plugin_store::Store;
+
+/// Transfer executed by the app, saved in the history next to the orders
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferPassed {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    /// Amount in euros
+    pub amount: f64,
+    pub timestamp: i64,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl TransferPassed {
+    pub fn new(from: &str, to: &str, amount: f64, reason: Option<String>) -> Self {
+        let timestamp = Local::now().timestamp();
+        TransferPassed {
+            // Bourso does not return an id for transfers
+            id: format!("{}-{}-{}", timestamp, from, to),
+            from: from.to_string(),
+            to: to.to_string(),
+            amount,
+            timestamp,
+            reason,
+        }
+    }
+}
+
+/// Append a transfer to the local history
+pub fn push_transfer(store: &Store<Wry>, transfer: &TransferPassed) -> Result<()> {
+    let mut transfers = load_transfers(store)?;
+    transfers.push(transfer.clone());
+    store.set("transfers", serde_json::json!(&transfers));
+    store.save()?;
+
+    Ok(())
+}
+
+pub fn load_transfers(store: &Store<Wry>) -> Result<Vec<TransferPassed>> {
+    let transfers = store.get("transfers").unwrap_or(serde_json::json!([]));
+    Ok(serde_json::from_value(transfers)?)
+}
+
+/// Find the source and target accounts of a transfer
+pub async fn find_transfer_accounts(
+    web_client: &BoursoWebClient,
+    source_account_id: &str,
+    target_account_id: &str,
+) -> Result<(Account, Account)> {
+    let accounts = web_client.get_accounts(None).await?;
+
+    let source_account = accounts
+        .iter()
+        .find(|a| a.id == source_account_id)
+        .ok_or_else(|| anyhow::anyhow!("Source account {} not found", source_account_id))?;
+
+    let target_account = accounts
+        .iter()
+        .find(|a| a.id == target_account_id)
+        .ok_or_else(|| anyhow::anyhow!("Target account {} not found", target_account_id))?;
+
+    Ok((source_account.clone(), target_account.clone()))
+}
+
+/// Transfer funds between two accounts of the user, without reporting
+/// the progress. Used by the scheduled jobs.
+pub async fn transfer(
+    web_client: &BoursoWebClient,
+    source_account_id: &str,
+    target_account_id: &str,
+    amount: f64,
+    reason: Option<String>,
+) -> Result<TransferPassed> {
+    let (source_account, target_account) =
+        find_transfer_accounts(web_client, source_account_id, target_account_id).await?;
+
+    let transfer =
+        TransferPassed::new(source_account_id, target_account_id, amount, reason.clone());
+    let stream = web_client.transfer_funds(amount, source_account, target_account, reason);
+
+    pin_mut!(stream);
+
+    while let Some(progress) = stream.next().await {
+        if let Err(e) = progress {
+            return Err(anyhow::anyhow!("Error while transferring funds: {:?}", e));
+        }
+    }
+
+    Ok(transfer)
+}
@@ -0,0 +1,350 @@
+//! Conditional jobs: a trigger evaluates a market condition from the end of
+//! day closes and the live quote on each scheduler run, and only lets the
+//! order run, or scales it, when the condition holds
+
+use anyhow::Result;
+use bourso_api::client::BoursoWebClient;
+use chrono::{DateTime, Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::ticks::{get_daily_closes, trailing_average, DailyClose};
+
+/// Evaluations kept on the trigger, the oldest are dropped
+const MAX_EVALUATIONS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Trigger {
+    pub condition: Condition,
+    #[serde(default)]
+    pub action: TriggerAction,
+    /// Days after firing during which the trigger does not fire again
+    #[serde(default)]
+    pub cooldown_days: u32,
+    /// Last time the trigger fired, in seconds
+    #[serde(default)]
+    pub last_fired: Option<i64>,
+    /// Last evaluations, the most recent last
+    #[serde(default)]
+    pub evaluations: Vec<Evaluation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Condition {
+    /// The price is at least `percent` below the highest close of the last
+    /// `days` days, 365 for the 52-week high
+    BelowHigh { percent: f64, days: u16 },
+    /// The price is below the average of the last `days` closes
+    BelowAverage { days: u16 },
+}
+
+/// What the trigger does to the job
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriggerAction {
+    /// The order is only placed when the condition holds, the job is
+    /// evaluated again on every scheduler run until it does
+    #[default]
+    RunWhenMet,
+    /// The order always runs on schedule, its amount or quantity is
+    /// multiplied by `factor` when the condition holds
+    ScaleWhenMet { factor: f64 },
+}
+
+/// Why the trigger did or did not fire
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Evaluation {
+    pub timestamp: i64,
+    pub price: Option<f64>,
+    /// Highest close or average the price is compared with
+    pub reference: Option<f64>,
+    pub fired: bool,
+    pub reason: String,
+}
+
+impl Condition {
+    /// Calendar days of closes needed to evaluate the condition
+    fn history_days(&self) -> u16 {
+        match self {
+            Condition::BelowHigh { days, .. } => days.saturating_add(5),
+            // Leave room for the week-ends and the holidays
+            Condition::BelowAverage { days } => days.saturating_mul(2).saturating_add(5),
+        }
+    }
+
+    /// Compare `price` with the closes before `today`, returns the reference
+    /// price, whether the condition holds and why
+    pub fn evaluate(
+        &self,
+        closes: &[DailyClose],
+        today: NaiveDate,
+        price: f64,
+    ) -> Result<(f64, bool, String)> {
+        match self {
+            Condition::BelowHigh { percent, days } => {
+                let since = today
+                    .checked_sub_days(Days::new((*days).into()))
+                    .unwrap_or(today);
+                let high = closes
+                    .iter()
+                    .filter(|c| c.date >= since && c.date < today)
+                    .map(|c| c.close)
+                    .fold(f64::NAN, f64::max);
+                if high.is_nan() {
+                    return Err(anyhow::anyhow!("No close in the last {} days", days));
+                }
+
+                let below = (high - price) / high * 100.0;
+                let met = below >= *percent;
+                Ok((
+                    high,
+                    met,
+                    format!(
+                        "{:.2}€ is {:.2}% below the {}-day high of {:.2}€, {} {:.2}%",
+                        price,
+                        below,
+                        days,
+                        high,
+                        if met { "at least" } else { "less than" },
+                        percent
+                    ),
+                ))
+            }
+            Condition::BelowAverage { days } => {
+                let average = trailing_average(closes, today, (*days).into())
+                    .ok_or_else(|| anyhow::anyhow!("Less than {} closes", days))?;
+
+                let met = price < average;
+                Ok((
+                    average,
+                    met,
+                    format!(
+                        "{:.2}€ is {} the {}-day average of {:.2}€",
+                        price,
+                        if met { "below" } else { "not below" },
+                        days,
+                        average
+                    ),
+                ))
+            }
+        }
+    }
+}
+
+impl Trigger {
+    pub fn validate(&self) -> Result<()> {
+        match self.condition {
+            Condition::BelowHigh { percent, days } => {
+                if !(0.0..100.0).contains(&percent) {
+                    return Err(anyhow::anyhow!(
+                        "The percentage below the high must be between 0 and 100"
+                    ));
+                }
+                if days == 0 {
+                    return Err(anyhow::anyhow!("The high needs at least one day"));
+                }
+            }
+            Condition::BelowAverage { days } => {
+                if days == 0 {
+                    return Err(anyhow::anyhow!("The average needs at least one day"));
+                }
+            }
+        }
+        if let TriggerAction::ScaleWhenMet { factor } = self.action {
+            if factor <= 0.0 {
+                return Err(anyhow::anyhow!("The scale factor must be positive"));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn in_cooldown(&self, now: i64) -> bool {
+        self.last_fired
+            .is_some_and(|fired| now - fired < self.cooldown_days as i64 * 86_400)
+    }
+
+    /// Evaluate the trigger for an order of `symbol` quoted at `price`. The
+    /// errors, e.g. a missing history, are recorded as a reason not to fire.
+    pub async fn evaluate(
+        &self,
+        web_client: &BoursoWebClient,
+        symbol: &str,
+        price: f64,
+    ) -> Evaluation {
+        let now = Local::now();
+        let mut evaluation = Evaluation {
+            timestamp: now.timestamp(),
+            price: Some(price),
+            reference: None,
+            fired: false,
+            reason: String::new(),
+        };
+
+        if self.in_cooldown(now.timestamp()) {
+            evaluation.reason = format!(
+                "Fired less than {} days ago, cooling down",
+                self.cooldown_days
+            );
+            return evaluation;
+        }
+
+        let days = self.condition.history_days();
+        let result = get_daily_closes(web_client, symbol, days)
+            .await
+            .and_then(|closes| self.condition.evaluate(&closes, now.date_naive(), price));
+        match result {
+            Ok((reference, met, reason)) => {
+                evaluation.reference = Some(reference);
+                evaluation.fired = met;
+                evaluation.reason = reason;
+            }
+            Err(e) => evaluation.reason = e.to_string(),
+        }
+
+        evaluation
+    }
+
+    /// Keep the evaluation. The job is evaluated on every scheduler run until
+    /// it fires, so only the last evaluation of the day is kept when it did
+    /// not fire.
+    pub fn record(&mut self, evaluation: Evaluation) {
+        if let Some(last) = self.evaluations.last() {
+            if !last.fired && !evaluation.fired && same_day(last.timestamp, evaluation.timestamp) {
+                self.evaluations.pop();
+            }
+        }
+        self.evaluations.push(evaluation);
+        if self.evaluations.len() > MAX_EVALUATIONS {
+            self.evaluations.remove(0);
+        }
+    }
+
+    /// Start the cooldown once the order of the last evaluation is placed. An
+    /// order that failed or was postponed keeps the trigger armed for the
+    /// retry.
+    pub fn start_cooldown(&mut self) {
+        if let Some(evaluation) = self.evaluations.last().filter(|e| e.fired) {
+            self.last_fired = Some(evaluation.timestamp);
+        }
+    }
+
+    /// Factor applied to the order after `evaluation`, `None` when the
+    /// order is not placed
+    pub fn factor(&self, evaluation: &Evaluation) -> Option<f64> {
+        match self.action {
+            TriggerAction::RunWhenMet => evaluation.fired.then_some(1.0),
+            TriggerAction::ScaleWhenMet { factor } => {
+                Some(if evaluation.fired { factor } else { 1.0 })
+            }
+        }
+    }
+}
+
+/// Quantity of an order scaled by `factor`, at least one share so that a
+/// small factor does not turn the order into an empty one
+pub fn scale_quantity(quantity: u64, factor: f64) -> u64 {
+    ((quantity as f64 * factor).round() as u64).max(1)
+}
+
+fn same_day(a: i64, b: i64) -> bool {
+    let day = |timestamp| {
+        DateTime::from_timestamp(timestamp, 0).map(|d| d.with_timezone(&Local).date_naive())
+    };
+    day(a) == day(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn closes(prices: &[f64]) -> Vec<DailyClose> {
+        let start = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, close)| DailyClose {
+                date: start + Days::new(i as u64),
+                close: *close,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_conditions() {
+        let closes = closes(&[100.0, 120.0, 110.0, 105.0]);
+        let today = NaiveDate::from_ymd_opt(2025, 3, 7).unwrap();
+
+        let below_high = Condition::BelowHigh {
+            percent: 10.0,
+            days: 365,
+        };
+        let (high, met, _) = below_high.evaluate(&closes, today, 108.0).unwrap();
+        assert_eq!(high, 120.0);
+        assert!(met);
+        assert!(!below_high.evaluate(&closes, today, 110.0).unwrap().1);
+        // The 120 close is out of a 2-day window
+        let (high, _, _) = Condition::BelowHigh {
+            percent: 10.0,
+            days: 2,
+        }
+        .evaluate(&closes, today, 100.0)
+        .unwrap();
+        assert_eq!(high, 110.0);
+
+        let below_average = Condition::BelowAverage { days: 4 };
+        let (average, met, _) = below_average.evaluate(&closes, today, 108.0).unwrap();
+        assert_eq!(average, 108.75);
+        assert!(met);
+        assert!(Condition::BelowAverage { days: 5 }
+            .evaluate(&closes, today, 108.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_cooldown_and_factor() {
+        let mut trigger = Trigger {
+            condition: Condition::BelowAverage { days: 200 },
+            action: TriggerAction::ScaleWhenMet { factor: 2.0 },
+            cooldown_days: 7,
+            last_fired: None,
+            evaluations: vec![],
+        };
+        let evaluation = |fired: bool| Evaluation {
+            timestamp: 1_000_000,
+            price: Some(100.0),
+            reference: Some(110.0),
+            fired,
+            reason: String::new(),
+        };
+
+        assert_eq!(trigger.factor(&evaluation(false)), Some(1.0));
+        assert_eq!(trigger.factor(&evaluation(true)), Some(2.0));
+        assert_eq!(scale_quantity(3, 2.0), 6);
+        assert_eq!(scale_quantity(1, 0.4), 1);
+
+        trigger.record(evaluation(true));
+        // Not before the order is placed
+        assert!(!trigger.in_cooldown(1_000_000 + 86_400));
+        trigger.start_cooldown();
+        assert!(trigger.in_cooldown(1_000_000 + 6 * 86_400));
+        assert!(!trigger.in_cooldown(1_000_000 + 7 * 86_400));
+
+        trigger.action = TriggerAction::RunWhenMet;
+        assert_eq!(trigger.factor(&evaluation(false)), None);
+
+        // One evaluation a day while it does not fire
+        trigger.record(evaluation(false));
+        trigger.record(evaluation(false));
+        assert_eq!(trigger.evaluations.len(), 2);
+
+        for day in 1..=MAX_EVALUATIONS as i64 {
+            trigger.record(Evaluation {
+                timestamp: 1_000_000 + day * 86_400,
+                ..evaluation(false)
+            });
+        }
+        assert_eq!(trigger.evaluations.len(), MAX_EVALUATIONS);
+        assert_eq!(trigger.last_fired, Some(1_000_000));
+    }
+}
@@ -0,0 +1,197 @@
+//! Value averaging: each period, invest what the position needs to follow a
+//! target value growing along a path, instead of a fixed amount
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::order::{qty_for_budget, OrderArgs, OrderSide};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ValueAveraging {
+    pub account: String,
+    pub symbol: String,
+    /// Value of the position the path starts from, in euros. Set to the
+    /// value of the position on the first run when unset.
+    #[serde(default)]
+    pub initial_value: Option<f64>,
+    /// Value added to the target on each period, in euros
+    pub increment: f64,
+    /// Growth of the target on each period, in percent, e.g. the expected
+    /// return of the security
+    #[serde(default)]
+    pub growth_percent: f64,
+    /// Amount invested at least on each period, in euros
+    #[serde(default)]
+    pub min_amount: Option<f64>,
+    /// Amount traded at most on each period, in euros
+    #[serde(default)]
+    pub max_amount: Option<f64>,
+    /// Sell the shares above the target when the position is ahead
+    #[serde(default)]
+    pub sell_when_ahead: bool,
+    /// Periods already run
+    #[serde(default)]
+    pub periods: u32,
+}
+
+/// Trade of a period
+#[derive(Debug, PartialEq)]
+pub struct Step {
+    pub target: f64,
+    pub value: f64,
+    /// Amount to buy, negative to sell, after the caps
+    pub amount: f64,
+}
+
+impl ValueAveraging {
+    pub fn validate(&self) -> Result<()> {
+        if self.increment <= 0.0 {
+            return Err(anyhow::anyhow!("The increment must be positive"));
+        }
+        if let (Some(min), Some(max)) = (self.min_amount, self.max_amount) {
+            if min > max {
+                return Err(anyhow::anyhow!(
+                    "The minimum amount is above the maximum amount"
+                ));
+            }
+        }
+        if self.sell_when_ahead && self.max_amount.is_none() {
+            return Err(anyhow::anyhow!(
+                "Selling when ahead needs a maximum amount per period"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Start the path from the position worth `value` on the first run, so
+    /// that an existing position is not sold to follow a path starting
+    /// from nothing
+    pub fn anchor(&mut self, value: f64) {
+        if self.periods == 0 && self.initial_value.is_none() {
+            self.initial_value = Some(value);
+        }
+    }
+
+    /// Target value of the position after `period` periods
+    pub fn target(&self, period: u32) -> f64 {
+        let growth = 1.0 + self.growth_percent / 100.0;
+        (0..period).fold(self.initial_value.unwrap_or_default(), |target, _| {
+            target * growth + self.increment
+        })
+    }
+
+    /// Amount bringing the position worth `value` to the target of the
+    /// next period
+    pub fn step(&self, value: f64) -> Step {
+        let target = self.target(self.periods + 1);
+        let required = target - value;
+
+        let amount = if required >= 0.0 {
+            let amount = required.max(self.min_amount.unwrap_or_default());
+            self.max_amount.map_or(amount, |max| amount.min(max))
+        } else if self.sell_when_ahead {
+            let amount = required;
+            self.max_amount.map_or(amount, |max| amount.max(-max))
+        } else {
+            self.min_amount.unwrap_or_default()
+        };
+
+        Step {
+            target,
+            value,
+            amount,
+        }
+    }
+
+    /// Order of a step, `None` when there is nothing to trade. The sales are
+    /// in whole shares at `price`.
+    pub fn order(&self, step: &Step, price: f64) -> Option<OrderArgs> {
+        let (quantity, amount, side) = if step.amount > 0.0 {
+            (None, Some(step.amount), OrderSide::Buy)
+        } else {
+            let quantity = qty_for_budget(-step.amount, price);
+            if quantity == 0 {
+                return None;
+            }
+            (Some(quantity), None, OrderSide::Sell)
+        };
+
+        Some(OrderArgs {
+            account: self.account.clone(),
+            symbol: self.symbol.clone(),
+            quantity,
+            amount,
+            side,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value_averaging() -> ValueAveraging {
+        ValueAveraging {
+            account: "1234567890".to_string(),
+            symbol: "1rTCW8".to_string(),
+            initial_value: Some(0.0),
+            increment: 100.0,
+            growth_percent: 0.0,
+            min_amount: None,
+            max_amount: Some(150.0),
+            sell_when_ahead: false,
+            periods: 2,
+        }
+    }
+
+    #[test]
+    fn test_value_averaging() {
+        let mut strategy = value_averaging();
+        assert_eq!(strategy.target(3), 300.0);
+
+        // The position lost value, the caps limit the catch up
+        assert_eq!(strategy.step(120.0).amount, 150.0);
+        assert_eq!(strategy.step(250.0).amount, 50.0);
+        // Ahead of the target
+        assert_eq!(strategy.step(320.0).amount, 0.0);
+        assert!(strategy.order(&strategy.step(320.0), 10.0).is_none());
+
+        strategy.sell_when_ahead = true;
+        let step = strategy.step(335.0);
+        assert_eq!(step.amount, -35.0);
+        let order = strategy.order(&step, 10.0).unwrap();
+        assert_eq!(order.side, OrderSide::Sell);
+        assert_eq!(order.quantity, Some(3));
+
+        strategy.growth_percent = 10.0;
+        strategy.initial_value = Some(1_000.0);
+        // 1000 * 1.1 + 100 = 1200, then 1200 * 1.1 + 100
+        assert!((strategy.target(2) - 1_420.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_existing_position() {
+        let mut strategy = value_averaging();
+        strategy.initial_value = None;
+        strategy.periods = 0;
+        strategy.sell_when_ahead = true;
+
+        strategy.max_amount = None;
+        assert!(strategy.validate().is_err());
+        strategy.max_amount = Some(150.0);
+        assert!(strategy.validate().is_ok());
+
+        // A 5000€ position is not sold down to the first increment
+        strategy.anchor(5_000.0);
+        assert_eq!(strategy.initial_value, Some(5_000.0));
+        let step = strategy.step(5_000.0);
+        assert_eq!(step.target, 5_100.0);
+        assert_eq!(step.amount, 100.0);
+
+        // The anchor is only set once
+        strategy.periods = 1;
+        strategy.anchor(6_000.0);
+        assert_eq!(strategy.initial_value, Some(5_000.0));
+    }
+}
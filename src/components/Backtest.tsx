@@ -0,0 +1,114 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import {
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  CartesianGrid,
+  Tooltip,
+  Legend,
+  ResponsiveContainer,
+} from "recharts";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { Backtest as BacktestResult, OrderArgs, WeeklyMonthly } from "@/types";
+
+const oneYearAgo = () => {
+  const date = new Date();
+  date.setFullYear(date.getFullYear() - 1);
+  return date.toISOString().slice(0, 10);
+};
+
+/**
+ * How the job being set up would have performed since a date
+ */
+export function Backtest({
+  schedule,
+  order,
+  carryOver,
+}: {
+  schedule: WeeklyMonthly | "daily";
+  order: OrderArgs;
+  carryOver: boolean;
+}) {
+  const [from, setFrom] = useState(oneYearAgo());
+  const [result, setResult] = useState<BacktestResult | null>(null);
+  const [running, setRunning] = useState(false);
+
+  const run = async () => {
+    setRunning(true);
+    try {
+      setResult(
+        await invoke("backtest_dca", { schedule, order, from, carryOver }),
+      );
+    } catch (error) {
+      toast.error("Error running the backtest", { description: `${error}` });
+    }
+    setRunning(false);
+  };
+
+  const euros = (value: number) => `${value.toFixed(2)}€`;
+
+  return (
+    <div className="space-y-2">
+      <div className="flex gap-2">
+        <Input
+          className="w-40"
+          type="date"
+          value={from}
+          onChange={(e) => setFrom(e.target.value)}
+        />
+        <Button
+          type="button"
+          variant="outline"
+          onClick={run}
+          disabled={running}
+        >
+          Backtest
+        </Button>
+      </div>
+      {result && (
+        <>
+          <p className="text-sm text-muted-foreground">
+            {result.runs.length} run(s), {result.shares} share(s) bought for{" "}
+            {euros(result.invested)} at {euros(result.average_cost)} on
+            average, worth {euros(result.value)} today ({euros(result.gain)}).
+            Invested at once on the first day: {euros(result.lump_sum.value)}{" "}
+            ({euros(result.lump_sum.gain)}).
+          </p>
+          <div className="h-[200px] w-full">
+            <ResponsiveContainer width="100%" height="100%">
+              <LineChart data={result.points}>
+                <CartesianGrid strokeDasharray="3 3" className="stroke-muted" />
+                <XAxis dataKey="date" className="text-xs" />
+                <YAxis className="text-xs" />
+                <Tooltip formatter={(value: number) => [euros(value)]} />
+                <Legend />
+                <Line
+                  dataKey="value"
+                  name="DCA"
+                  stroke="#2563eb"
+                  dot={false}
+                />
+                <Line
+                  dataKey="invested"
+                  name="Invested"
+                  stroke="#9ca3af"
+                  dot={false}
+                />
+                <Line
+                  dataKey="lump_sum_value"
+                  name="Lump sum"
+                  stroke="#16a34a"
+                  dot={false}
+                />
+              </LineChart>
+            </ResponsiveContainer>
+          </div>
+        </>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,141 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import {
+  AccountType,
+  Job,
+  PriceGuard,
+  Weight,
+  WeeklyMonthly,
+} from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { weightsToString } from "@/utils/jobUtils";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { PriceGuardEditor } from "./PriceGuardEditor";
+import { WeightsEditor } from "./WeightsEditor";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+/**
+ * DCA of one amount split across several assets, placed as one job
+ */
+export function BasketForm({
+  accounts,
+  onCreated,
+}: {
+  accounts: AccountType[];
+  onCreated: (job: Job) => void;
+}) {
+  const tradingAccounts = accounts.filter((a) => a.kind === "Trading");
+  const [account, setAccount] = useState(tradingAccounts[0]?.id ?? "");
+  const [amount, setAmount] = useState("");
+  const [scheduleType, setScheduleType] = useState("monthly");
+  const [topUpFrom, setTopUpFrom] = useState("none");
+  const [legs, setLegs] = useState<Weight[]>([{ symbol: "", weight: 100 }]);
+  const [priceGuard, setPriceGuard] = useState<PriceGuard | undefined>();
+
+  const create = async () => {
+    const schedule: WeeklyMonthly | "daily" =
+      scheduleType === "weekly"
+        ? { weekly: { day: 1 } }
+        : scheduleType === "monthly"
+          ? { monthly: { day: 1 } }
+          : "daily";
+    const basketLegs = legs.filter((l) => l.symbol);
+    const job: Job = {
+      id: `${scheduleType}basket_${amount}_${basketLegs.map((l) => l.symbol).join("_")}`,
+      schedule,
+      last_run: Math.floor(Date.now() / 1000),
+      command: {
+        basket: { account, amount: parseFloat(amount), legs: basketLegs },
+      },
+      top_up_from: topUpFrom === "none" ? undefined : topUpFrom,
+      price_guard: priceGuard,
+    };
+
+    try {
+      await invoke("add_scheduled_job", { job });
+      onCreated(job);
+      toast("Basket scheduled", {
+        description: `${amount}€ in ${weightsToString(basketLegs)}`,
+      });
+    } catch (error) {
+      toast.error("Error scheduling the basket", { description: `${error}` });
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Basket</CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <p className="text-sm text-muted-foreground">
+          Split one amount across several assets. The markets and the cash are
+          checked once, then every order is placed in the same run.
+        </p>
+        <div className="grid grid-cols-2 gap-2">
+          <Select value={account} onValueChange={setAccount}>
+            <SelectTrigger>
+              <SelectValue placeholder="Account" />
+            </SelectTrigger>
+            <SelectContent>
+              {tradingAccounts.map((a) => (
+                <SelectItem key={a.id} value={a.id}>
+                  {getFormattedAccountName(a, accounts)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Input
+            type="number"
+            placeholder="Total amount (€)"
+            value={amount}
+            onChange={(e) => setAmount(e.target.value)}
+          />
+          <Select value={scheduleType} onValueChange={setScheduleType}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="daily">Every day</SelectItem>
+              <SelectItem value="weekly">Every week</SelectItem>
+              <SelectItem value="monthly">Every month</SelectItem>
+            </SelectContent>
+          </Select>
+          <Select value={topUpFrom} onValueChange={setTopUpFrom}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="none">No top-up</SelectItem>
+              {accounts
+                .filter((a) => a.kind !== "Trading" && a.kind !== "Loans")
+                .map((a) => (
+                  <SelectItem key={a.id} value={a.id}>
+                    Top up from {getFormattedAccountName(a, accounts)}
+                  </SelectItem>
+                ))}
+            </SelectContent>
+          </Select>
+        </div>
+        <WeightsEditor weights={legs} onChange={setLegs} />
+        <PriceGuardEditor guard={priceGuard} onChange={setPriceGuard} />
+        <Button
+          className="w-full"
+          onClick={create}
+          disabled={!account || !amount || !legs.some((l) => l.symbol)}
+        >
+          Schedule basket
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,130 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { Scale } from "lucide-react";
+import {
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  CartesianGrid,
+  Tooltip,
+  Legend,
+  ResponsiveContainer,
+} from "recharts";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { BenchmarkComparison } from "@/types";
+
+const euros = (value: number) => `${value.toFixed(2)}€`;
+const percent = (value?: number) =>
+  value === undefined || value === null ? "-" : `${(value * 100).toFixed(2)}%`;
+
+/**
+ * The portfolio against the same contributions invested in a benchmark
+ */
+export function Benchmark() {
+  // Amundi MSCI World
+  const [symbol, setSymbol] = useState("1rTCW8");
+  const [comparison, setComparison] = useState<BenchmarkComparison | null>(
+    null,
+  );
+  const [loading, setLoading] = useState(false);
+
+  const compare = async () => {
+    setLoading(true);
+    try {
+      setComparison(await invoke("compare_benchmark", { symbol }));
+    } catch (error) {
+      toast.error("Error comparing with the benchmark", {
+        description: `${error}`,
+      });
+    }
+    setLoading(false);
+  };
+
+  return (
+    <Card className="col-span-full">
+      <CardHeader>
+        <div className="flex items-center justify-between">
+          <CardTitle className="flex items-center gap-2 text-xl">
+            <Scale className="h-6 w-6" />
+            Benchmark
+          </CardTitle>
+          <div className="flex gap-2">
+            <Input
+              className="w-40"
+              placeholder="Symbol"
+              value={symbol}
+              onChange={(e) => setSymbol(e.target.value)}
+            />
+            <Button onClick={compare} disabled={loading || !symbol}>
+              Compare
+            </Button>
+          </div>
+        </div>
+      </CardHeader>
+      <CardContent className="space-y-2">
+        {comparison && (
+          <>
+            <p className="text-sm text-muted-foreground">
+              Portfolio {euros(comparison.portfolio.end_value)} (TWR{" "}
+              {percent(comparison.portfolio.twr)}) against{" "}
+              {euros(comparison.benchmark.end_value)} in {comparison.symbol}{" "}
+              (TWR {percent(comparison.benchmark.twr)}):{" "}
+              <span
+                className={
+                  comparison.outperformance >= 0
+                    ? "text-green-600"
+                    : "text-red-600"
+                }
+              >
+                {comparison.outperformance >= 0 ? "+" : ""}
+                {euros(comparison.outperformance)}
+              </span>
+            </p>
+            {comparison.unpriced.length > 0 && (
+              <p className="text-xs text-muted-foreground">
+                No closes for {comparison.unpriced.join(", ")}, valued at the
+                price of their last order
+              </p>
+            )}
+            <div className="h-[300px] w-full">
+              <ResponsiveContainer width="100%" height="100%">
+                <LineChart data={comparison.points}>
+                  <CartesianGrid
+                    strokeDasharray="3 3"
+                    className="stroke-muted"
+                  />
+                  <XAxis dataKey="date" className="text-xs" />
+                  <YAxis className="text-xs" />
+                  <Tooltip formatter={(value: number) => [euros(value)]} />
+                  <Legend />
+                  <Line
+                    dataKey="portfolio"
+                    name="Portfolio"
+                    stroke="#2563eb"
+                    dot={false}
+                  />
+                  <Line
+                    dataKey="benchmark"
+                    name={comparison.symbol}
+                    stroke="#16a34a"
+                    dot={false}
+                  />
+                  <Line
+                    dataKey="net_contributions"
+                    name="Contributions"
+                    stroke="#9ca3af"
+                    dot={false}
+                  />
+                </LineChart>
+              </ResponsiveContainer>
+            </div>
+          </>
+        )}
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,146 @@
+import { AlertTriangle, Calculator } from "lucide-react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Table, TableBody, TableHead, TableHeader, TableRow } from "./ui/table";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+import {
+  Tooltip,
+  TooltipContent,
+  TooltipProvider,
+  TooltipTrigger,
+} from "./ui/tooltip";
+import { AccountType, CostMethod, Position } from "@/types";
+import { Dialog, DialogContent } from "./ui/dialog";
+import { Button } from "./ui/button";
+import { TaxReport } from "./TaxReport";
+import { Reconcile } from "./Reconcile";
+
+const euros = (value?: number) =>
+  value === undefined || value === null ? "N/A" : `${value.toFixed(2)}€`;
+
+export function CostBasis({
+  accounts,
+  jobsExecuted,
+}: {
+  accounts: AccountType[];
+  jobsExecuted: number;
+}) {
+  const [method, setMethod] = useState<CostMethod>("pru");
+  const [positions, setPositions] = useState<Position[]>([]);
+  const [taxOpen, setTaxOpen] = useState(false);
+  const [reconcileOpen, setReconcileOpen] = useState(false);
+
+  const load = async () => {
+    try {
+      setPositions(await invoke("get_positions", { method }));
+    } catch (error) {
+      toast.error("Error computing positions", { description: `${error}` });
+    }
+  };
+
+  useEffect(() => {
+    load();
+  }, [method, jobsExecuted]);
+
+  const accountName = (id: string) =>
+    accounts.find((a) => a.id === id)?.name ?? id;
+
+  return (
+    <Card>
+      <CardHeader>
+        <div className="flex items-center justify-between">
+          <CardTitle className="flex items-center gap-2 text-xl">
+            <Calculator className="h-6 w-6" />
+            Cost basis
+          </CardTitle>
+          <div className="flex items-center gap-2">
+            <Button variant="outline" onClick={() => setReconcileOpen(true)}>
+              Reconcile
+            </Button>
+            <Button variant="outline" onClick={() => setTaxOpen(true)}>
+              Tax report
+            </Button>
+            <Select
+              value={method}
+              onValueChange={(v) => setMethod(v as CostMethod)}
+            >
+              <SelectTrigger className="w-32">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="pru">PRU</SelectItem>
+                <SelectItem value="fifo">FIFO</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+        </div>
+        <Dialog open={reconcileOpen} onOpenChange={setReconcileOpen}>
+          <DialogContent>
+            <Reconcile onCorrected={load} />
+          </DialogContent>
+        </Dialog>
+        <Dialog open={taxOpen} onOpenChange={setTaxOpen}>
+          <DialogContent>
+            <TaxReport accounts={accounts} />
+          </DialogContent>
+        </Dialog>
+      </CardHeader>
+      <CardContent>
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>Asset</TableHead>
+              <TableHead>Account</TableHead>
+              <TableHead>Quantity</TableHead>
+              <TableHead>Average cost</TableHead>
+              <TableHead>Value</TableHead>
+              <TableHead>Unrealized</TableHead>
+              <TableHead>Realized</TableHead>
+              <TableHead>Dividends</TableHead>
+              <TableHead>Fees</TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {positions.map((position) => (
+              <TableRow key={`${position.account}-${position.symbol}`}>
+                <td>
+                  <div className="flex items-center gap-1">
+                    {position.symbol}
+                    {position.warnings.length > 0 && (
+                      <TooltipProvider>
+                        <Tooltip>
+                          <TooltipTrigger asChild>
+                            <AlertTriangle className="h-4 w-4 text-yellow-500" />
+                          </TooltipTrigger>
+                          <TooltipContent>
+                            {position.warnings.join("\n")}
+                          </TooltipContent>
+                        </Tooltip>
+                      </TooltipProvider>
+                    )}
+                  </div>
+                </td>
+                <td>{accountName(position.account)}</td>
+                <td>{position.quantity}</td>
+                <td>{euros(position.average_cost)}</td>
+                <td>{euros(position.market_value)}</td>
+                <td>{euros(position.unrealized_gain)}</td>
+                <td>{euros(position.realized_gain)}</td>
+                <td>{euros(position.dividends)}</td>
+                <td>{euros(position.fees)}</td>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,226 @@
+import { PiggyBank, Trash2 } from "lucide-react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Table, TableBody, TableHead, TableHeader, TableRow } from "./ui/table";
+import { Progress } from "./ui/progress";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+import { AccountType, Envelope, EnvelopeKind, EnvelopeStatus } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+
+const KIND_LABELS: Record<EnvelopeKind, string> = {
+  pea: "PEA",
+  pea_pme: "PEA-PME",
+  livret_a: "Livret A",
+  ldds: "LDDS",
+};
+
+const toEnvelope = ({
+  account,
+  kind,
+  starting_amount,
+  custom_ceiling,
+  action,
+}: Envelope): Envelope => ({
+  account,
+  kind,
+  starting_amount,
+  custom_ceiling,
+  action,
+});
+
+/**
+ * Contributions of the accounts with a ceiling, and when the scheduled
+ * jobs will reach it
+ */
+export function Envelopes({
+  accounts,
+  jobsExecuted,
+}: {
+  accounts: AccountType[];
+  jobsExecuted: number;
+}) {
+  const [envelopes, setEnvelopes] = useState<EnvelopeStatus[]>([]);
+  const [account, setAccount] = useState("");
+  const [kind, setKind] = useState<EnvelopeKind>("pea");
+  const [startingAmount, setStartingAmount] = useState("");
+
+  const load = async () => {
+    try {
+      setEnvelopes(await invoke("get_envelopes"));
+    } catch (error) {
+      toast.error("Error loading the ceilings", { description: `${error}` });
+    }
+  };
+
+  useEffect(() => {
+    load();
+  }, [jobsExecuted]);
+
+  const save = async (updated: Envelope[]) => {
+    try {
+      await invoke("set_envelopes", { envelopes: updated });
+      await load();
+    } catch (error) {
+      toast.error("Error saving the ceilings", { description: `${error}` });
+    }
+  };
+
+  const add = () =>
+    save([
+      ...envelopes.filter((e) => e.account !== account).map(toEnvelope),
+      {
+        account,
+        kind,
+        starting_amount: parseFloat(startingAmount || "0"),
+        action: "block",
+      },
+    ]);
+
+  const accountName = (id: string) => {
+    const found = accounts.find((a) => a.id === id);
+    return found ? getFormattedAccountName(found, accounts) : id;
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2 text-xl">
+          <PiggyBank className="h-6 w-6" />
+          Ceilings
+        </CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>Account</TableHead>
+              <TableHead>Contributed</TableHead>
+              <TableHead>Left</TableHead>
+              <TableHead>Reached on</TableHead>
+              <TableHead>Over the ceiling</TableHead>
+              <TableHead></TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {envelopes.map((envelope) => (
+              <TableRow key={envelope.account}>
+                <td>
+                  {accountName(envelope.account)}
+                  <div className="text-xs text-muted-foreground">
+                    {KIND_LABELS[envelope.kind]}
+                  </div>
+                </td>
+                <td>
+                  {envelope.contributed.toFixed(2)}€ /{" "}
+                  {envelope.ceiling.toFixed(2)}€
+                  <Progress
+                    value={(envelope.contributed / envelope.ceiling) * 100}
+                  />
+                </td>
+                <td>{envelope.remaining.toFixed(2)}€</td>
+                <td>
+                  {envelope.projection.ceiling_date
+                    ? new Date(
+                        envelope.projection.ceiling_date,
+                      ).toLocaleDateString()
+                    : "Not scheduled"}
+                  {envelope.projection.monthly_contribution > 0 && (
+                    <div className="text-xs text-muted-foreground">
+                      {envelope.projection.monthly_contribution.toFixed(2)}€ /
+                      month
+                    </div>
+                  )}
+                </td>
+                <td>
+                  <Select
+                    value={envelope.action}
+                    onValueChange={(action) =>
+                      save(
+                        envelopes.map((e) =>
+                          toEnvelope(
+                            e.account === envelope.account
+                              ? { ...e, action: action as Envelope["action"] }
+                              : e,
+                          ),
+                        ),
+                      )
+                    }
+                  >
+                    <SelectTrigger className="w-28">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="block">Block</SelectItem>
+                      <SelectItem value="warn">Warn</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </td>
+                <td>
+                  <Button
+                    variant="ghost"
+                    size="icon"
+                    className="text-destructive"
+                    onClick={() =>
+                      save(
+                        envelopes
+                          .filter((e) => e.account !== envelope.account)
+                          .map(toEnvelope),
+                      )
+                    }
+                  >
+                    <Trash2 className="h-4 w-4" />
+                  </Button>
+                </td>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+        <div className="grid grid-cols-4 gap-2">
+          <Select value={account} onValueChange={setAccount}>
+            <SelectTrigger>
+              <SelectValue placeholder="Account" />
+            </SelectTrigger>
+            <SelectContent>
+              {accounts.map((a) => (
+                <SelectItem key={a.id} value={a.id}>
+                  {getFormattedAccountName(a, accounts)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Select value={kind} onValueChange={(v) => setKind(v as EnvelopeKind)}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              {Object.entries(KIND_LABELS).map(([value, label]) => (
+                <SelectItem key={value} value={value}>
+                  {label}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Input
+            type="number"
+            placeholder="Already contributed (€)"
+            value={startingAmount}
+            onChange={(e) => setStartingAmount(e.target.value)}
+          />
+          <Button variant="outline" disabled={!account} onClick={add}>
+            Track
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,276 @@
+import { Coins, Trash2 } from "lucide-react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Table, TableBody, TableHead, TableHeader, TableRow } from "./ui/table";
+import { Dialog, DialogContent, DialogHeader, DialogTitle } from "./ui/dialog";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+import { AccountEvent, AccountType, EventKind, ImportSummary } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+
+const describe = (event: AccountEvent) => {
+  switch (event.kind) {
+    case "dividend":
+      return `Dividend ${(event.gross - event.withholding_tax).toFixed(2)}€ (${event.gross.toFixed(2)}€ gross)`;
+    case "split":
+      return event.to > event.from
+        ? `Split ${event.from}:${event.to}`
+        : `Reverse split ${event.from}:${event.to}`;
+    case "fee":
+      return `Fee ${event.amount.toFixed(2)}€`;
+  }
+};
+
+/**
+ * Dividends, splits and fees of the trading accounts, entered by hand or
+ * imported from a CSV
+ */
+export function Events({
+  accounts,
+  onChanged,
+}: {
+  accounts: AccountType[];
+  onChanged: () => void;
+}) {
+  const [events, setEvents] = useState<AccountEvent[]>([]);
+  const [account, setAccount] = useState(accounts[0]?.id ?? "");
+  const [kind, setKind] = useState<EventKind["kind"]>("dividend");
+  const [symbol, setSymbol] = useState("");
+  const [date, setDate] = useState("");
+  const [amount, setAmount] = useState("");
+  const [withholding, setWithholding] = useState("");
+  const [ratio, setRatio] = useState("");
+  const [importOpen, setImportOpen] = useState(false);
+  const [file, setFile] = useState<File | null>(null);
+
+  const load = async () => {
+    try {
+      setEvents(await invoke("get_events"));
+    } catch (error) {
+      toast.error("Error loading the events", { description: `${error}` });
+    }
+  };
+
+  useEffect(() => {
+    load();
+  }, []);
+
+  const changed = async () => {
+    await load();
+    onChanged();
+  };
+
+  const toKind = (): EventKind => {
+    switch (kind) {
+      case "dividend":
+        return {
+          kind,
+          gross: parseFloat(amount || "0"),
+          withholding_tax: parseFloat(withholding || "0"),
+        };
+      case "split": {
+        const [from, to] = ratio.split(/[:/]/).map((n) => parseInt(n, 10));
+        return { kind, from, to };
+      }
+      case "fee":
+        return { kind, amount: parseFloat(amount || "0") };
+    }
+  };
+
+  const add = async () => {
+    try {
+      await invoke("add_event_cmd", {
+        event: {
+          ...toKind(),
+          id: "",
+          account,
+          symbol: symbol || undefined,
+          timestamp: Math.floor(new Date(date).getTime() / 1000),
+        },
+      });
+      setAmount("");
+      setWithholding("");
+      setRatio("");
+      await changed();
+    } catch (error) {
+      toast.error("Error adding the event", { description: `${error}` });
+    }
+  };
+
+  const remove = async (id: string) => {
+    try {
+      await invoke("delete_event_cmd", { id });
+      await changed();
+    } catch (error) {
+      toast.error("Error deleting the event", { description: `${error}` });
+    }
+  };
+
+  const importEvents = async () => {
+    if (!file) return;
+    try {
+      const summary: ImportSummary = await invoke("import_events_csv", {
+        content: await file.text(),
+        account,
+      });
+      toast.success(`${summary.imported} event(s) imported`, {
+        description: `${summary.duplicates} already in the history, ${summary.skipped.length} row(s) skipped`,
+      });
+      setImportOpen(false);
+      await changed();
+    } catch (error) {
+      toast.error("Error importing the events", { description: `${error}` });
+    }
+  };
+
+  const accountName = (id: string) => {
+    const found = accounts.find((a) => a.id === id);
+    return found ? getFormattedAccountName(found, accounts) : id;
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <div className="flex items-center justify-between">
+          <CardTitle className="flex items-center gap-2 text-xl">
+            <Coins className="h-6 w-6" />
+            Dividends, splits and fees
+          </CardTitle>
+          <Button variant="outline" onClick={() => setImportOpen(true)}>
+            Import CSV
+          </Button>
+        </div>
+        <Dialog open={importOpen} onOpenChange={setImportOpen}>
+          <DialogContent>
+            <DialogHeader>
+              <DialogTitle>Import events</DialogTitle>
+            </DialogHeader>
+            <p className="text-sm text-muted-foreground">
+              The rows without an account column are added to the selected
+              account. Events already in the history are ignored.
+            </p>
+            <Input
+              type="file"
+              accept=".csv,text/csv"
+              onChange={(e) => setFile(e.target.files?.[0] ?? null)}
+            />
+            <Button onClick={importEvents} disabled={!file || !account}>
+              Import
+            </Button>
+          </DialogContent>
+        </Dialog>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>Date</TableHead>
+              <TableHead>Account</TableHead>
+              <TableHead>Asset</TableHead>
+              <TableHead>Event</TableHead>
+              <TableHead></TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {events.map((event) => (
+              <TableRow key={event.id}>
+                <td>{new Date(event.timestamp * 1000).toLocaleDateString()}</td>
+                <td>{accountName(event.account)}</td>
+                <td>{event.symbol ?? "-"}</td>
+                <td>
+                  {describe(event)}
+                  {event.note && (
+                    <div className="text-xs text-muted-foreground">
+                      {event.note}
+                    </div>
+                  )}
+                </td>
+                <td>
+                  <Button
+                    variant="ghost"
+                    size="icon"
+                    className="text-destructive"
+                    onClick={() => remove(event.id)}
+                  >
+                    <Trash2 className="h-4 w-4" />
+                  </Button>
+                </td>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+        <div className="grid grid-cols-3 gap-2">
+          <Select value={account} onValueChange={setAccount}>
+            <SelectTrigger>
+              <SelectValue placeholder="Account" />
+            </SelectTrigger>
+            <SelectContent>
+              {accounts.map((a) => (
+                <SelectItem key={a.id} value={a.id}>
+                  {getFormattedAccountName(a, accounts)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Select
+            value={kind}
+            onValueChange={(v) => setKind(v as EventKind["kind"])}
+          >
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="dividend">Dividend</SelectItem>
+              <SelectItem value="split">Split</SelectItem>
+              <SelectItem value="fee">Fee</SelectItem>
+            </SelectContent>
+          </Select>
+          <Input
+            type="date"
+            value={date}
+            onChange={(e) => setDate(e.target.value)}
+          />
+          <Input
+            placeholder={kind === "fee" ? "Symbol (optional)" : "Symbol"}
+            value={symbol}
+            onChange={(e) => setSymbol(e.target.value)}
+          />
+          {kind === "split" ? (
+            <Input
+              placeholder="Ratio old:new"
+              value={ratio}
+              onChange={(e) => setRatio(e.target.value)}
+            />
+          ) : (
+            <Input
+              type="number"
+              placeholder={kind === "dividend" ? "Gross amount" : "Amount"}
+              value={amount}
+              onChange={(e) => setAmount(e.target.value)}
+            />
+          )}
+          {kind === "dividend" && (
+            <Input
+              type="number"
+              placeholder="Withholding tax"
+              value={withholding}
+              onChange={(e) => setWithholding(e.target.value)}
+            />
+          )}
+        </div>
+        <Button className="w-full" onClick={add} disabled={!account || !date}>
+          Add
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,103 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { downloadDir, join } from "@tauri-apps/api/path";
+import { toast } from "sonner";
+import { AccountType, ExportFormat, OrderFilter } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { DialogHeader, DialogTitle } from "./ui/dialog";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+/**
+ * Export the orders history for spreadsheets and portfolio tools
+ */
+export function ExportOrders({ accounts }: { accounts: AccountType[] }) {
+  const [format, setFormat] = useState<ExportFormat>("csv");
+  const [directory, setDirectory] = useState("");
+  const [account, setAccount] = useState("all");
+  const [symbol, setSymbol] = useState("");
+  const [from, setFrom] = useState("");
+  const [to, setTo] = useState("");
+
+  useEffect(() => {
+    downloadDir().then(setDirectory);
+  }, []);
+
+  const exportOrders = async () => {
+    const filter: OrderFilter = {
+      from: from || undefined,
+      to: to || undefined,
+      account: account === "all" ? undefined : account,
+      symbol: symbol || undefined,
+    };
+    const path = await join(directory, `bourso-orders.${format}`);
+    try {
+      const exported: number = await invoke("export_orders_cmd", {
+        path,
+        format,
+        filter,
+      });
+      toast.success(`${exported} order(s) exported`, { description: path });
+    } catch (error) {
+      toast.error("Error exporting orders", { description: `${error}` });
+    }
+  };
+
+  return (
+    <div className="space-y-4">
+      <DialogHeader>
+        <DialogTitle>Export orders</DialogTitle>
+      </DialogHeader>
+      <Select
+        value={format}
+        onValueChange={(v) => setFormat(v as ExportFormat)}
+      >
+        <SelectTrigger>
+          <SelectValue />
+        </SelectTrigger>
+        <SelectContent>
+          <SelectItem value="csv">CSV</SelectItem>
+          <SelectItem value="json">JSON</SelectItem>
+          <SelectItem value="ofx">OFX</SelectItem>
+        </SelectContent>
+      </Select>
+      <Select value={account} onValueChange={setAccount}>
+        <SelectTrigger>
+          <SelectValue />
+        </SelectTrigger>
+        <SelectContent>
+          <SelectItem value="all">All accounts</SelectItem>
+          {accounts.map((a) => (
+            <SelectItem key={a.id} value={a.id}>
+              {getFormattedAccountName(a, accounts)}
+            </SelectItem>
+          ))}
+        </SelectContent>
+      </Select>
+      <Input
+        placeholder="Symbol (all by default)"
+        value={symbol}
+        onChange={(e) => setSymbol(e.target.value)}
+      />
+      <div className="grid grid-cols-2 gap-4">
+        <Input type="date" value={from} onChange={(e) => setFrom(e.target.value)} />
+        <Input type="date" value={to} onChange={(e) => setTo(e.target.value)} />
+      </div>
+      <Input
+        placeholder="Directory"
+        value={directory}
+        onChange={(e) => setDirectory(e.target.value)}
+      />
+      <Button className="w-full" onClick={exportOrders} disabled={!directory}>
+        Export
+      </Button>
+    </div>
+  );
+}
@@ -0,0 +1,114 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { AccountType, ImportSummary, Order } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { DialogHeader, DialogTitle } from "./ui/dialog";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+/**
+ * Import the orders of a Bourso CSV export into the local history
+ */
+export function ImportOrders({
+  accounts,
+  onImported,
+}: {
+  accounts: AccountType[];
+  onImported: (orders: Order[]) => void;
+}) {
+  const [account, setAccount] = useState(accounts[0]?.id ?? "");
+  const [file, setFile] = useState<File | null>(null);
+  const [importing, setImporting] = useState(false);
+  const [skipped, setSkipped] = useState<string[]>([]);
+  // One `ISIN=symbol` per line, e.g. FR0010315770=1rTCW8
+  const [mapping, setMapping] = useState("");
+
+  const symbols = () =>
+    Object.fromEntries(
+      mapping
+        .split("\n")
+        .map((line) => line.split("=").map((part) => part.trim()))
+        .filter(([isin, symbol]) => isin && symbol),
+    );
+
+  const importOrders = async () => {
+    if (!file) return;
+    setImporting(true);
+    try {
+      const content = await file.text();
+      const summary: ImportSummary = await invoke("import_orders_csv", {
+        content,
+        account,
+        symbols: symbols(),
+      });
+      setSkipped(summary.skipped);
+      toast.success(`${summary.imported} order(s) imported`, {
+        description: `${summary.duplicates} already in the history, ${summary.skipped.length} row(s) skipped`,
+      });
+      onImported(await invoke("get_orders_cmd"));
+    } catch (error) {
+      toast.error("Error importing orders", { description: `${error}` });
+    }
+    setImporting(false);
+  };
+
+  return (
+    <div className="space-y-4">
+      <DialogHeader>
+        <DialogTitle>Import orders</DialogTitle>
+      </DialogHeader>
+      <p className="text-sm text-muted-foreground">
+        Export your orders as CSV from the Bourso website and import them to
+        keep them longer than a year. Orders already in the history are
+        ignored. The exports use ISIN codes: give the Bourso symbol of each
+        one, the rows of the other ISIN are skipped.
+      </p>
+      <Select value={account} onValueChange={setAccount}>
+        <SelectTrigger>
+          <SelectValue placeholder="Account of the orders" />
+        </SelectTrigger>
+        <SelectContent>
+          {accounts.map((a) => (
+            <SelectItem key={a.id} value={a.id}>
+              {getFormattedAccountName(a, accounts)}
+            </SelectItem>
+          ))}
+        </SelectContent>
+      </Select>
+      <Input
+        type="file"
+        accept=".csv,text/csv"
+        onChange={(e) => setFile(e.target.files?.[0] ?? null)}
+      />
+      <textarea
+        className="w-full rounded-md border px-3 py-2 text-sm font-mono"
+        rows={3}
+        placeholder="One ISIN=symbol per line, e.g. FR0010315770=1rTCW8"
+        value={mapping}
+        onChange={(e) => setMapping(e.target.value)}
+      />
+      {skipped.length > 0 && (
+        <ul className="text-xs text-muted-foreground max-h-32 overflow-y-auto">
+          {skipped.map((line) => (
+            <li key={line}>{line}</li>
+          ))}
+        </ul>
+      )}
+      <Button
+        className="w-full"
+        onClick={importOrders}
+        disabled={!file || !account || importing}
+      >
+        Import
+      </Button>
+    </div>
+  );
+}
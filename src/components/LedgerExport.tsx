@@ -0,0 +1,124 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { downloadDir, join } from "@tauri-apps/api/path";
+import { toast } from "sonner";
+import { AccountType, LedgerExport as LedgerExportResult, LedgerSettings } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { DialogHeader, DialogTitle } from "./ui/dialog";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+/**
+ * Export the orders, transfers and balances to a Beancount or hledger file
+ */
+export function LedgerExport({ accounts }: { accounts: AccountType[] }) {
+  const [settings, setSettings] = useState<LedgerSettings | null>(null);
+  const [path, setPath] = useState("");
+  const [incremental, setIncremental] = useState(true);
+  const [includeBalances, setIncludeBalances] = useState(true);
+
+  useEffect(() => {
+    invoke<LedgerSettings>("get_ledger_settings").then(setSettings);
+  }, []);
+
+  useEffect(() => {
+    if (!settings || path) return;
+    downloadDir()
+      .then((dir) =>
+        join(dir, settings.format === "beancount" ? "bourso.beancount" : "bourso.journal"),
+      )
+      .then(setPath);
+  }, [settings, path]);
+
+  if (!settings) return null;
+
+  const exportLedger = async () => {
+    try {
+      const accountNames = Object.fromEntries(
+        Object.entries(settings.accounts).filter(([, name]) => name.trim()),
+      );
+      await invoke("set_ledger_settings", {
+        settings: { ...settings, accounts: accountNames },
+      });
+      const result: LedgerExportResult = await invoke("export_ledger", {
+        path,
+        incremental,
+        includeBalances,
+      });
+      toast.success(`${result.entries} entries exported`, {
+        description: `${result.existing} already in the file, ${result.balances} balance(s)`,
+      });
+    } catch (error) {
+      toast.error("Error exporting ledger", { description: `${error}` });
+    }
+  };
+
+  return (
+    <div className="space-y-4">
+      <DialogHeader>
+        <DialogTitle>Accounting export</DialogTitle>
+      </DialogHeader>
+      <Select
+        value={settings.format}
+        onValueChange={(format) =>
+          setSettings({ ...settings, format: format as LedgerSettings["format"] })
+        }
+      >
+        <SelectTrigger>
+          <SelectValue />
+        </SelectTrigger>
+        <SelectContent>
+          <SelectItem value="beancount">Beancount</SelectItem>
+          <SelectItem value="hledger">hledger</SelectItem>
+        </SelectContent>
+      </Select>
+      <div className="space-y-2">
+        <p className="text-sm font-medium">Ledger accounts</p>
+        {accounts.map((account) => (
+          <div key={account.id} className="grid grid-cols-2 gap-2 items-center">
+            <span className="text-sm">
+              {getFormattedAccountName(account, accounts)}
+            </span>
+            <Input
+              placeholder={`Assets:Bourso:${account.id}`}
+              value={settings.accounts[account.id] ?? ""}
+              onChange={(e) =>
+                setSettings({
+                  ...settings,
+                  accounts: { ...settings.accounts, [account.id]: e.target.value },
+                })
+              }
+            />
+          </div>
+        ))}
+      </div>
+      <label className="flex items-center gap-2 text-sm">
+        <input
+          type="checkbox"
+          checked={incremental}
+          onChange={(e) => setIncremental(e.target.checked)}
+        />
+        Only append the new entries
+      </label>
+      <label className="flex items-center gap-2 text-sm">
+        <input
+          type="checkbox"
+          checked={includeBalances}
+          onChange={(e) => setIncludeBalances(e.target.checked)}
+        />
+        Add the current balances as assertions
+      </label>
+      <Input value={path} onChange={(e) => setPath(e.target.value)} />
+      <Button className="w-full" onClick={exportLedger} disabled={!path}>
+        Export
+      </Button>
+    </div>
+  );
+}
@@ -0,0 +1,165 @@
+import { ShieldCheck } from "lucide-react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { Limits } from "@/types";
+
+const toNumber = (value: string) => (value ? parseFloat(value) : undefined);
+const toText = (value?: number) => (value === undefined ? "" : `${value}`);
+
+/**
+ * Hard limits and confirmation thresholds of the orders and transfers
+ */
+export function LimitsSettings() {
+  const [pinSet, setPinSet] = useState(false);
+  const [maxOrderValue, setMaxOrderValue] = useState("");
+  const [maxTransferAmount, setMaxTransferAmount] = useState("");
+  const [confirmOrderValue, setConfirmOrderValue] = useState("");
+  const [confirmTransferAmount, setConfirmTransferAmount] = useState("");
+  // One `symbol=quantity` per line
+  const [maxQuantity, setMaxQuantity] = useState("");
+  const [currentPin, setCurrentPin] = useState("");
+  const [newPin, setNewPin] = useState("");
+  const [removePin, setRemovePin] = useState(false);
+
+  const load = async () => {
+    try {
+      const limits: Limits = await invoke("get_limits");
+      setPinSet(!!limits.pin_set);
+      setMaxOrderValue(toText(limits.max_order_value));
+      setMaxTransferAmount(toText(limits.max_transfer_amount));
+      setConfirmOrderValue(toText(limits.confirm_order_value));
+      setConfirmTransferAmount(toText(limits.confirm_transfer_amount));
+      setMaxQuantity(
+        Object.entries(limits.max_quantity ?? {})
+          .map(([symbol, quantity]) => `${symbol}=${quantity}`)
+          .join("\n"),
+      );
+    } catch (error) {
+      toast.error("Error loading the limits", { description: `${error}` });
+    }
+  };
+
+  useEffect(() => {
+    load();
+  }, []);
+
+  const save = async () => {
+    const limits: Limits = {
+      max_order_value: toNumber(maxOrderValue),
+      max_transfer_amount: toNumber(maxTransferAmount),
+      confirm_order_value: toNumber(confirmOrderValue),
+      confirm_transfer_amount: toNumber(confirmTransferAmount),
+      max_quantity: Object.fromEntries(
+        maxQuantity
+          .split("\n")
+          .map((line) => line.split("=").map((part) => part.trim()))
+          .filter(([symbol, quantity]) => symbol && quantity)
+          .map(([symbol, quantity]) => [symbol, parseInt(quantity)]),
+      ),
+    };
+
+    try {
+      await invoke("set_limits", {
+        limits,
+        currentPin: currentPin || null,
+        // Without a new PIN the stored one is kept, an empty one removes it
+        newPin: removePin ? "" : newPin || null,
+      });
+      setCurrentPin("");
+      setNewPin("");
+      setRemovePin(false);
+      toast.success("Limits saved");
+      await load();
+    } catch (error) {
+      toast.error("Error saving the limits", { description: `${error}` });
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2 text-xl">
+          <ShieldCheck className="h-6 w-6" />
+          Limits
+        </CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <p className="text-sm text-muted-foreground">
+          Orders and transfers above a limit are refused, above a confirmation
+          threshold they need a confirmation, and the PIN when one is set.
+        </p>
+        <div className="grid grid-cols-2 gap-2">
+          <Input
+            type="number"
+            placeholder="Max order value (€)"
+            value={maxOrderValue}
+            onChange={(e) => setMaxOrderValue(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Confirm orders above (€)"
+            value={confirmOrderValue}
+            onChange={(e) => setConfirmOrderValue(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Max transfer amount (€)"
+            value={maxTransferAmount}
+            onChange={(e) => setMaxTransferAmount(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Confirm transfers above (€)"
+            value={confirmTransferAmount}
+            onChange={(e) => setConfirmTransferAmount(e.target.value)}
+          />
+        </div>
+        <textarea
+          className="w-full rounded-md border px-3 py-2 text-sm font-mono"
+          rows={3}
+          placeholder="Max quantity per order, one symbol=quantity per line, e.g. 1rTCW8=10"
+          value={maxQuantity}
+          onChange={(e) => setMaxQuantity(e.target.value)}
+        />
+        <div className="grid grid-cols-2 gap-2">
+          {pinSet && (
+            <Input
+              type="password"
+              placeholder="Current PIN"
+              value={currentPin}
+              onChange={(e) => setCurrentPin(e.target.value)}
+            />
+          )}
+          <Input
+            type="password"
+            placeholder={pinSet ? "New PIN (optional)" : "PIN (optional)"}
+            value={newPin}
+            disabled={removePin}
+            onChange={(e) => setNewPin(e.target.value)}
+          />
+        </div>
+        {pinSet && (
+          <label className="flex items-center gap-2 text-sm">
+            <input
+              type="checkbox"
+              checked={removePin}
+              onChange={(e) => setRemovePin(e.target.checked)}
+            />
+            Remove the PIN
+          </label>
+        )}
+        <Button
+          className="w-full"
+          onClick={save}
+          disabled={pinSet && !currentPin}
+        >
+          Save limits
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,154 @@
+import { useEffect, useMemo, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { Landmark } from "lucide-react";
+import {
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  CartesianGrid,
+  Tooltip,
+  Legend,
+  ResponsiveContainer,
+} from "recharts";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+import { NetWorth as NetWorthData, NetWorthSeries } from "@/types";
+
+type View = "total" | "kinds" | "accounts";
+
+const COLORS = [
+  "#2563eb",
+  "#16a34a",
+  "#dc2626",
+  "#9333ea",
+  "#ea580c",
+  "#0891b2",
+  "#ca8a04",
+  "#db2777",
+];
+
+/**
+ * Net worth over time, from the snapshots taken once a day
+ */
+export function NetWorth({ jobsExecuted }: { jobsExecuted: number }) {
+  const [netWorth, setNetWorth] = useState<NetWorthData | null>(null);
+  const [view, setView] = useState<View>("total");
+
+  useEffect(() => {
+    (async () => {
+      try {
+        setNetWorth(await invoke("get_net_worth"));
+      } catch (error) {
+        toast.error("Error loading the net worth", {
+          description: `${error}`,
+        });
+      }
+    })();
+  }, [jobsExecuted]);
+
+  const series: NetWorthSeries[] = useMemo(() => {
+    if (!netWorth) return [];
+    switch (view) {
+      case "total":
+        return [{ id: "total", label: "Total", points: netWorth.total }];
+      case "kinds":
+        return netWorth.kinds;
+      case "accounts":
+        return netWorth.accounts;
+    }
+  }, [netWorth, view]);
+
+  // One point per date with a key per series
+  const chartData = useMemo(() => {
+    const byDate = new Map<string, { [key: string]: number | string }>();
+    for (const s of series) {
+      for (const point of s.points) {
+        const entry = byDate.get(point.date) ?? { date: point.date };
+        entry[s.id] = point.value;
+        byDate.set(point.date, entry);
+      }
+    }
+    return [...byDate.values()].sort((a, b) =>
+      (a.date as string).localeCompare(b.date as string),
+    );
+  }, [series]);
+
+  const formatDate = (date: string) =>
+    new Date(date).toLocaleDateString("fr-FR", {
+      day: "numeric",
+      month: "short",
+    });
+  const formatEuros = (value: number) => `${value.toFixed(0)}€`;
+
+  return (
+    <Card className="col-span-full">
+      <CardHeader>
+        <div className="flex items-center justify-between">
+          <CardTitle className="flex items-center gap-2 text-xl">
+            <Landmark className="h-6 w-6" />
+            Net worth
+          </CardTitle>
+          <Select value={view} onValueChange={(v) => setView(v as View)}>
+            <SelectTrigger className="w-40">
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="total">Total</SelectItem>
+              <SelectItem value="kinds">Per kind</SelectItem>
+              <SelectItem value="accounts">Per account</SelectItem>
+            </SelectContent>
+          </Select>
+        </div>
+      </CardHeader>
+      <CardContent>
+        {chartData.length === 0 ? (
+          <p className="text-sm text-muted-foreground">
+            No snapshot yet, one is taken each day.
+          </p>
+        ) : (
+          <div className="h-[300px] w-full">
+            <ResponsiveContainer width="100%" height="100%">
+              <LineChart
+                data={chartData}
+                margin={{ top: 5, right: 30, left: 20, bottom: 5 }}
+              >
+                <CartesianGrid strokeDasharray="3 3" className="stroke-muted" />
+                <XAxis
+                  dataKey="date"
+                  tickFormatter={formatDate}
+                  className="text-xs"
+                />
+                <YAxis className="text-xs" tickFormatter={formatEuros} />
+                <Tooltip
+                  labelFormatter={formatDate}
+                  formatter={(value: number) => [`${value.toFixed(2)}€`]}
+                />
+                <Legend />
+                {series.map((s, index) => (
+                  <Line
+                    key={s.id}
+                    type="linear"
+                    dataKey={s.id}
+                    name={s.label}
+                    stroke={COLORS[index % COLORS.length]}
+                    dot={false}
+                    strokeWidth={2}
+                    connectNulls
+                  />
+                ))}
+              </LineChart>
+            </ResponsiveContainer>
+          </div>
+        )}
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,185 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { AccountType, Order, OrderSide } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { DialogHeader, DialogTitle } from "./ui/dialog";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+const toDateTimeInput = (timestamp?: number) =>
+  timestamp
+    ? new Date(timestamp * 1000 - new Date().getTimezoneOffset() * 60000)
+        .toISOString()
+        .slice(0, 16)
+    : "";
+
+/**
+ * Add an order placed outside of the app, or fix an order of the history
+ * when `order` is given
+ */
+export function OrderEditor({
+  accounts,
+  order,
+  onSaved,
+}: {
+  accounts: AccountType[];
+  order?: Order;
+  onSaved: (order: Order) => void;
+}) {
+  const [id, setId] = useState("");
+  const [account, setAccount] = useState(order?.args.account ?? accounts[0]?.id ?? "");
+  const [symbol, setSymbol] = useState(order?.args.symbol ?? "");
+  const [side, setSide] = useState<OrderSide>(order?.args.side ?? "buy");
+  const [quantity, setQuantity] = useState(`${order?.args.quantity ?? ""}`);
+  const [price, setPrice] = useState(`${order?.price ?? ""}`);
+  const [fees, setFees] = useState(`${order?.fees ?? ""}`);
+  const [date, setDate] = useState(toDateTimeInput(order?.timestamp));
+  const [note, setNote] = useState(order?.note ?? "");
+  const [tags, setTags] = useState((order?.tags ?? []).join(", "));
+
+  const save = async () => {
+    const timestamp = Math.floor(new Date(date).getTime() / 1000);
+    const tagList = tags
+      .split(",")
+      .map((t) => t.trim())
+      .filter(Boolean);
+    try {
+      const saved: Order = order
+        ? await invoke("update_order_cmd", {
+            id: order.id,
+            update: {
+              price: parseFloat(price),
+              quantity: parseInt(quantity),
+              timestamp,
+              fees: parseFloat(fees || "0"),
+              note,
+              tags: tagList,
+            },
+          })
+        : await invoke("add_order_cmd", {
+            order: {
+              id: id || undefined,
+              account,
+              symbol,
+              side,
+              quantity: parseInt(quantity),
+              price: parseFloat(price),
+              timestamp,
+              fees: parseFloat(fees || "0"),
+              note: note || undefined,
+              tags: tagList,
+            },
+          });
+      onSaved(saved);
+    } catch (error) {
+      toast.error("Error saving order", { description: `${error}` });
+    }
+  };
+
+  return (
+    <div className="space-y-4">
+      <DialogHeader>
+        <DialogTitle>{order ? `Edit order ${order.id}` : "Add an order"}</DialogTitle>
+      </DialogHeader>
+      {!order && (
+        <>
+          <Input
+            placeholder="Bourso reference (optional)"
+            value={id}
+            onChange={(e) => setId(e.target.value)}
+          />
+          <Select value={account} onValueChange={setAccount}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              {accounts.map((a) => (
+                <SelectItem key={a.id} value={a.id}>
+                  {getFormattedAccountName(a, accounts)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <div className="grid grid-cols-2 gap-4">
+            <Input
+              placeholder="Symbol"
+              value={symbol}
+              onChange={(e) => setSymbol(e.target.value)}
+            />
+            <Select value={side} onValueChange={(v) => setSide(v as OrderSide)}>
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="buy">Buy</SelectItem>
+                <SelectItem value="sell">Sell</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+        </>
+      )}
+      <div className="grid grid-cols-3 gap-4">
+        <Input
+          type="number"
+          placeholder="Quantity"
+          value={quantity}
+          onChange={(e) => setQuantity(e.target.value)}
+        />
+        <Input
+          type="number"
+          step="0.0001"
+          placeholder="Price"
+          value={price}
+          onChange={(e) => setPrice(e.target.value)}
+        />
+        <Input
+          type="number"
+          step="0.01"
+          placeholder="Fees"
+          value={fees}
+          onChange={(e) => setFees(e.target.value)}
+        />
+      </div>
+      <Input
+        type="datetime-local"
+        value={date}
+        onChange={(e) => setDate(e.target.value)}
+      />
+      <Input
+        placeholder="Note"
+        value={note}
+        onChange={(e) => setNote(e.target.value)}
+      />
+      <Input
+        placeholder="Tags, separated by commas"
+        value={tags}
+        onChange={(e) => setTags(e.target.value)}
+      />
+      {order?.edits && order.edits.length > 0 && (
+        <ul className="text-xs text-muted-foreground max-h-32 overflow-y-auto">
+          {order.edits.map((edit, i) => (
+            <li key={i}>
+              {new Date(edit.timestamp * 1000).toLocaleString()}: {edit.field}{" "}
+              {JSON.stringify(edit.from)} → {JSON.stringify(edit.to)}
+            </li>
+          ))}
+        </ul>
+      )}
+      <Button
+        className="w-full"
+        onClick={save}
+        disabled={!quantity || !price || !date || (!order && !symbol)}
+      >
+        Save
+      </Button>
+    </div>
+  );
+}
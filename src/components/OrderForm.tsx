@@ -1,5 +1,5 @@
 import { ShoppingCart } from "lucide-react";
-import { AccountType, AssetData, Order } from "@/types";
+import { AccountType, AssetData, Confirmation, Order, OrderError } from "@/types";
 import {
   Card,
   CardContent,
@@ -7,7 +7,8 @@ import {
   CardHeader,
   CardTitle,
 } from "./ui/card";
-import { getFormattedAccountName } from "@/utils/format";
+import { getFormattedAccountName, orderErrorToString } from "@/utils/format";
+import { askConfirmation } from "@/utils/limits";
 import {
   Select,
   SelectContent,
@@ -70,17 +71,38 @@ export function OrderForm({
   const onSubmit = handleSubmit(async (data) => {
     try {
       setWaitingForOrder(true);
-      const order: Order = await invoke("new_order_cmd", data);
+      let confirmation: Confirmation | null = null;
+      let order: Order | null = null;
+      while (order === null) {
+        try {
+          order = await invoke("new_order_cmd", { ...data, confirmation });
+        } catch (error) {
+          // Orders above a soft limit need a second confirmation or the PIN
+          const orderError = error as OrderError;
+          if (orderError.kind !== "limit") throw error;
+          confirmation = askConfirmation(orderError.limit);
+          if (confirmation === null) throw error;
+        }
+      }
       addNewOrder(order);
-      toast.success(`Order ${order.id} placed successfully`);
-    } catch (error) {
-      console.error(error);
-      if (error instanceof Error) {
-        toast.error("An error occurred while placing the order.", {
-          description: error.message,
+      if (order.estimated_price) {
+        toast.warning(`Order ${order.id} placed without an execution price`, {
+          description: `Recorded at the quote of ${order.price.toFixed(2)}€, check the price on Bourso. Do not place it again.`,
           duration: 15000,
         });
+      } else {
+        toast.success(`Order ${order.id} placed successfully`);
       }
+    } catch (error) {
+      console.error(error);
+      const message =
+        error instanceof Error
+          ? error.message
+          : orderErrorToString(error as OrderError);
+      toast.error("An error occurred while placing the order.", {
+        description: message,
+        duration: 15000,
+      });
     }
     setWaitingForOrder(false);
   });
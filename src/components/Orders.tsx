@@ -1,9 +1,17 @@
-import { HelpCircle, ShoppingCart } from "lucide-react";
+import { HelpCircle, Pencil, ShoppingCart, Trash2 } from "lucide-react";
 import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
 import { Table, TableBody, TableHead, TableHeader, TableRow } from "./ui/table";
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { AccountType, AssetData, Order } from "@/types";
+import {
+  AccountType,
+  AssetData,
+  Order,
+  OrderQuery,
+  OrderPage,
+  OrderSortField,
+  OrderTotals,
+} from "@/types";
 import {
   Tooltip,
   TooltipContent,
@@ -12,8 +20,13 @@ import {
 } from "./ui/tooltip";
 import { Dialog, DialogContent, DialogTrigger } from "./ui/dialog";
 import { OrderForm } from "./OrderForm";
+import { ImportOrders } from "./ImportOrders";
+import { ExportOrders } from "./ExportOrders";
+import { LedgerExport } from "./LedgerExport";
+import { OrderEditor } from "./OrderEditor";
 import { toast, Toaster } from "sonner";
 import { Button } from "./ui/button";
+import { Input } from "./ui/input";
 
 export function Orders({
   accounts,
@@ -25,7 +38,40 @@ export function Orders({
   jobsExecuted: number;
 }) {
   const [orders, setOrders] = useState<Order[]>([]);
+  const [query, setQuery] = useState<OrderQuery>({
+    sort: "date",
+    descending: true,
+  });
+  const [nextCursor, setNextCursor] = useState<string | undefined>();
+  const [totals, setTotals] = useState<OrderTotals | undefined>();
   const [dialogOpen, setDialogOpen] = useState(false);
+  const [importOpen, setImportOpen] = useState(false);
+  const [exportOpen, setExportOpen] = useState(false);
+  const [ledgerOpen, setLedgerOpen] = useState(false);
+  // Order being edited, `null` to add a new one
+  const [editing, setEditing] = useState<Order | null | undefined>(undefined);
+
+  const onOrderSaved = (saved: Order) => {
+    setOrders((orders) =>
+      orders.some((o) => o.id === saved.id)
+        ? orders.map((o) => (o.id === saved.id ? saved : o))
+        : [...orders, saved].sort(
+            (a, b) => (a.timestamp ?? 0) - (b.timestamp ?? 0),
+          ),
+    );
+    setEditing(undefined);
+    toast.success("Order saved", { description: `Order ID: ${saved.id}` });
+  };
+
+  const deleteOrder = async (order: Order) => {
+    if (!confirm(`Delete order ${order.id} from the history?`)) return;
+    try {
+      await invoke("delete_order_cmd", { id: order.id });
+      setOrders((orders) => orders.filter((o) => o.id !== order.id));
+    } catch (error) {
+      toast.error("Error deleting order", { description: `${error}` });
+    }
+  };
 
   const addNewOrder = (order: Order) => {
     setOrders((orders) => [order, ...orders]);
@@ -34,12 +80,27 @@ export function Orders({
     });
   };
 
+  const loadOrders = async (cursor?: string) => {
+    try {
+      const page: OrderPage = await invoke("query_orders", {
+        query: { ...query, cursor },
+      });
+      setOrders((orders) =>
+        cursor ? [...orders, ...page.orders] : page.orders,
+      );
+      setNextCursor(page.next_cursor);
+      setTotals(page.totals);
+    } catch (error) {
+      toast.error("Error loading orders", { description: `${error}` });
+    }
+  };
+
+  const setFilter = (key: keyof OrderQuery, value: string) =>
+    setQuery((query) => ({ ...query, [key]: value || undefined }));
+
   useEffect(() => {
-    (async () => {
-      const orders: Order[] = await invoke("get_orders_cmd");
-      setOrders(orders);
-    })();
-  }, [jobsExecuted]);
+    loadOrders();
+  }, [jobsExecuted, query]);
 
   return (
     <div className="space-y-6">
@@ -57,16 +118,31 @@ export function Orders({
                       <HelpCircle className="h-4 w-4 text-muted-foreground" />
                     </TooltipTrigger>
                     <TooltipContent>
-                      Only the orders passed with this program are shown here.
+                      Only the orders passed with this program or imported
+                      from Bourso are shown here.
                     </TooltipContent>
                   </Tooltip>
                 </TooltipProvider>
               </CardTitle>
-              <DialogTrigger>
-                <Button variant="outline">
-                  Place order
+              <div className="flex items-center gap-2">
+                <Button variant="outline" onClick={() => setEditing(null)}>
+                  Add
+                </Button>
+                <Button variant="outline" onClick={() => setImportOpen(true)}>
+                  Import
+                </Button>
+                <Button variant="outline" onClick={() => setExportOpen(true)}>
+                  Export
                 </Button>
-              </DialogTrigger>
+                <Button variant="outline" onClick={() => setLedgerOpen(true)}>
+                  Ledger
+                </Button>
+                <DialogTrigger>
+                  <Button variant="outline">
+                    Place order
+                  </Button>
+                </DialogTrigger>
+              </div>
             </div>
             <DialogContent>
               <OrderForm
@@ -76,8 +152,127 @@ export function Orders({
               />
             </DialogContent>
           </Dialog>
+          <Dialog open={importOpen} onOpenChange={setImportOpen}>
+            <DialogContent>
+              <ImportOrders accounts={accounts} onImported={() => loadOrders()} />
+            </DialogContent>
+          </Dialog>
+          <Dialog open={exportOpen} onOpenChange={setExportOpen}>
+            <DialogContent>
+              <ExportOrders accounts={accounts} />
+            </DialogContent>
+          </Dialog>
+          <Dialog
+            open={editing !== undefined}
+            onOpenChange={(open) => !open && setEditing(undefined)}
+          >
+            <DialogContent>
+              {editing !== undefined && (
+                <OrderEditor
+                  key={editing?.id ?? "new"}
+                  accounts={accounts}
+                  order={editing ?? undefined}
+                  onSaved={onOrderSaved}
+                />
+              )}
+            </DialogContent>
+          </Dialog>
+          <Dialog open={ledgerOpen} onOpenChange={setLedgerOpen}>
+            <DialogContent>
+              <LedgerExport accounts={accounts} />
+            </DialogContent>
+          </Dialog>
         </CardHeader>
         <CardContent>
+          <div className="mb-4 flex flex-wrap items-center gap-2">
+            <Input
+              className="w-32"
+              placeholder="Symbol"
+              value={query.symbol ?? ""}
+              onChange={(e) => setFilter("symbol", e.target.value)}
+            />
+            <select
+              className="h-9 rounded-md border px-2 text-sm"
+              value={query.account ?? ""}
+              onChange={(e) => setFilter("account", e.target.value)}
+            >
+              <option value="">All accounts</option>
+              {accounts.map((account) => (
+                <option key={account.id} value={account.id}>
+                  {account.name}
+                </option>
+              ))}
+            </select>
+            <select
+              className="h-9 rounded-md border px-2 text-sm"
+              value={query.side ?? ""}
+              onChange={(e) => setFilter("side", e.target.value)}
+            >
+              <option value="">Buy & sell</option>
+              <option value="buy">Buy</option>
+              <option value="sell">Sell</option>
+            </select>
+            <select
+              className="h-9 rounded-md border px-2 text-sm"
+              value={query.source ?? ""}
+              onChange={(e) => setFilter("source", e.target.value)}
+            >
+              <option value="">All sources</option>
+              <option value="app">App</option>
+              <option value="dca">DCA</option>
+              <option value="manual">Manual</option>
+              <option value="imported">Imported</option>
+            </select>
+            <Input
+              className="w-40"
+              type="date"
+              value={query.from ?? ""}
+              onChange={(e) => setFilter("from", e.target.value)}
+            />
+            <Input
+              className="w-40"
+              type="date"
+              value={query.to ?? ""}
+              onChange={(e) => setFilter("to", e.target.value)}
+            />
+            <select
+              className="h-9 rounded-md border px-2 text-sm"
+              value={query.sort}
+              onChange={(e) =>
+                setQuery((query) => ({
+                  ...query,
+                  sort: e.target.value as OrderSortField,
+                }))
+              }
+            >
+              <option value="date">Date</option>
+              <option value="symbol">Symbol</option>
+              <option value="quantity">Quantity</option>
+              <option value="price">Price</option>
+              <option value="amount">Amount</option>
+            </select>
+            <Button
+              variant="outline"
+              onClick={() =>
+                setQuery((query) => ({
+                  ...query,
+                  descending: !query.descending,
+                }))
+              }
+            >
+              {query.descending ? "Descending" : "Ascending"}
+            </Button>
+          </div>
+          {totals && (
+            <div className="mb-4 text-sm text-muted-foreground">
+              {totals.count} orders, {totals.bought_quantity} shares bought
+              for {totals.invested.toFixed(2)}€, {totals.sold_quantity} sold
+              for {totals.proceeds.toFixed(2)}€
+              {totals.average_price !== undefined &&
+                totals.average_price !== null &&
+                `, average price ${totals.average_price.toFixed(2)}€`}
+            </div>
+          )}
           <Table>
             <TableHeader>
               <TableRow>
@@ -88,6 +283,8 @@ export function Orders({
                 <TableHead>Price</TableHead>
                 <TableHead>Type</TableHead>
                 <TableHead>Account</TableHead>
+                <TableHead>Source</TableHead>
+                <TableHead></TableHead>
               </TableRow>
             </TableHeader>
             <TableBody>
@@ -100,10 +297,49 @@ export function Orders({
                   <td>{order.price}</td>
                   <td>{order.args.side}</td>
                   <td>{order.args.account}</td>
+                  <td>
+                    {order.source ?? "app"}
+                    {order.note && (
+                      <div className="text-xs text-muted-foreground">
+                        {order.note}
+                      </div>
+                    )}
+                    {order.tags && order.tags.length > 0 && (
+                      <div className="text-xs text-muted-foreground">
+                        {order.tags.map((tag) => `#${tag}`).join(" ")}
+                      </div>
+                    )}
+                  </td>
+                  <td>
+                    <div className="flex items-center">
+                      <Button
+                        variant="ghost"
+                        size="icon"
+                        onClick={() => setEditing(order)}
+                      >
+                        <Pencil className="h-4 w-4" />
+                      </Button>
+                      <Button
+                        variant="ghost"
+                        size="icon"
+                        onClick={() => deleteOrder(order)}
+                        className="text-destructive"
+                      >
+                        <Trash2 className="h-4 w-4" />
+                      </Button>
+                    </div>
+                  </td>
                 </TableRow>
               ))}
             </TableBody>
           </Table>
+          {nextCursor && (
+            <div className="mt-4 flex justify-center">
+              <Button variant="outline" onClick={() => loadOrders(nextCursor)}>
+                Load more
+              </Button>
+            </div>
+          )}
         </CardContent>
       </Card>
     </div>
@@ -0,0 +1,120 @@
+import { PriceGuard } from "@/types";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+const DEFAULT_GUARD: PriceGuard = {
+  max_move_percent: 5,
+  reference: { kind: "previous_close" },
+  action: "skip",
+  max_slippage_percent: 1,
+};
+
+const toNumber = (value: string) => (value ? parseFloat(value) : undefined);
+
+/**
+ * Price guard of a job: no order when the price moved too much from its
+ * reference, and a limit price at the maximum slippage
+ */
+export function PriceGuardEditor({
+  guard,
+  onChange,
+}: {
+  guard?: PriceGuard;
+  onChange: (guard?: PriceGuard) => void;
+}) {
+  const update = (changes: Partial<PriceGuard>) =>
+    guard && onChange({ ...guard, ...changes });
+
+  return (
+    <div className="space-y-2">
+      <label className="flex items-center gap-2 text-sm">
+        <input
+          type="checkbox"
+          checked={!!guard}
+          onChange={(e) =>
+            onChange(e.target.checked ? DEFAULT_GUARD : undefined)
+          }
+        />
+        Guard against abnormal prices
+      </label>
+      {guard && (
+        <div className="grid grid-cols-2 gap-2">
+          <Input
+            type="number"
+            placeholder="Max move from the reference (%)"
+            value={guard.max_move_percent}
+            onChange={(e) =>
+              update({ max_move_percent: parseFloat(e.target.value) })
+            }
+          />
+          <Select
+            value={guard.reference.kind}
+            onValueChange={(kind) =>
+              update({
+                reference:
+                  kind === "trailing_average"
+                    ? { kind: "trailing_average", days: 20 }
+                    : { kind: "previous_close" },
+              })
+            }
+          >
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="previous_close">
+                From the previous close
+              </SelectItem>
+              <SelectItem value="trailing_average">
+                From the moving average
+              </SelectItem>
+            </SelectContent>
+          </Select>
+          {guard.reference.kind === "trailing_average" && (
+            <Input
+              type="number"
+              placeholder="Average of the last closes"
+              value={guard.reference.days}
+              onChange={(e) =>
+                update({
+                  reference: {
+                    kind: "trailing_average",
+                    days: parseInt(e.target.value),
+                  },
+                })
+              }
+            />
+          )}
+          <Select
+            value={guard.action ?? "skip"}
+            onValueChange={(action) =>
+              update({ action: action as PriceGuard["action"] })
+            }
+          >
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="skip">Skip until the next period</SelectItem>
+              <SelectItem value="postpone">Retry on the next run</SelectItem>
+            </SelectContent>
+          </Select>
+          <Input
+            type="number"
+            placeholder="Max slippage, limit price (%)"
+            value={guard.max_slippage_percent ?? ""}
+            onChange={(e) =>
+              update({ max_slippage_percent: toNumber(e.target.value) })
+            }
+          />
+        </div>
+      )}
+    </div>
+  );
+}
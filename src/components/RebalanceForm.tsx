@@ -0,0 +1,216 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import {
+  AccountType,
+  Job,
+  Rebalance,
+  RebalanceReport,
+  Weight,
+  WeeklyMonthly,
+} from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { Table, TableBody, TableHead, TableHeader, TableRow } from "./ui/table";
+import { WeightsEditor } from "./WeightsEditor";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+/**
+ * Target weights of a trading account, with a drift report and a job
+ * getting back to the targets
+ */
+export function RebalanceForm({
+  accounts,
+  onCreated,
+}: {
+  accounts: AccountType[];
+  onCreated: (job: Job) => void;
+}) {
+  const tradingAccounts = accounts.filter((a) => a.kind === "Trading");
+  const [account, setAccount] = useState(tradingAccounts[0]?.id ?? "");
+  const [targets, setTargets] = useState<Weight[]>([
+    { symbol: "", weight: 100 },
+  ]);
+  const [threshold, setThreshold] = useState("5");
+  const [scheduleType, setScheduleType] = useState("monthly");
+  const [buyOnly, setBuyOnly] = useState(false);
+  const [contribution, setContribution] = useState("");
+  const [dryRun, setDryRun] = useState(true);
+  const [report, setReport] = useState<RebalanceReport | null>(null);
+
+  const rebalance = (): Rebalance => ({
+    account,
+    targets: targets.filter((t) => t.symbol),
+    threshold: parseFloat(threshold || "0"),
+    buy_only: buyOnly,
+    contribution:
+      buyOnly && contribution ? parseFloat(contribution) : undefined,
+    dry_run: dryRun,
+  });
+
+  const loadReport = async () => {
+    try {
+      setReport(
+        await invoke("get_rebalance_report", { rebalance: rebalance() }),
+      );
+    } catch (error) {
+      toast.error("Error computing the drift", { description: `${error}` });
+    }
+  };
+
+  const create = async () => {
+    const schedule: WeeklyMonthly | "daily" =
+      scheduleType === "weekly"
+        ? { weekly: { day: 1 } }
+        : scheduleType === "monthly"
+          ? { monthly: { day: 1 } }
+          : "daily";
+    const job: Job = {
+      id: `${scheduleType}rebalance_${threshold}_${account}`,
+      schedule,
+      last_run: Math.floor(Date.now() / 1000),
+      command: { rebalance: rebalance() },
+    };
+
+    try {
+      await invoke("add_scheduled_job", { job });
+      onCreated(job);
+      toast("Rebalancing scheduled");
+    } catch (error) {
+      toast.error("Error scheduling the rebalancing", {
+        description: `${error}`,
+      });
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Rebalance</CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <p className="text-sm text-muted-foreground">
+          Trade back to the target weights when an asset drifts by more than
+          the threshold. In buy-only mode, the contributions only buy the
+          underweight assets.
+        </p>
+        <div className="grid grid-cols-2 gap-2">
+          <Select value={account} onValueChange={setAccount}>
+            <SelectTrigger>
+              <SelectValue placeholder="Account" />
+            </SelectTrigger>
+            <SelectContent>
+              {tradingAccounts.map((a) => (
+                <SelectItem key={a.id} value={a.id}>
+                  {getFormattedAccountName(a, accounts)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Input
+            type="number"
+            placeholder="Drift threshold (points)"
+            value={threshold}
+            onChange={(e) => setThreshold(e.target.value)}
+          />
+          <Select value={scheduleType} onValueChange={setScheduleType}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="daily">Every day</SelectItem>
+              <SelectItem value="weekly">Every week</SelectItem>
+              <SelectItem value="monthly">Every month</SelectItem>
+            </SelectContent>
+          </Select>
+          {buyOnly && (
+            <Input
+              type="number"
+              placeholder="Contribution (€), cash available if empty"
+              value={contribution}
+              onChange={(e) => setContribution(e.target.value)}
+            />
+          )}
+        </div>
+        <WeightsEditor weights={targets} onChange={setTargets} />
+        <div className="flex gap-4 text-sm">
+          <label className="flex items-center gap-2">
+            <input
+              type="checkbox"
+              checked={buyOnly}
+              onChange={(e) => setBuyOnly(e.target.checked)}
+            />
+            Buy only
+          </label>
+          <label className="flex items-center gap-2">
+            <input
+              type="checkbox"
+              checked={dryRun}
+              onChange={(e) => setDryRun(e.target.checked)}
+            />
+            Dry run: only notify the orders
+          </label>
+        </div>
+        {report && (
+          <div className="space-y-2">
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>Asset</TableHead>
+                  <TableHead>Value</TableHead>
+                  <TableHead>Weight</TableHead>
+                  <TableHead>Target</TableHead>
+                  <TableHead>Drift</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {report.drifts.map((drift) => (
+                  <TableRow key={drift.symbol}>
+                    <td>{drift.symbol}</td>
+                    <td>{drift.value.toFixed(2)}€</td>
+                    <td>{drift.weight.toFixed(2)}%</td>
+                    <td>{drift.target.toFixed(2)}%</td>
+                    <td>{drift.drift.toFixed(2)}</td>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+            <p className="text-sm text-muted-foreground">
+              {report.orders.length === 0
+                ? `Within ${threshold} points of the targets`
+                : report.orders
+                    .map(
+                      (o) =>
+                        `${o.side} ${o.quantity} ${o.symbol} at ${o.price.toFixed(2)}€`,
+                    )
+                    .join(", ")}
+            </p>
+          </div>
+        )}
+        <div className="grid grid-cols-2 gap-2">
+          <Button
+            variant="outline"
+            onClick={loadReport}
+            disabled={!account || !targets.some((t) => t.symbol)}
+          >
+            Drift report
+          </Button>
+          <Button
+            onClick={create}
+            disabled={!account || !targets.some((t) => t.symbol)}
+          >
+            Schedule rebalancing
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,93 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { Discrepancy, DiscrepancyCause } from "@/types";
+import { DialogHeader, DialogTitle } from "./ui/dialog";
+import { Button } from "./ui/button";
+
+function causeToString(cause: DiscrepancyCause): string {
+  switch (cause.kind) {
+    case "missing_orders":
+      return "Shares bought on the website or before using the app";
+    case "sold_outside":
+      return "Shares sold or transferred on the website";
+    case "split":
+      return `Split with a ratio of ${cause.ratio}`;
+    case "recorded_split":
+      return `Split ${cause.event_id} not applied on Bourso yet`;
+    case "partial_fill":
+      return `Order ${cause.order_id} only executed ${cause.quantity} share(s)`;
+    case "unsettled":
+      return `Order(s) ${cause.order_ids.join(", ")} not settled yet`;
+  }
+}
+
+/**
+ * Compare the positions of the history with the ones held on Bourso
+ */
+export function Reconcile({ onCorrected }: { onCorrected: () => void }) {
+  const [discrepancies, setDiscrepancies] = useState<Discrepancy[]>();
+
+  const load = async () => {
+    try {
+      setDiscrepancies(await invoke("reconcile_positions"));
+    } catch (error) {
+      toast.error("Error reconciling positions", { description: `${error}` });
+    }
+  };
+
+  useEffect(() => {
+    load();
+  }, []);
+
+  const correct = async (discrepancy: Discrepancy) => {
+    try {
+      await invoke("add_order_cmd", { order: discrepancy.correction });
+      toast.success(`${discrepancy.symbol} corrected`);
+      onCorrected();
+      await load();
+    } catch (error) {
+      toast.error("Error adding the order", { description: `${error}` });
+    }
+  };
+
+  return (
+    <div className="space-y-4">
+      <DialogHeader>
+        <DialogTitle>Reconcile with Bourso</DialogTitle>
+      </DialogHeader>
+      {discrepancies === undefined && <p>Loading…</p>}
+      {discrepancies?.length === 0 && (
+        <p className="text-sm text-muted-foreground">
+          The history matches the positions held on Bourso
+        </p>
+      )}
+      {discrepancies?.map((discrepancy) => (
+        <div
+          key={`${discrepancy.account}-${discrepancy.symbol}`}
+          className="space-y-1 rounded-md border p-2"
+        >
+          <div className="font-medium">
+            {discrepancy.label || discrepancy.symbol} ({discrepancy.account})
+          </div>
+          <div className="text-sm">
+            History: {discrepancy.local_quantity}, Bourso:{" "}
+            {discrepancy.bourso_quantity}
+          </div>
+          <ul className="list-disc pl-4 text-sm text-muted-foreground">
+            {discrepancy.causes.map((cause) => (
+              <li key={cause.kind}>{causeToString(cause)}</li>
+            ))}
+          </ul>
+          {discrepancy.correction && (
+            <Button variant="outline" onClick={() => correct(discrepancy)}>
+              Add a {discrepancy.correction.side} of{" "}
+              {discrepancy.correction.quantity} at{" "}
+              {discrepancy.correction.price.toFixed(2)}€
+            </Button>
+          )}
+        </div>
+      ))}
+    </div>
+  );
+}
@@ -0,0 +1,116 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { AccountType, Job, Weight } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import { WeightsEditor } from "./WeightsEditor";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+/**
+ * Daily job buying securities with the cash credited to a trading account,
+ * e.g. the dividends of distributing ETFs
+ */
+export function ReinvestForm({
+  accounts,
+  onCreated,
+}: {
+  accounts: AccountType[];
+  onCreated: (job: Job) => void;
+}) {
+  const tradingAccounts = accounts.filter((a) => a.kind === "Trading");
+  const [account, setAccount] = useState(tradingAccounts[0]?.id ?? "");
+  const [threshold, setThreshold] = useState("50");
+  const [targets, setTargets] = useState<Weight[]>([
+    { symbol: "", weight: 100 },
+  ]);
+  const [dryRun, setDryRun] = useState(true);
+
+  const create = async () => {
+    const job: Job = {
+      id: `dailyreinvest_${threshold}_${account}`,
+      schedule: "daily",
+      last_run: 0,
+      command: {
+        reinvest: {
+          account,
+          threshold: parseFloat(threshold),
+          targets: targets.filter((t) => t.symbol),
+          dry_run: dryRun,
+        },
+      },
+    };
+
+    try {
+      await invoke("add_scheduled_job", { job });
+      onCreated(job);
+      toast("Reinvestment scheduled", {
+        description:
+          "The cash of the account is recorded on the first run, the new cash is reinvested from the next ones",
+      });
+    } catch (error) {
+      toast.error("Error scheduling the reinvestment", {
+        description: `${error}`,
+      });
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Reinvest new cash</CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <p className="text-sm text-muted-foreground">
+          Every day, the cash of the account is compared with the previous
+          run, net of the transfers and orders made by the app. New cash above
+          the threshold, such as dividends, is split across the assets below.
+        </p>
+        <div className="grid grid-cols-2 gap-2">
+          <Select value={account} onValueChange={setAccount}>
+            <SelectTrigger>
+              <SelectValue placeholder="Account" />
+            </SelectTrigger>
+            <SelectContent>
+              {tradingAccounts.map((a) => (
+                <SelectItem key={a.id} value={a.id}>
+                  {getFormattedAccountName(a, accounts)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Input
+            type="number"
+            placeholder="Threshold (€)"
+            value={threshold}
+            onChange={(e) => setThreshold(e.target.value)}
+          />
+        </div>
+        <WeightsEditor weights={targets} onChange={setTargets} />
+        <label className="flex items-center gap-2 text-sm">
+          <input
+            type="checkbox"
+            checked={dryRun}
+            onChange={(e) => setDryRun(e.target.checked)}
+          />
+          Dry run: only notify the orders that would be placed
+        </label>
+        <Button
+          className="w-full"
+          onClick={create}
+          disabled={!account || !targets.some((t) => t.symbol)}
+        >
+          Schedule reinvestment
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
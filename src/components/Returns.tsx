@@ -0,0 +1,116 @@
+import { Activity } from "lucide-react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Table, TableBody, TableHead, TableHeader, TableRow } from "./ui/table";
+import { Input } from "./ui/input";
+import { AccountType, Performance, ScopePerformance } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+
+const percent = (value?: number) =>
+  value === undefined || value === null ? "-" : `${(value * 100).toFixed(2)}%`;
+const euros = (value: number) => `${value.toFixed(2)}€`;
+
+/**
+ * Time and money-weighted returns, volatility and drawdown of the
+ * portfolio, its accounts and its positions
+ */
+export function Returns({
+  accounts,
+  jobsExecuted,
+}: {
+  accounts: AccountType[];
+  jobsExecuted: number;
+}) {
+  const [performance, setPerformance] = useState<Performance | null>(null);
+  const [from, setFrom] = useState("");
+  const [to, setTo] = useState("");
+
+  useEffect(() => {
+    (async () => {
+      try {
+        setPerformance(
+          await invoke("get_performance", {
+            from: from || null,
+            to: to || null,
+          }),
+        );
+      } catch (error) {
+        toast.error("Error computing the performance", {
+          description: `${error}`,
+        });
+      }
+    })();
+  }, [jobsExecuted, from, to]);
+
+  const label = (scope: ScopePerformance) => {
+    if (!scope.account) return scope.label;
+    const account = accounts.find((a) => a.id === scope.account);
+    const accountName = account
+      ? getFormattedAccountName(account, accounts)
+      : scope.account;
+    return scope.symbol ? `${scope.symbol} (${accountName})` : accountName;
+  };
+
+  const rows = performance
+    ? [performance.portfolio, ...performance.accounts, ...performance.positions]
+    : [];
+
+  return (
+    <Card className="col-span-full">
+      <CardHeader>
+        <div className="flex items-center justify-between">
+          <CardTitle className="flex items-center gap-2 text-xl">
+            <Activity className="h-6 w-6" />
+            Returns
+          </CardTitle>
+          <div className="flex gap-2">
+            <Input
+              className="w-40"
+              type="date"
+              value={from}
+              onChange={(e) => setFrom(e.target.value)}
+            />
+            <Input
+              className="w-40"
+              type="date"
+              value={to}
+              onChange={(e) => setTo(e.target.value)}
+            />
+          </div>
+        </div>
+      </CardHeader>
+      <CardContent>
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>Scope</TableHead>
+              <TableHead>Value</TableHead>
+              <TableHead>Contributions</TableHead>
+              <TableHead>Gain</TableHead>
+              <TableHead>TWR</TableHead>
+              <TableHead>XIRR</TableHead>
+              <TableHead>Volatility</TableHead>
+              <TableHead>Max drawdown</TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {rows.map((scope) => (
+              <TableRow key={`${scope.account}-${scope.symbol}`}>
+                <td>{label(scope)}</td>
+                <td>{euros(scope.end_value)}</td>
+                <td>{euros(scope.net_contributions)}</td>
+                <td>{euros(scope.gain)}</td>
+                <td>{percent(scope.twr)}</td>
+                <td>{percent(scope.xirr)}</td>
+                <td>{percent(scope.volatility)}</td>
+                <td>{percent(scope.max_drawdown)}</td>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      </CardContent>
+    </Card>
+  );
+}
@@ -1,7 +1,19 @@
 import React, { useEffect, useState } from "react";
-import { AccountType, AssetData, Job, WeeklyMonthly } from "../types";
+import {
+  AccountType,
+  AssetData,
+  Job,
+  PriceGuard,
+  WeeklyMonthly,
+} from "../types";
 import { Clock, HelpCircle, Info, Settings, Trash2 } from "lucide-react";
 import { getFormattedAccountName } from "@/utils/format";
+import {
+  commandToString,
+  jobOutcomeToString,
+  splitProgressToString,
+  triggerToString,
+} from "@/utils/jobUtils";
 import {
   DialogContent,
   DialogFooter,
@@ -10,6 +22,13 @@ import {
 } from "./ui/dialog";
 import { Button } from "./ui/button";
 import { Input } from "./ui/input";
+import { Backtest } from "./Backtest";
+import { ReinvestForm } from "./ReinvestForm";
+import { BasketForm } from "./BasketForm";
+import { RebalanceForm } from "./RebalanceForm";
+import { PriceGuardEditor } from "./PriceGuardEditor";
+import { TriggerForm } from "./TriggerForm";
+import { ValueAveragingForm } from "./ValueAveragingForm";
 import {
   Select,
   SelectContent,
@@ -73,6 +92,15 @@ export function SetupDca({
   //
   // By default, we use quantity
   const [amountInsteadOfQuantity, setAmountInsteadOfQuantity] = useState(false);
+  // Carry the unspent cash of amount-based DCA over to the next run
+  const [carryOver, setCarryOver] = useState(false);
+  // Account to top up the trading account from when cash is missing
+  const [topUpFrom, setTopUpFrom] = useState<string>("none");
+  // Spread an amount over several slices instead of a recurring DCA
+  const [slices, setSlices] = useState(1);
+  // Minutes between two slices, empty for one slice per scheduled session
+  const [sliceInterval, setSliceInterval] = useState("");
+  const [priceGuard, setPriceGuard] = useState<PriceGuard | undefined>();
 
   const formSchema = z.object({
     symbol: z.string().nonempty("Asset is required"),
@@ -98,7 +126,13 @@ export function SetupDca({
     const getDcaJobs = async () => {
       const jobs: Job[] = await invoke("get_scheduled_jobs");
       const filtered = jobs.filter(
-        (j) => typeof j.command.order !== "undefined",
+        (j) =>
+          typeof j.command.order !== "undefined" ||
+          typeof j.command.split !== "undefined" ||
+          typeof j.command.reinvest !== "undefined" ||
+          typeof j.command.basket !== "undefined" ||
+          typeof j.command.rebalance !== "undefined" ||
+          typeof j.command.value_averaging !== "undefined",
       );
       setJobs(filtered);
     };
@@ -118,6 +152,7 @@ export function SetupDca({
 
   const onSubmit = handleSubmit(async (data) => {
     setCreatingJob(true);
+    const split = amountInsteadOfQuantity && slices > 1;
     const job: Job = {
       schedule:
         data.scheduleType === "weekly"
@@ -125,17 +160,33 @@ export function SetupDca({
           : data.scheduleType === "monthly"
             ? { monthly: { day: 1 } }
             : "daily",
-      command: {
-        order: {
-          side: "buy",
-          symbol: data.symbol,
-          account: data.accountId,
-          amount: amountInsteadOfQuantity ? data.amount : undefined,
-          quantity: amountInsteadOfQuantity ? undefined : data.amount,
-        },
-      },
-      id: `${data.scheduleType}order_buy_${data.amount}_${data.symbol}`,
+      command: split
+        ? {
+            split: {
+              side: "buy",
+              symbol: data.symbol,
+              account: data.accountId,
+              total_amount: data.amount,
+              slices,
+              interval_minutes: sliceInterval
+                ? parseInt(sliceInterval)
+                : undefined,
+            },
+          }
+        : {
+            order: {
+              side: "buy",
+              symbol: data.symbol,
+              account: data.accountId,
+              amount: amountInsteadOfQuantity ? data.amount : undefined,
+              quantity: amountInsteadOfQuantity ? undefined : data.amount,
+            },
+          },
+      id: `${data.scheduleType}${split ? "split" : "order"}_buy_${data.amount}_${data.symbol}`,
       last_run: Math.floor(Date.now() / 1000),
+      carry_over: amountInsteadOfQuantity && !split && carryOver,
+      top_up_from: topUpFrom === "none" ? undefined : topUpFrom,
+      price_guard: priceGuard,
     };
 
     try {
@@ -346,6 +397,74 @@ export function SetupDca({
                   />
                 </div>
 
+                {amountInsteadOfQuantity && (
+                  <div className="grid grid-cols-2 gap-4">
+                    <div className="space-y-2">
+                      <p className="text-sm font-medium">Split into slices</p>
+                      <Input
+                        type="number"
+                        min={1}
+                        value={slices}
+                        onChange={(e) =>
+                          setSlices(Math.max(1, parseInt(e.target.value) || 1))
+                        }
+                      />
+                    </div>
+                    {slices > 1 && (
+                      <div className="space-y-2">
+                        <p className="text-sm font-medium">
+                          Minutes between slices
+                        </p>
+                        <Input
+                          type="number"
+                          min={5}
+                          placeholder="One slice per session"
+                          value={sliceInterval}
+                          onChange={(e) => setSliceInterval(e.target.value)}
+                        />
+                      </div>
+                    )}
+                  </div>
+                )}
+
+                {amountInsteadOfQuantity && slices === 1 && (
+                  <label className="flex items-center gap-2 text-sm">
+                    <input
+                      type="checkbox"
+                      checked={carryOver}
+                      onChange={(e) => setCarryOver(e.target.checked)}
+                    />
+                    Carry unspent cash over to the next DCA
+                  </label>
+                )}
+
+                <div className="space-y-2">
+                  <p className="text-sm font-medium">
+                    When cash is missing on the account
+                  </p>
+                  <Select value={topUpFrom} onValueChange={setTopUpFrom}>
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="none">Skip the DCA</SelectItem>
+                      {accounts
+                        .filter((a) => a.id !== form.watch("accountId"))
+                        .map((account) => (
+                          <SelectItem key={account.id} value={account.id}>
+                            Top up from{" "}
+                            {getFormattedAccountName(account, accounts)}
+                          </SelectItem>
+                        ))}
+                    </SelectContent>
+                  </Select>
+                </div>
+
+                <PriceGuardEditor
+                  guard={priceGuard}
+                  onChange={setPriceGuard}
+                />
+
                 <Separator />
                 <p className="text-sm text-muted-foreground">
                   The DCA will be made at the frequency below if your
@@ -449,6 +568,29 @@ export function SetupDca({
                         </>
                       )}
                     </p>
+                    {!(amountInsteadOfQuantity && slices > 1) && (
+                      <Backtest
+                        schedule={
+                          form.watch("scheduleType") === "weekly"
+                            ? { weekly: { day: 1 } }
+                            : form.watch("scheduleType") === "monthly"
+                              ? { monthly: { day: 1 } }
+                              : "daily"
+                        }
+                        order={{
+                          side: "buy",
+                          symbol: form.watch("symbol"),
+                          account: form.watch("accountId"),
+                          amount: amountInsteadOfQuantity
+                            ? form.watch("amount")
+                            : undefined,
+                          quantity: amountInsteadOfQuantity
+                            ? undefined
+                            : form.watch("amount"),
+                        }}
+                        carryOver={amountInsteadOfQuantity && carryOver}
+                      />
+                    )}
                   </div>
                 )}
                 <Button type="submit" className="w-full" disabled={creatingJob}>
@@ -459,6 +601,31 @@ export function SetupDca({
           </CardContent>
         </Card>
 
+        <TriggerForm
+          accounts={accounts}
+          onCreated={(job) => setJobs((prev) => [...prev, job])}
+        />
+
+        <BasketForm
+          accounts={accounts}
+          onCreated={(job) => setJobs((prev) => [...prev, job])}
+        />
+
+        <ValueAveragingForm
+          accounts={accounts}
+          onCreated={(job) => setJobs((prev) => [...prev, job])}
+        />
+
+        <RebalanceForm
+          accounts={accounts}
+          onCreated={(job) => setJobs((prev) => [...prev, job])}
+        />
+
+        <ReinvestForm
+          accounts={accounts}
+          onCreated={(job) => setJobs((prev) => [...prev, job])}
+        />
+
         <Separator />
 
         <div className="space-y-4">
@@ -481,7 +648,17 @@ export function SetupDca({
                   </TableHeader>
                   <TableBody>
                     {jobs.map(
-                      ({ id, schedule, command: { order }, last_run }) => {
+                      ({
+                        id,
+                        schedule,
+                        command,
+                        last_run,
+                        carry_over,
+                        leftover,
+                        last_outcome,
+                        trigger,
+                      }) => {
+                        const { order, split } = command;
                         if (order) {
                           return (
                             <TableRow key={id}>
@@ -497,6 +674,30 @@ export function SetupDca({
                               <TableCell>
                                 {order!.quantity}
                                 {order!.amount && `${order!.amount}€`}
+                                {carry_over && (
+                                  <div className="text-xs text-muted-foreground">
+                                    Carried over: {(leftover ?? 0).toFixed(2)}€
+                                  </div>
+                                )}
+                                {trigger && (
+                                  <div className="text-xs text-muted-foreground">
+                                    {triggerToString(trigger)}
+                                    {trigger.evaluations
+                                      ?.slice(-3)
+                                      .reverse()
+                                      .map((evaluation) => (
+                                        <div key={evaluation.timestamp}>
+                                          {new Date(
+                                            evaluation.timestamp * 1000,
+                                          ).toLocaleDateString()}
+                                          {evaluation.fired
+                                            ? " fired: "
+                                            : ": "}
+                                          {evaluation.reason}
+                                        </div>
+                                      ))}
+                                  </div>
+                                )}
                               </TableCell>
                               <TableCell className="font-mono text-sm">
                                 {schedule === "daily"
@@ -510,6 +711,11 @@ export function SetupDca({
                                   <Clock className="h-3 w-3" />
                                   {formatNextRun(last_run, schedule)}
                                 </div>
+                                {last_outcome && (
+                                  <div className="text-xs">
+                                    {jobOutcomeToString(last_outcome)}
+                                  </div>
+                                )}
                               </TableCell>
                               <TableCell>
                                 <div className="flex items-center gap-2">
@@ -526,6 +732,97 @@ export function SetupDca({
                             </TableRow>
                           );
                         }
+                        if (split) {
+                          return (
+                            <TableRow key={id}>
+                              <TableCell className="font-medium">
+                                {split.symbol}
+                              </TableCell>
+                              <TableCell>
+                                {
+                                  accounts.find((a) => a.id === split.account)
+                                    ?.name
+                                }
+                              </TableCell>
+                              <TableCell>
+                                {split.total_amount}€
+                                <div className="text-xs text-muted-foreground">
+                                  {splitProgressToString(split)}
+                                </div>
+                              </TableCell>
+                              <TableCell className="font-mono text-sm">
+                                {split.interval_minutes
+                                  ? `every ${split.interval_minutes} min`
+                                  : schedule === "daily"
+                                    ? schedule
+                                    : schedule.monthly
+                                      ? "monthly"
+                                      : "weekly"}
+                              </TableCell>
+                              <TableCell className="text-muted-foreground">
+                                {(split.executed_slices ?? 0) >=
+                                split.slices ? (
+                                  "Completed"
+                                ) : (
+                                  <div className="flex items-center gap-1">
+                                    <Clock className="h-3 w-3" />
+                                    {formatNextRun(last_run, schedule)}
+                                  </div>
+                                )}
+                                {last_outcome && (
+                                  <div className="text-xs">
+                                    {jobOutcomeToString(last_outcome)}
+                                  </div>
+                                )}
+                              </TableCell>
+                              <TableCell>
+                                <Button
+                                  variant="ghost"
+                                  size="icon"
+                                  onClick={() => deleteDcaScheduledJob(id)}
+                                  className="text-destructive"
+                                >
+                                  <Trash2 className="h-4 w-4" />
+                                </Button>
+                              </TableCell>
+                            </TableRow>
+                          );
+                        }
+                        return (
+                          <TableRow key={id}>
+                            <TableCell className="font-medium" colSpan={3}>
+                              {commandToString(command)}
+                            </TableCell>
+                            <TableCell className="font-mono text-sm">
+                              {schedule === "daily"
+                                ? schedule
+                                : schedule.monthly
+                                  ? "monthly"
+                                  : "weekly"}
+                            </TableCell>
+                            <TableCell className="text-muted-foreground">
+                              <div className="flex items-center gap-1">
+                                <Clock className="h-3 w-3" />
+                                {formatNextRun(last_run, schedule)}
+                              </div>
+                              {last_outcome && (
+                                <div className="text-xs">
+                                  {jobOutcomeToString(last_outcome)}
+                                </div>
+                              )}
+                            </TableCell>
+                            <TableCell>
+                              <Button
+                                variant="ghost"
+                                size="icon"
+                                onClick={() => deleteDcaScheduledJob(id)}
+                                className="text-destructive"
+                              >
+                                <Trash2 className="h-4 w-4" />
+                              </Button>
+                            </TableCell>
+                          </TableRow>
+                        );
                       },
                     )}
                   </TableBody>
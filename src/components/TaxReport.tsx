@@ -0,0 +1,157 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { downloadDir, join } from "@tauri-apps/api/path";
+import { toast } from "sonner";
+import {
+  AccountType,
+  TaxReport as Report,
+  TaxReportFormat,
+  TaxSection,
+} from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { DialogHeader, DialogTitle } from "./ui/dialog";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+function SectionTable({ section }: { section: TaxSection }) {
+  if (section.lines.length === 0) {
+    return <p className="text-sm text-muted-foreground">No disposal</p>;
+  }
+
+  return (
+    <table className="w-full text-sm">
+      <thead>
+        <tr className="text-left">
+          <th>Security</th>
+          <th>Proceeds</th>
+          <th>Fees</th>
+          <th>Cost</th>
+          <th>Net gain</th>
+        </tr>
+      </thead>
+      <tbody>
+        {[...section.lines, section.total].map((line) => (
+          <tr key={line.symbol}>
+            <td>{line.symbol}</td>
+            <td>{line.proceeds.toFixed(2)}€</td>
+            <td>{line.fees.toFixed(2)}€</td>
+            <td>{line.acquisition_cost.toFixed(2)}€</td>
+            <td>{line.net_gain.toFixed(2)}€</td>
+          </tr>
+        ))}
+      </tbody>
+    </table>
+  );
+}
+
+/**
+ * Realized gains of a tax year computed with the PRU, to fill the form 2074
+ */
+export function TaxReport({ accounts }: { accounts: AccountType[] }) {
+  const [year, setYear] = useState(`${new Date().getFullYear() - 1}`);
+  const [report, setReport] = useState<Report | undefined>();
+  const [directory, setDirectory] = useState("");
+
+  useEffect(() => {
+    downloadDir().then(setDirectory);
+  }, []);
+
+  const load = async () => {
+    try {
+      setReport(await invoke("get_tax_report", { year: parseInt(year) }));
+    } catch (error) {
+      toast.error("Error computing the tax report", {
+        description: `${error}`,
+      });
+    }
+  };
+
+  useEffect(() => {
+    load();
+  }, [year]);
+
+  const accountName = (id: string) => {
+    const found = accounts.find((a) => a.id === id);
+    return found ? getFormattedAccountName(found, accounts) : id;
+  };
+
+  // The tag is kept for the closed or renamed PEA accounts
+  const tagPea = async (account: string, pea: boolean) => {
+    if (!report) return;
+    try {
+      await invoke("set_pea_accounts", {
+        accounts: pea
+          ? [...report.pea_accounts, account]
+          : report.pea_accounts.filter((a) => a !== account),
+      });
+      await load();
+    } catch (error) {
+      toast.error("Error saving the PEA accounts", {
+        description: `${error}`,
+      });
+    }
+  };
+
+  const exportReport = async (format: TaxReportFormat) => {
+    const path = await join(directory, `bourso-2074-${year}.${format}`);
+    try {
+      await invoke("export_tax_report", {
+        year: parseInt(year),
+        path,
+        format,
+      });
+      toast.success("Tax report exported", { description: path });
+    } catch (error) {
+      toast.error("Error exporting the tax report", {
+        description: `${error}`,
+      });
+    }
+  };
+
+  return (
+    <div className="space-y-4">
+      <DialogHeader>
+        <DialogTitle>Tax report</DialogTitle>
+      </DialogHeader>
+      <Input
+        type="number"
+        value={year}
+        onChange={(e) => setYear(e.target.value)}
+      />
+      {report && (
+        <>
+          <h3 className="font-semibold">PEA accounts</h3>
+          {[...new Set([...report.accounts, ...report.pea_accounts])].map(
+            (account) => (
+              <label key={account} className="flex items-center gap-2 text-sm">
+                <input
+                  type="checkbox"
+                  checked={report.pea_accounts.includes(account)}
+                  onChange={(e) => tagPea(account, e.target.checked)}
+                />
+                {accountName(account)}
+              </label>
+            ),
+          )}
+          <h3 className="font-semibold">Securities accounts</h3>
+          <SectionTable section={report.taxable} />
+          <h3 className="font-semibold">PEA</h3>
+          <SectionTable section={report.pea} />
+          {report.warnings.map((warning) => (
+            <p key={warning} className="text-sm text-yellow-600">
+              {warning}
+            </p>
+          ))}
+        </>
+      )}
+      <div className="flex gap-2">
+        <Button variant="outline" onClick={() => exportReport("csv")}>
+          Export CSV
+        </Button>
+        <Button variant="outline" onClick={() => exportReport("html")}>
+          Export HTML
+        </Button>
+      </div>
+    </div>
+  );
+}
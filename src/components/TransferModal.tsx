@@ -1,5 +1,5 @@
 import { useState, useEffect } from "react";
-import { AccountType } from "@/types";
+import { AccountType, Confirmation, EnvelopeStatus, LimitError } from "@/types";
 import {
   Dialog,
   DialogContent,
@@ -28,6 +28,11 @@ import {
 } from "@/components/ui/form";
 import { Progress } from "@/components/ui/progress";
 import { getFormattedAccountName } from "@/utils/format";
+import {
+  askConfirmation,
+  limitErrorToString,
+  parseLimitError,
+} from "@/utils/limits";
 
 // Transfer progress step descriptions
 const TRANSFER_STEPS: Record<number, string> = {
@@ -89,6 +94,7 @@ export function TransferModal({
 }: TransferModalProps) {
   const [loading, setLoading] = useState(false);
   const [transferProgress, setTransferProgress] = useState<number>(0);
+  const [envelope, setEnvelope] = useState<EnvelopeStatus | undefined>();
 
   const form = useForm<z.infer<typeof transferFormSchema>>({
     resolver: zodResolver(transferFormSchema),
@@ -106,6 +112,19 @@ export function TransferModal({
     }
   }, [open, form]);
 
+  // Contribution ceiling of the target account, if tracked
+  useEffect(() => {
+    if (!open || !targetAccount) return;
+    invoke<EnvelopeStatus[]>("get_envelopes")
+      .then((envelopes) =>
+        setEnvelope(envelopes.find((e) => e.account === targetAccount.id)),
+      )
+      .catch(() => setEnvelope(undefined));
+  }, [open, targetAccount]);
+
+  const amount = parseFloat(form.watch("amount"));
+  const aboveCeiling = envelope && amount > envelope.remaining;
+
   // Listen for transfer progress events
   useEffect(() => {
     let unlisten: (() => void) | undefined;
@@ -153,19 +172,39 @@ export function TransferModal({
     setTransferProgress(0);
     try {
 
-      await invoke("transfer_funds", {
-        sourceAccountId: sourceAccount.id,
-        targetAccountId: targetAccount.id,
-        amount: parseFloat(data.amount),
-        reason: data.reason.trim(),
-      });
+      // Resolves to the warning of a ceiling exceeded with the warn action
+      const transfer = (confirmation: Confirmation | null) =>
+        invoke<LimitError | null>("transfer_funds", {
+          sourceAccountId: sourceAccount.id,
+          targetAccountId: targetAccount.id,
+          amount: parseFloat(data.amount),
+          reason: data.reason.trim(),
+          confirmation,
+        });
+
+      let warning: LimitError | null;
+      try {
+        warning = await transfer(null);
+      } catch (error) {
+        // Transfers above a soft limit need a second confirmation or the PIN
+        const limitError = parseLimitError(error);
+        const confirmation = limitError && askConfirmation(limitError);
+        if (!confirmation) throw error;
+        warning = await transfer(confirmation);
+      }
 
       toast.success(`Transfered €${data.amount} successfully`);
+      if (warning) {
+        toast.warning(limitErrorToString(warning));
+      }
       handleClose();
       onTransferComplete?.();
     } catch (error) {
       console.error("Transfer error:", error);
-      toast.error(`Transfer failed: ${error}`);
+      const limitError = parseLimitError(error);
+      toast.error(
+        `Transfer failed: ${limitError ? limitErrorToString(limitError) : error}`,
+      );
     } finally {
       setLoading(false);
       setTransferProgress(0);
@@ -228,6 +267,18 @@ export function TransferModal({
                     <FormDescription>
                       Enter the amount to transfer (max 2 decimal places)
                     </FormDescription>
+                    {envelope && (
+                      <p
+                        className={`text-sm ${aboveCeiling ? "text-destructive" : "text-muted-foreground"}`}
+                      >
+                        {envelope.remaining.toFixed(2)}€ left before the
+                        ceiling of {envelope.ceiling.toFixed(2)}€
+                        {aboveCeiling &&
+                          (envelope.action === "block"
+                            ? ", the transfer will be refused"
+                            : ", the transfer goes over it")}
+                      </p>
+                    )}
                     <FormMessage />
                   </FormItem>
                 )}
@@ -0,0 +1,206 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { AccountType, Job, Trigger, WeeklyMonthly } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { triggerToString } from "@/utils/jobUtils";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+/**
+ * Order waiting for a market condition, e.g. a dip below the 52-week high,
+ * or a DCA scaled up while the condition holds
+ */
+export function TriggerForm({
+  accounts,
+  onCreated,
+}: {
+  accounts: AccountType[];
+  onCreated: (job: Job) => void;
+}) {
+  const tradingAccounts = accounts.filter((a) => a.kind === "Trading");
+  const [account, setAccount] = useState(tradingAccounts[0]?.id ?? "");
+  const [symbol, setSymbol] = useState("");
+  const [amount, setAmount] = useState("");
+  const [scheduleType, setScheduleType] = useState("daily");
+  const [conditionKind, setConditionKind] = useState("below_high");
+  const [percent, setPercent] = useState("10");
+  const [days, setDays] = useState("365");
+  const [actionKind, setActionKind] = useState("run_when_met");
+  const [factor, setFactor] = useState("2");
+  const [cooldown, setCooldown] = useState("7");
+
+  const create = async () => {
+    const schedule: WeeklyMonthly | "daily" =
+      scheduleType === "weekly"
+        ? { weekly: { day: 1 } }
+        : scheduleType === "monthly"
+          ? { monthly: { day: 1 } }
+          : "daily";
+    const trigger: Trigger = {
+      condition:
+        conditionKind === "below_high"
+          ? {
+              kind: "below_high",
+              percent: parseFloat(percent),
+              days: parseInt(days),
+            }
+          : { kind: "below_average", days: parseInt(days) },
+      action:
+        actionKind === "scale_when_met"
+          ? { kind: "scale_when_met", factor: parseFloat(factor) }
+          : { kind: "run_when_met" },
+      cooldown_days: cooldown ? parseInt(cooldown) : 0,
+    };
+    const job: Job = {
+      id: `${scheduleType}order_buy_${amount}_${symbol}_${conditionKind}`,
+      schedule,
+      last_run: Math.floor(Date.now() / 1000),
+      command: {
+        order: {
+          side: "buy",
+          symbol,
+          account,
+          amount: parseFloat(amount),
+          quantity: undefined,
+        },
+      },
+      trigger,
+    };
+
+    try {
+      await invoke("add_scheduled_job", { job });
+      onCreated(job);
+      toast("Conditional order scheduled", {
+        description: `${amount}€ of ${symbol}: ${triggerToString(trigger)}`,
+      });
+    } catch (error) {
+      toast.error("Error scheduling the conditional order", {
+        description: `${error}`,
+      });
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Conditional order</CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <p className="text-sm text-muted-foreground">
+          The condition is checked on every scheduler run once the order is
+          due. Either the order waits for the condition, or it runs on
+          schedule and its amount is multiplied while the condition holds.
+        </p>
+        <div className="grid grid-cols-2 gap-2">
+          <Select value={account} onValueChange={setAccount}>
+            <SelectTrigger>
+              <SelectValue placeholder="Account" />
+            </SelectTrigger>
+            <SelectContent>
+              {tradingAccounts.map((a) => (
+                <SelectItem key={a.id} value={a.id}>
+                  {getFormattedAccountName(a, accounts)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Input
+            placeholder="Symbol"
+            value={symbol}
+            onChange={(e) => setSymbol(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Amount (€)"
+            value={amount}
+            onChange={(e) => setAmount(e.target.value)}
+          />
+          <Select value={scheduleType} onValueChange={setScheduleType}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="daily">Every day</SelectItem>
+              <SelectItem value="weekly">Every week</SelectItem>
+              <SelectItem value="monthly">Every month</SelectItem>
+            </SelectContent>
+          </Select>
+          <Select value={conditionKind} onValueChange={setConditionKind}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="below_high">Below the high</SelectItem>
+              <SelectItem value="below_average">
+                Below the moving average
+              </SelectItem>
+            </SelectContent>
+          </Select>
+          {conditionKind === "below_high" ? (
+            <Input
+              type="number"
+              placeholder="Below the high (%)"
+              value={percent}
+              onChange={(e) => setPercent(e.target.value)}
+            />
+          ) : (
+            <div />
+          )}
+          <Input
+            type="number"
+            placeholder={
+              conditionKind === "below_high"
+                ? "High of the last days"
+                : "Average of the last closes"
+            }
+            value={days}
+            onChange={(e) => setDays(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Cooldown (days)"
+            value={cooldown}
+            onChange={(e) => setCooldown(e.target.value)}
+          />
+          <Select value={actionKind} onValueChange={setActionKind}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="run_when_met">
+                Wait for the condition
+              </SelectItem>
+              <SelectItem value="scale_when_met">
+                Scale the amount when met
+              </SelectItem>
+            </SelectContent>
+          </Select>
+          {actionKind === "scale_when_met" && (
+            <Input
+              type="number"
+              placeholder="Factor"
+              value={factor}
+              onChange={(e) => setFactor(e.target.value)}
+            />
+          )}
+        </div>
+        <Button
+          className="w-full"
+          onClick={create}
+          disabled={!account || !symbol || !amount || !days}
+        >
+          Schedule conditional order
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,168 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { AccountType, Job, WeeklyMonthly } from "@/types";
+import { getFormattedAccountName } from "@/utils/format";
+import { Card, CardContent, CardHeader, CardTitle } from "./ui/card";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "./ui/select";
+
+const optional = (value: string) => (value ? parseFloat(value) : undefined);
+
+/**
+ * Value averaging job: the position follows a target value growing by a
+ * fixed increment, and optionally a rate, on each period
+ */
+export function ValueAveragingForm({
+  accounts,
+  onCreated,
+}: {
+  accounts: AccountType[];
+  onCreated: (job: Job) => void;
+}) {
+  const tradingAccounts = accounts.filter((a) => a.kind === "Trading");
+  const [account, setAccount] = useState(tradingAccounts[0]?.id ?? "");
+  const [symbol, setSymbol] = useState("");
+  const [scheduleType, setScheduleType] = useState("monthly");
+  const [initialValue, setInitialValue] = useState("");
+  const [increment, setIncrement] = useState("");
+  const [growth, setGrowth] = useState("");
+  const [minAmount, setMinAmount] = useState("");
+  const [maxAmount, setMaxAmount] = useState("");
+  const [sellWhenAhead, setSellWhenAhead] = useState(false);
+
+  const create = async () => {
+    const schedule: WeeklyMonthly | "daily" =
+      scheduleType === "weekly"
+        ? { weekly: { day: 1 } }
+        : scheduleType === "monthly"
+          ? { monthly: { day: 1 } }
+          : "daily";
+    const job: Job = {
+      id: `${scheduleType}valueaveraging_${increment}_${symbol}`,
+      schedule,
+      last_run: Math.floor(Date.now() / 1000),
+      command: {
+        value_averaging: {
+          account,
+          symbol,
+          initial_value: optional(initialValue),
+          increment: parseFloat(increment),
+          growth_percent: optional(growth),
+          min_amount: optional(minAmount),
+          max_amount: optional(maxAmount),
+          sell_when_ahead: sellWhenAhead,
+        },
+      },
+    };
+
+    try {
+      await invoke("add_scheduled_job", { job });
+      onCreated(job);
+      toast("Value averaging scheduled");
+    } catch (error) {
+      toast.error("Error scheduling the value averaging", {
+        description: `${error}`,
+      });
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Value averaging</CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <p className="text-sm text-muted-foreground">
+          Each period, invest what the position needs to reach its target
+          value: more when the price went down, less or nothing when it went
+          up.
+        </p>
+        <div className="grid grid-cols-2 gap-2">
+          <Select value={account} onValueChange={setAccount}>
+            <SelectTrigger>
+              <SelectValue placeholder="Account" />
+            </SelectTrigger>
+            <SelectContent>
+              {tradingAccounts.map((a) => (
+                <SelectItem key={a.id} value={a.id}>
+                  {getFormattedAccountName(a, accounts)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Input
+            placeholder="Symbol"
+            value={symbol}
+            onChange={(e) => setSymbol(e.target.value)}
+          />
+          <Select value={scheduleType} onValueChange={setScheduleType}>
+            <SelectTrigger>
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="daily">Every day</SelectItem>
+              <SelectItem value="weekly">Every week</SelectItem>
+              <SelectItem value="monthly">Every month</SelectItem>
+            </SelectContent>
+          </Select>
+          <Input
+            type="number"
+            placeholder="Starting value (€), current value if empty"
+            value={initialValue}
+            onChange={(e) => setInitialValue(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Increment per period (€)"
+            value={increment}
+            onChange={(e) => setIncrement(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Growth per period (%)"
+            value={growth}
+            onChange={(e) => setGrowth(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Minimum per period (€)"
+            value={minAmount}
+            onChange={(e) => setMinAmount(e.target.value)}
+          />
+          <Input
+            type="number"
+            placeholder="Maximum per period (€)"
+            value={maxAmount}
+            onChange={(e) => setMaxAmount(e.target.value)}
+          />
+        </div>
+        <label className="flex items-center gap-2 text-sm">
+          <input
+            type="checkbox"
+            checked={sellWhenAhead}
+            onChange={(e) => setSellWhenAhead(e.target.checked)}
+          />
+          Sell the shares above the target when ahead, up to the maximum per
+          period
+        </label>
+        <Button
+          className="w-full"
+          onClick={create}
+          disabled={
+            !account || !symbol || !increment || (sellWhenAhead && !maxAmount)
+          }
+        >
+          Schedule value averaging
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
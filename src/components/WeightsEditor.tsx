@@ -0,0 +1,55 @@
+import { Plus, Trash2 } from "lucide-react";
+import { Weight } from "@/types";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+/**
+ * Symbols with their relative weights, e.g. the legs of a basket
+ */
+export function WeightsEditor({
+  weights,
+  onChange,
+}: {
+  weights: Weight[];
+  onChange: (weights: Weight[]) => void;
+}) {
+  const update = (index: number, weight: Partial<Weight>) =>
+    onChange(weights.map((w, i) => (i === index ? { ...w, ...weight } : w)));
+
+  return (
+    <div className="space-y-2">
+      {weights.map((weight, index) => (
+        <div key={index} className="grid grid-cols-[1fr_8rem_auto] gap-2">
+          <Input
+            placeholder="Symbol"
+            value={weight.symbol}
+            onChange={(e) => update(index, { symbol: e.target.value })}
+          />
+          <Input
+            type="number"
+            placeholder="Weight"
+            value={weight.weight}
+            onChange={(e) =>
+              update(index, { weight: parseFloat(e.target.value) })
+            }
+          />
+          <Button
+            variant="ghost"
+            size="icon"
+            className="text-destructive"
+            disabled={weights.length === 1}
+            onClick={() => onChange(weights.filter((_, i) => i !== index))}
+          >
+            <Trash2 className="h-4 w-4" />
+          </Button>
+        </div>
+      ))}
+      <Button
+        variant="outline"
+        onClick={() => onChange([...weights, { symbol: "", weight: 0 }])}
+      >
+        <Plus className="h-4 w-4" /> Add asset
+      </Button>
+    </div>
+  );
+}
@@ -47,18 +47,288 @@ export interface OrderArgs {
    * Either quantity or amount should be set
    */
   quantity: number | undefined;
-  side: string;
+  side: OrderSide;
 }
 
+export type OrderSide = "buy" | "sell";
+
+/**
+ * Structured error returned by `new_order_cmd`
+ */
+export type OrderError =
+  | { kind: "unknown_side"; side: string }
+  | { kind: "zero_quantity" }
+  | { kind: "quantity_overflow"; quantity: number }
+  | { kind: "market_closed" }
+  | { kind: "limit"; limit: LimitError }
+  | { kind: "bourso"; message: string }
+  | { kind: "storage"; message: string };
+
 export interface Order {
   id: string;
   timestamp?: number;
   price: number;
   args: OrderArgs;
+  source?: OrderSource;
+  note?: string;
+  tags?: string[];
+  /** Changes made to the record after it was saved */
+  edits?: OrderEdit[];
+  /** Scheduled job which placed the order */
+  job_id?: string;
+  /** Brokerage fees in euros */
+  fees?: number;
+  /** Bourso did not return the execution price, the price is the quote */
+  estimated_price?: boolean;
+}
+
+export type CostMethod = "pru" | "fifo";
+
+export interface Lot {
+  timestamp?: number;
+  quantity: number;
+  /** Cost of a share, fees included */
+  unit_cost: number;
+}
+
+/** Position computed from the local history of the orders */
+export interface Position {
+  account: string;
+  symbol: string;
+  quantity: number;
+  cost_basis: number;
+  average_cost: number;
+  realized_gain: number;
+  fees: number;
+  /** Dividends received after the withholding tax */
+  dividends: number;
+  lots: Lot[];
+  last_price?: number;
+  market_value?: number;
+  unrealized_gain?: number;
+  warnings: string[];
+}
+
+export type OrderSource = "app" | "dca" | "manual" | "imported";
+
+export type EventKind =
+  | { kind: "dividend"; gross: number; withholding_tax: number }
+  /** `from` shares become `to` shares */
+  | { kind: "split"; from: number; to: number }
+  | { kind: "fee"; amount: number };
+
+/** Dividend, split or fee of an account */
+export type AccountEvent = EventKind & {
+  id: string;
+  account: string;
+  /** Unset for the fees of the account */
+  symbol?: string;
+  /** In seconds */
+  timestamp: number;
+  source?: OrderSource;
+  note?: string;
+};
+
+export interface OrderEdit {
+  timestamp: number;
+  field: string;
+  from: unknown;
+  to: unknown;
+}
+
+/**
+ * Filter of the orders history, dates are YYYY-MM-DD and inclusive
+ */
+export interface OrderFilter {
+  from?: string;
+  to?: string;
+  account?: string;
+  symbol?: string;
+  side?: "buy" | "sell";
+  source?: OrderSource;
+  job_id?: string;
+}
+
+export type OrderSortField = "date" | "symbol" | "quantity" | "price" | "amount";
+
+export interface OrderQuery extends OrderFilter {
+  sort?: OrderSortField;
+  descending?: boolean;
+  /** Id of the last order of the previous page */
+  cursor?: string;
+  limit?: number;
+}
+
+export interface OrderTotals {
+  count: number;
+  bought_quantity: number;
+  sold_quantity: number;
+  invested: number;
+  proceeds: number;
+  average_price?: number;
+}
+
+export interface OrderPage {
+  orders: Order[];
+  next_cursor?: string;
+  totals: OrderTotals;
+}
+
+export type ExportFormat = "csv" | "json" | "ofx";
+
+export interface LedgerSettings {
+  format: "beancount" | "hledger";
+  /** Ledger account names by Bourso account id */
+  accounts: Record<string, string>;
+  /** Commodity names by symbol */
+  commodities: Record<string, string>;
+  gains_account: string;
+}
+
+export interface LedgerExport {
+  entries: number;
+  existing: number;
+  balances: number;
+}
+
+/**
+ * Result of an orders import
+ */
+export interface ImportSummary {
+  imported: number;
+  /** Orders already in the history */
+  duplicates: number;
+  /** Rows that could not be imported, with the reason */
+  skipped: string[];
 }
 
 export interface Command {
   order?: OrderArgs;
+  split?: SplitOrder;
+  reinvest?: Reinvestment;
+  basket?: Basket;
+  rebalance?: Rebalance;
+  value_averaging?: ValueAveraging;
+}
+
+/**
+ * Invest on each period what the position needs to follow a target value
+ */
+export interface ValueAveraging {
+  account: string;
+  symbol: string;
+  /**
+   * Value of the position the path starts from, in euros, the value of the
+   * position on the first run when unset
+   */
+  initial_value?: number;
+  /** Value added to the target on each period, in euros */
+  increment: number;
+  /** Growth of the target on each period, in percent */
+  growth_percent?: number;
+  /** Amount invested at least on each period, in euros */
+  min_amount?: number;
+  /** Amount traded at most on each period, in euros */
+  max_amount?: number;
+  /** Sell the shares above the target when the position is ahead */
+  sell_when_ahead?: boolean;
+  /** Periods already run */
+  periods?: number;
+}
+
+/** Target weights of a trading account */
+export interface Rebalance {
+  account: string;
+  targets: Weight[];
+  /** Drift from a target, in percentage points, above which to rebalance */
+  threshold: number;
+  /** Only buy the underweight assets with the new contributions */
+  buy_only?: boolean;
+  /** Amount invested on each run in buy-only mode, the cash available when unset */
+  contribution?: number;
+  /** Only notify the orders that would be placed */
+  dry_run?: boolean;
+}
+
+export interface Drift {
+  symbol: string;
+  quantity: number;
+  price: number;
+  value: number;
+  /** Current weight, in percent */
+  weight: number;
+  /** Target weight, in percent */
+  target: number;
+  /** Current weight minus the target, in percentage points */
+  drift: number;
+}
+
+export interface RebalanceOrder {
+  symbol: string;
+  side: OrderSide;
+  quantity: number;
+  price: number;
+}
+
+export interface RebalanceReport {
+  account: string;
+  total: number;
+  cash: number;
+  max_drift: number;
+  drifts: Drift[];
+  /** The sales first */
+  orders: RebalanceOrder[];
+}
+
+/** Amount invested across several securities in one run */
+export interface Basket {
+  account: string;
+  /** Total amount invested on each run, in euros */
+  amount: number;
+  legs: Weight[];
+}
+
+/** Share of an amount invested in a security */
+export interface Weight {
+  symbol: string;
+  /** Relative weight, the weights do not need to add up to 1 or 100 */
+  weight: number;
+}
+
+/**
+ * Buy securities with the cash credited to a trading account, e.g. the
+ * dividends of distributing ETFs
+ */
+export interface Reinvestment {
+  account: string;
+  /** Minimum new cash to reinvest, in euros */
+  threshold: number;
+  targets: Weight[];
+  /** Only notify the orders that would be placed */
+  dry_run?: boolean;
+  /** Cash of the account at the previous run, in euros */
+  last_cash?: number;
+  /** Time of the previous run, in seconds */
+  last_checked?: number;
+}
+
+/**
+ * Large order spread over several slices
+ */
+export interface SplitOrder {
+  account: string;
+  symbol: string;
+  side: OrderSide;
+  total_amount: number;
+  slices: number;
+  /**
+   * Minutes between two slices within a session, one slice per scheduled
+   * session when unset
+   */
+  interval_minutes?: number;
+  executed_amount?: number;
+  executed_quantity?: number;
+  executed_slices?: number;
 }
 // {schedule: {Monthly: {day: 2}}, last_run: 0, command: Object}
 export interface Job {
@@ -66,6 +336,127 @@ export interface Job {
   schedule: WeeklyMonthly | "daily";
   last_run: number;
   command: Command;
+  /**
+   * Carry the cash left unspent by amount-based orders over to the next run
+   */
+  carry_over?: boolean;
+  /**
+   * Unspent cash carried over from the previous runs, in euros
+   */
+  leftover?: number;
+  /**
+   * Account to transfer the missing cash from when the trading account
+   * cannot afford the order
+   */
+  top_up_from?: string;
+  last_outcome?: JobOutcome;
+  price_guard?: PriceGuard;
+  /** Market condition the order waits for, or scales the order with */
+  trigger?: Trigger;
+}
+
+export interface PriceGuard {
+  /** Maximum move of the last price from the reference price, in percent */
+  max_move_percent: number;
+  reference: { kind: "previous_close" } | { kind: "trailing_average"; days: number };
+  action?: "skip" | "postpone";
+  /**
+   * Maximum slippage accepted from the last price, in percent. The order is
+   * sent as a limit order at the last price plus the slippage.
+   */
+  max_slippage_percent?: number;
+}
+
+export interface Trigger {
+  condition:
+    | { kind: "below_high"; percent: number; days: number }
+    | { kind: "below_average"; days: number };
+  action?: { kind: "run_when_met" } | { kind: "scale_when_met"; factor: number };
+  /** Days after firing during which the trigger does not fire again */
+  cooldown_days?: number;
+  /** Last time the trigger fired, in seconds */
+  last_fired?: number;
+  /** Last evaluations, the most recent last */
+  evaluations?: Evaluation[];
+}
+
+/** Why the trigger did or did not fire */
+export interface Evaluation {
+  timestamp: number;
+  price?: number;
+  /** Highest close or average the price is compared with */
+  reference?: number;
+  fired: boolean;
+  reason: string;
+}
+
+export type JobOutcome =
+  | { status: "executed"; quantity: number; price: number }
+  | { status: "carried_over"; leftover: number }
+  | { status: "insufficient_funds"; required: number; available: number }
+  | {
+      status: "price_moved";
+      price: number;
+      reference: number;
+      change_percent: number;
+      postponed: boolean;
+    }
+  | { status: "beyond_limit"; price: number; limit: number }
+  | { status: "limit_exceeded"; reason: LimitError }
+  | { status: "transferred"; amount: number }
+  | { status: "planned"; quantity: number; price: number }
+  | { status: "failed"; error: string }
+  | { status: "no_new_cash"; increase: number; threshold: number }
+  | {
+      status: "reinvested";
+      cash: number;
+      legs: LegOutcome[];
+      dry_run: boolean;
+    }
+  | { status: "basket"; amount: number; legs: LegOutcome[] }
+  | { status: "balanced"; max_drift: number; threshold: number }
+  | {
+      status: "rebalanced";
+      max_drift: number;
+      legs: LegOutcome[];
+      dry_run: boolean;
+    }
+  | { status: "on_target"; target: number; value: number }
+  | { status: "not_triggered"; reason: string };
+
+/** Outcome of one of the orders of a job placing several orders */
+export interface LegOutcome {
+  symbol: string;
+  amount: number;
+  outcome: JobOutcome;
+}
+
+export type LimitError =
+  | { kind: "order_value_exceeded"; value: number; max: number }
+  | { kind: "quantity_exceeded"; symbol: string; quantity: number; max: number }
+  | { kind: "transfer_amount_exceeded"; amount: number; max: number }
+  | { kind: "ceiling_exceeded"; account: string; amount: number; remaining: number }
+  | { kind: "confirmation_required"; value: number; threshold: number; pin: boolean }
+  | { kind: "wrong_pin" };
+
+/**
+ * Confirmation sent for operations above a soft limit
+ */
+export interface Confirmation {
+  pin?: string;
+}
+
+export interface Limits {
+  max_order_value?: number;
+  max_quantity?: Record<string, number>;
+  max_transfer_amount?: number;
+  confirm_order_value?: number;
+  confirm_transfer_amount?: number;
+  /**
+   * Whether a PIN is required to confirm, the PIN itself never leaves the
+   * backend
+   */
+  pin_set?: boolean;
 }
 
 export type AccountKind = "Banking" | "Savings" | "Trading" | "Loans";
@@ -180,3 +571,196 @@ export interface UpdateAvailable {
   body: string;
   date: string;
 }
+
+export type TaxReportFormat = "csv" | "html";
+
+/** Disposals of a security during a tax year */
+export interface TaxLine {
+  symbol: string;
+  disposals: number;
+  quantity: number;
+  proceeds: number;
+  fees: number;
+  acquisition_cost: number;
+  net_gain: number;
+}
+
+export interface TaxSection {
+  lines: TaxLine[];
+  total: TaxLine;
+}
+
+/** Realized gains of a tax year, to fill the form 2074 */
+export interface TaxReport {
+  year: number;
+  taxable: TaxSection;
+  pea: TaxSection;
+  warnings: string[];
+  accounts: string[];
+  pea_accounts: string[];
+}
+
+export type EnvelopeKind = "pea" | "pea_pme" | "livret_a" | "ldds";
+
+/** Contribution ceiling tracked for an account */
+export interface Envelope {
+  account: string;
+  kind: EnvelopeKind;
+  /** Contributions made before the app recorded the transfers */
+  starting_amount: number;
+  /** Overrides the regulatory ceiling */
+  custom_ceiling?: number;
+  action: "warn" | "block";
+}
+
+export interface EnvelopeStatus extends Envelope {
+  ceiling: number;
+  contributed: number;
+  remaining: number;
+  projection: {
+    monthly_contribution: number;
+    /** Unset when the scheduled jobs do not reach the ceiling */
+    ceiling_date?: string;
+  };
+}
+
+/** Why the history and Bourso disagree on a position */
+export type DiscrepancyCause =
+  | { kind: "missing_orders" }
+  | { kind: "sold_outside" }
+  | { kind: "split"; ratio: number }
+  | { kind: "recorded_split"; event_id: string }
+  | { kind: "partial_fill"; order_id: string; quantity: number }
+  | { kind: "unsettled"; order_ids: string[] };
+
+/** Order added by hand to the history */
+export interface ManualOrder {
+  id?: string;
+  account: string;
+  symbol: string;
+  side: OrderSide;
+  quantity: number;
+  price: number;
+  timestamp: number;
+  fees?: number;
+  note?: string;
+  tags?: string[];
+}
+
+export interface Discrepancy {
+  account: string;
+  symbol: string;
+  label: string;
+  local_quantity: number;
+  bourso_quantity: number;
+  /** Shares held on Bourso minus shares in the history */
+  difference: number;
+  local_average_cost: number;
+  bourso_buying_price?: number;
+  /** Most likely first */
+  causes: DiscrepancyCause[];
+  correction?: ManualOrder;
+}
+
+export interface NetWorthPoint {
+  /** YYYY-MM-DD */
+  date: string;
+  value: number;
+}
+
+export interface NetWorthSeries {
+  id: string;
+  label: string;
+  points: NetWorthPoint[];
+}
+
+/** Net worth from the daily snapshots, in euros, loans counted as negative */
+export interface NetWorth {
+  accounts: NetWorthSeries[];
+  kinds: NetWorthSeries[];
+  total: NetWorthPoint[];
+}
+
+/** Performance over a date range, the returns are fractions */
+export interface Metrics {
+  start_date?: string;
+  end_date?: string;
+  start_value: number;
+  end_value: number;
+  net_contributions: number;
+  gain: number;
+  /** Time-weighted return, not annualized */
+  twr?: number;
+  /** Money-weighted return, annualized */
+  xirr?: number;
+  volatility?: number;
+  max_drawdown: number;
+}
+
+export interface ScopePerformance extends Metrics {
+  account?: string;
+  symbol?: string;
+  label: string;
+}
+
+export interface Performance {
+  portfolio: ScopePerformance;
+  accounts: ScopePerformance[];
+  positions: ScopePerformance[];
+}
+
+export interface BacktestPoint {
+  date: string;
+  price: number;
+  shares: number;
+  /** Money spent on shares so far */
+  invested: number;
+  value: number;
+  lump_sum_value: number;
+}
+
+export interface BacktestRun {
+  date: string;
+  price: number;
+  quantity: number;
+  leftover: number;
+}
+
+/** Replay of a DCA job over the past closes, fees excluded */
+export interface Backtest {
+  points: BacktestPoint[];
+  runs: BacktestRun[];
+  shares: number;
+  invested: number;
+  average_cost: number;
+  value: number;
+  gain: number;
+  /** The total spent invested at the close of the first day */
+  lump_sum: {
+    price: number;
+    shares: number;
+    cash: number;
+    value: number;
+    gain: number;
+  };
+}
+
+export interface BenchmarkPoint {
+  date: string;
+  net_contributions: number;
+  portfolio: number;
+  benchmark: number;
+}
+
+/** The orders of the history replayed into a benchmark */
+export interface BenchmarkComparison {
+  symbol: string;
+  points: BenchmarkPoint[];
+  portfolio: Metrics;
+  benchmark: Metrics;
+  /** Value of the portfolio minus value of the benchmark */
+  outperformance: number;
+  outperformance_twr?: number;
+  /** Securities without closes, valued at the price of their last order */
+  unpriced: string[];
+}
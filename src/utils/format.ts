@@ -1,4 +1,5 @@
-import { AccountType } from "@/types";
+import { AccountType, OrderError } from "@/types";
+import { limitErrorToString } from "./limits";
 
 /**
  * Check if incognito mode is enabled
@@ -64,3 +65,22 @@ export const getFormattedAccountName = (
       return "Account";
   }
 };
+
+export const orderErrorToString = (error: OrderError): string => {
+  switch (error.kind) {
+    case "unknown_side":
+      return `Unknown order side: ${error.side}`;
+    case "zero_quantity":
+      return "Quantity must be greater than 0";
+    case "quantity_overflow":
+      return `Quantity ${error.quantity} is too large`;
+    case "market_closed":
+      return "Market is closed";
+    case "limit":
+      return limitErrorToString(error.limit);
+    case "bourso":
+      return `Error from Bourso: ${error.message}`;
+    case "storage":
+      return `Error while saving order: ${error.message}`;
+  }
+};
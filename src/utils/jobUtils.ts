@@ -1,5 +1,18 @@
-import { AccountType, AssetData, Command, Job, OrderArgs, WeeklyMonthly } from "@/types";
+import {
+  AccountType,
+  AssetData,
+  Command,
+  Job,
+  JobOutcome,
+  LegOutcome,
+  OrderArgs,
+  SplitOrder,
+  Trigger,
+  Weight,
+  WeeklyMonthly,
+} from "@/types";
 import { findAssetDataBySymbol } from "./assetUtils";
+import { limitErrorToString } from "./limits";
 
 export function scheduleToString(schedule: WeeklyMonthly | "daily") {
   if (schedule === "daily") {
@@ -26,11 +39,102 @@ function orderAmountQuantityToString(orderArgs: OrderArgs) {
 export function commandToString(command: Command) {
   if (command.order) {
     return `Order: ${command.order.side} ${orderAmountQuantityToString(command.order)}`;
+  } else if (command.split) {
+    return `Split: ${command.split.side} ${command.split.total_amount}€ of ${command.split.symbol} in ${command.split.slices} slices`;
+  } else if (command.basket) {
+    return `Basket: ${command.basket.amount}€ in ${weightsToString(command.basket.legs)}`;
+  } else if (command.rebalance) {
+    return `Rebalance${command.rebalance.buy_only ? " (buy only)" : ""}: ${weightsToString(command.rebalance.targets)}, ${command.rebalance.threshold} points of drift${command.rebalance.dry_run ? " (dry run)" : ""}`;
+  } else if (command.value_averaging) {
+    const strategy = command.value_averaging;
+    return `Value averaging: ${strategy.symbol} +${strategy.increment}€ per period${strategy.growth_percent ? ` and ${strategy.growth_percent}%` : ""}${strategy.sell_when_ahead ? ", sells when ahead" : ""}`;
+  } else if (command.reinvest) {
+    return `Reinvest: new cash above ${command.reinvest.threshold}€ in ${weightsToString(command.reinvest.targets)}${command.reinvest.dry_run ? " (dry run)" : ""}`;
   } else {
     return "Unknown";
   }
 }
 
+export function jobOutcomeToString(outcome: JobOutcome) {
+  switch (outcome.status) {
+    case "executed":
+      return `Bought ${outcome.quantity} at ${outcome.price.toFixed(2)}€`;
+    case "carried_over":
+      return `Carried over ${outcome.leftover.toFixed(2)}€`;
+    case "insufficient_funds":
+      return `Skipped: insufficient funds (${outcome.available.toFixed(2)}€ / ${outcome.required.toFixed(2)}€)`;
+    case "price_moved":
+      return `${outcome.postponed ? "Postponed" : "Skipped"}: price moved ${outcome.change_percent.toFixed(2)}% from ${outcome.reference.toFixed(2)}€`;
+    case "beyond_limit":
+      return `Skipped: price ${outcome.price.toFixed(2)}€ beyond limit ${outcome.limit.toFixed(2)}€`;
+    case "limit_exceeded":
+      return `Not run: ${limitErrorToString(outcome.reason)}`;
+    case "transferred":
+      return `Transferred ${outcome.amount.toFixed(2)}€`;
+    case "planned":
+      return `Would buy ${outcome.quantity} at ${outcome.price.toFixed(2)}€`;
+    case "failed":
+      return `Failed: ${outcome.error}`;
+    case "no_new_cash":
+      return `No new cash (${outcome.increase.toFixed(2)}€ / ${outcome.threshold.toFixed(2)}€)`;
+    case "reinvested":
+      return `${outcome.dry_run ? "Dry run, " : ""}${outcome.cash.toFixed(2)}€ reinvested: ${legsToString(outcome.legs)}`;
+    case "basket":
+      return `${outcome.amount.toFixed(2)}€ basket: ${legsToString(outcome.legs)}`;
+    case "balanced":
+      return `Balanced: ${outcome.max_drift.toFixed(2)} points of drift / ${outcome.threshold}`;
+    case "on_target":
+      return `On target: ${outcome.value.toFixed(2)}€ / ${outcome.target.toFixed(2)}€`;
+    case "rebalanced":
+      return `${outcome.dry_run ? "Dry run, " : ""}rebalanced ${outcome.max_drift.toFixed(2)} points of drift: ${legsToString(outcome.legs)}`;
+    case "not_triggered":
+      return `Waiting: ${outcome.reason}`;
+  }
+}
+
+/**
+ * Condition and action of a trigger, e.g. "10% below the 365-day high, x2"
+ */
+export function triggerToString(trigger: Trigger) {
+  const { condition, action } = trigger;
+  const when =
+    condition.kind === "below_high"
+      ? `${condition.percent}% below the ${condition.days}-day high`
+      : `Below the ${condition.days}-day average`;
+  const scale = action?.kind === "scale_when_met" ? `, x${action.factor}` : "";
+  const cooldown = trigger.cooldown_days
+    ? `, ${trigger.cooldown_days} days cooldown`
+    : "";
+  return `${when}${scale}${cooldown}`;
+}
+
+/**
+ * Outcomes of the orders of a multi-order job, e.g. "1rTCW8: Bought 2 at 100.00€"
+ */
+export function legsToString(legs: LegOutcome[]): string {
+  return legs
+    .map((leg) => `${leg.symbol}: ${jobOutcomeToString(leg.outcome)}`)
+    .join(", ");
+}
+
+/**
+ * Progress of a split order, e.g. "2/5 slices, 400.00€ / 1000.00€"
+ */
+/**
+ * Weights of a split across securities, e.g. "1rTCW8 75%, 1rPAEEM 25%"
+ */
+export function weightsToString(weights: Weight[]) {
+  const total = weights.reduce((sum, w) => sum + w.weight, 0);
+  return weights
+    .map((w) => `${w.symbol} ${((w.weight / total) * 100).toFixed(0)}%`)
+    .join(", ");
+}
+
+export function splitProgressToString(split: SplitOrder) {
+  const executedAmount = split.executed_amount ?? 0;
+  return `${split.executed_slices ?? 0}/${split.slices} slices, ${executedAmount.toFixed(2)}€ / ${split.total_amount.toFixed(2)}€ (${split.executed_quantity ?? 0} shares)`;
+}
+
 /**
  * Calculate estimated cost of a job order
  */
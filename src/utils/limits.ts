@@ -0,0 +1,46 @@
+import { Confirmation, LimitError } from "@/types";
+
+export function limitErrorToString(error: LimitError): string {
+  switch (error.kind) {
+    case "order_value_exceeded":
+      return `Order value ${error.value.toFixed(2)}€ exceeds the limit of ${error.max.toFixed(2)}€`;
+    case "quantity_exceeded":
+      return `Quantity ${error.quantity} of ${error.symbol} exceeds the limit of ${error.max}`;
+    case "transfer_amount_exceeded":
+      return `Transfer amount ${error.amount.toFixed(2)}€ exceeds the limit of ${error.max.toFixed(2)}€`;
+    case "ceiling_exceeded":
+      return `Transfer of ${error.amount.toFixed(2)}€ exceeds the ceiling of the account, ${error.remaining.toFixed(2)}€ left`;
+    case "confirmation_required":
+      return `${error.value.toFixed(2)}€ is above ${error.threshold.toFixed(2)}€ and needs a confirmation`;
+    case "wrong_pin":
+      return "Wrong PIN";
+  }
+}
+
+/**
+ * Ask the user to confirm an operation above a soft limit
+ *
+ * @returns the confirmation to send back, or null if the user declined
+ */
+export function askConfirmation(error: LimitError): Confirmation | null {
+  if (error.kind !== "confirmation_required" && error.kind !== "wrong_pin") {
+    return null;
+  }
+
+  if (error.kind === "wrong_pin" || error.pin) {
+    const pin = window.prompt(`${limitErrorToString(error)}. Enter your PIN:`);
+    return pin ? { pin } : null;
+  }
+
+  return window.confirm(`${limitErrorToString(error)}. Continue?`) ? {} : null;
+}
+
+/**
+ * Parse the `limit:` prefixed errors returned by `transfer_funds`
+ */
+export function parseLimitError(error: unknown): LimitError | null {
+  if (typeof error !== "string" || !error.startsWith("limit:")) {
+    return null;
+  }
+  return JSON.parse(error.slice("limit:".length)) as LimitError;
+}
@@ -1,6 +1,13 @@
 import { AccountsList } from "@/components/AccountsList";
 import { AssetsChart } from "@/components/AssetsChart";
 import { Orders } from "@/components/Orders";
+import { CostBasis } from "@/components/CostBasis";
+import { Events } from "@/components/Events";
+import { Envelopes } from "@/components/Envelopes";
+import { LimitsSettings } from "@/components/LimitsSettings";
+import { NetWorth } from "@/components/NetWorth";
+import { Returns } from "@/components/Returns";
+import { Benchmark } from "@/components/Benchmark";
 import { SetupDca } from "@/components/SetupDca";
 import { PerformanceSummary } from "@/components/PerformanceSummary";
 import { Button } from "@/components/ui/button";
@@ -14,6 +21,7 @@ import {
   AccountType,
   AssetData,
   InitResponse,
+  JobOutcome,
   PositionSummary,
   TradingSummaryItem,
 } from "@/types";
@@ -24,7 +32,8 @@ import { X } from "lucide-react";
 import { useEffect, useRef, useState } from "react";
 import { toast } from "sonner";
 import { Toaster } from "@/components/ui/sonner";
-import { jobDescriptionInfos } from "@/utils/jobUtils";
+import { jobDescriptionInfos, jobOutcomeToString } from "@/utils/jobUtils";
+import { askConfirmation } from "@/utils/limits";
 import { isDevMode, isSimulateCron, mockJobs } from "@/lib/mockData";
 
 export function Dashboard({
@@ -124,7 +133,26 @@ export function Dashboard({
               label: "Run",
               onClick: async () => {
                 try {
-                  await invoke("run_job_manually", { job });
+                  let outcome: JobOutcome | null = await invoke(
+                    "run_job_manually",
+                    { job },
+                  );
+                  // Jobs above a soft limit need a second confirmation or the PIN
+                  while (outcome?.status === "limit_exceeded") {
+                    const confirmation = askConfirmation(outcome.reason);
+                    if (confirmation === null) break;
+                    outcome = await invoke("run_job_manually", {
+                      job,
+                      confirmation,
+                    });
+                  }
+                  if (outcome && outcome.status !== "executed") {
+                    toast.warning(`Job ${job.id} was not executed`, {
+                      description: jobOutcomeToString(outcome),
+                      duration: 30000,
+                    });
+                    return;
+                  }
                   toast.success(`Job ${job.id} executed successfully`, {
                     duration: 30000,
                   });
@@ -291,6 +319,16 @@ export function Dashboard({
               assetsData={assetsData}
               jobsExecuted={jobsExecuted}
             />
+            <CostBasis accounts={accounts} jobsExecuted={jobsExecuted} />
+            <Events
+              accounts={accounts}
+              onChanged={() => setJobsExecuted((prev) => prev + 1)}
+            />
+            <Envelopes accounts={accounts} jobsExecuted={jobsExecuted} />
+            <LimitsSettings />
+            <NetWorth jobsExecuted={jobsExecuted} />
+            <Returns accounts={accounts} jobsExecuted={jobsExecuted} />
+            <Benchmark />
           </div>
         </div>
       </Dialog>